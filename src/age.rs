@@ -0,0 +1,57 @@
+//! Tracking of the age of the input data
+
+use crate::Error;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Age of the input data in ticks counted by the user.
+pub(crate) struct UpdateAge {
+    /// Ticks since the last update, `None` if not updated yet.
+    age: Option<u32>,
+
+    /// Maximum age before the data is considered stale, if checked.
+    max_age: Option<u32>,
+}
+
+impl UpdateAge {
+    /// Creates a new age for data not updated yet.
+    pub(crate) const fn new() -> Self {
+        Self {
+            age: None,
+            max_age: None,
+        }
+    }
+
+    /// Returns the ticks since the last update.
+    pub(crate) fn age(&self) -> Option<u32> {
+        self.age
+    }
+
+    /// Sets the maximum age, `None` disables the check.
+    pub(crate) fn set_max_age(&mut self, max_age: Option<u32>) {
+        self.max_age = max_age;
+    }
+
+    /// Advances the age by one tick.
+    pub(crate) fn tick(&mut self) {
+        self.age = self.age.map(|age| age.saturating_add(1));
+    }
+
+    /// Resets the age after an update.
+    pub(crate) fn reset(&mut self) {
+        self.age = Some(0);
+    }
+
+    /// Checks the age against the maximum age.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        let Some(max_age) = self.max_age else {
+            return Ok(());
+        };
+
+        match self.age {
+            None => Err(Error::NotUpdated),
+            Some(age) if age > max_age => Err(Error::Stale),
+            Some(_) => Ok(()),
+        }
+    }
+}
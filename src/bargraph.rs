@@ -0,0 +1,177 @@
+//! Bar graph displays on output pins
+//!
+//! A [`BarGraph`] maps a value onto a contiguous range of output pins, e.g.
+//! a 10-segment LED bar driven by two 74HC595, as a filled bar or a single
+//! dot. Optionally, the peak of the values is held as a dot for a number of
+//! ticks.
+
+use crate::{output::SetOutput, Error, Length};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Display mode of a bar graph.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BarMode {
+    /// All segments up to the value are lit.
+    #[default]
+    Fill,
+
+    /// Only the segment at the value is lit.
+    Dot,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Bar graph on consecutive output pins, the first pin being the lowest
+/// segment.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BarGraph {
+    /// Pin of the lowest segment.
+    start_pin: usize,
+
+    /// Number of segments.
+    len: usize,
+
+    /// Value lighting all segments.
+    max: u32,
+
+    /// Display mode.
+    mode: BarMode,
+
+    /// Ticks the peak is held, `None` if disabled.
+    peak_hold: Option<u32>,
+
+    /// Lit segments at the peak.
+    peak: usize,
+
+    /// Ticks until the peak falls back to the current value.
+    peak_remaining: u32,
+
+    /// Lit segments at the current value.
+    segments: usize,
+}
+
+impl BarGraph {
+    /// Creates a new bar graph with `len` segments on the pins starting at
+    /// `start_pin` of `chain`, lighting all segments at `max`.
+    ///
+    /// Returns an error if the pins exceed the chain or `len` or `max` is 0.
+    pub fn new(chain: &impl Length, start_pin: usize, len: usize, max: u32) -> Result<Self, Error> {
+        if len == 0 || max == 0 {
+            return Err(Error::InvalidRange);
+        }
+
+        match start_pin.checked_add(len) {
+            Some(end) if end <= chain.output_len() * 8 => {}
+            _ => return Err(Error::PinOutOfRange),
+        }
+
+        Ok(Self {
+            start_pin,
+            len,
+            max,
+            mode: BarMode::Fill,
+            peak_hold: None,
+            peak: 0,
+            peak_remaining: 0,
+            segments: 0,
+        })
+    }
+
+    /// Returns the display mode.
+    pub fn mode(&self) -> BarMode {
+        self.mode
+    }
+
+    /// Sets the display mode, `BarMode::Fill` by default.
+    pub fn set_mode(&mut self, mode: BarMode) {
+        self.mode = mode;
+    }
+
+    /// Returns the ticks the peak is held, `None` if disabled.
+    pub fn peak_hold(&self) -> Option<u32> {
+        self.peak_hold
+    }
+
+    /// Sets the ticks the peak is held as a dot above the bar, `None` by
+    /// default which disables the peak.
+    pub fn set_peak_hold(&mut self, ticks: Option<u32>) {
+        self.peak_hold = ticks;
+        self.peak = self.segments;
+        self.peak_remaining = 0;
+    }
+
+    /// Returns the number of lit segments at the current value.
+    pub fn segments(&self) -> usize {
+        self.segments
+    }
+
+    /// Returns the number of lit segments at the held peak.
+    pub fn peak(&self) -> usize {
+        self.peak
+    }
+
+    /// Sets the value, clamped to the maximum.
+    ///
+    /// The segments are rounded to the nearest one, so values below half a
+    /// segment light none. A higher number of segments restarts the peak
+    /// hold.
+    pub fn set_value(&mut self, value: u32) {
+        let value = u64::from(value.min(self.max));
+        let max = u64::from(self.max);
+        let segments = (value * self.len as u64 + max / 2) / max;
+
+        self.segments = usize::try_from(segments).unwrap_or(self.len);
+
+        if self.segments >= self.peak {
+            self.peak = self.segments;
+
+            if let Some(ticks) = self.peak_hold {
+                self.peak_remaining = ticks;
+            }
+        }
+    }
+
+    /// Advances the peak hold by one tick, dropping the peak to the current
+    /// value once the hold ended.
+    pub fn tick(&mut self) {
+        if self.peak_remaining > 0 {
+            self.peak_remaining -= 1;
+        } else {
+            self.peak = self.segments;
+        }
+    }
+
+    /// Returns `true` if a segment is lit, `false` if out of range.
+    pub fn is_lit(&self, segment: usize) -> bool {
+        let lit = match self.mode {
+            BarMode::Fill => segment < self.segments,
+            BarMode::Dot => segment + 1 == self.segments,
+        };
+
+        let peak = self.peak_hold.is_some() && segment + 1 == self.peak;
+
+        segment < self.len && (lit || peak)
+    }
+
+    /// Writes the segments into the chain buffer.
+    ///
+    /// Returns an error if the pins exceed the chain.
+    pub fn write(&self, chain: &mut (impl SetOutput + ?Sized)) -> Result<(), Error> {
+        for segment in 0..self.len {
+            chain.set_output(self.start_pin + segment, self.is_lit(segment))?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the value and writes the segments into the chain buffer.
+    ///
+    /// Returns an error if the pins exceed the chain.
+    pub fn show(&mut self, chain: &mut (impl SetOutput + ?Sized), value: u32) -> Result<(), Error> {
+        self.set_value(value);
+        self.write(chain)
+    }
+}
@@ -0,0 +1,361 @@
+//! Builder configuring chains before the pins are consumed
+
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+};
+
+use crate::{
+    inout, input,
+    input::Sampling,
+    output,
+    transport::{ClockConfig, LatchPolarity},
+    BitOrder, ChipOrder,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Builder collecting the configuration of a chain.
+///
+/// The options not applying to a chain, e.g. the clock configuration for an
+/// SPI chain or the input inversion for an output chain, are ignored when
+/// building it. Optional pins are attached to the built chain, e.g. with
+/// `with_oe_pin()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainBuilder<const CHAIN_LENGTH: usize> {
+    /// Order in which the bits of each chip are shifted.
+    bit_order: BitOrder,
+
+    /// Order in which the chips are numbered.
+    chip_order: ChipOrder,
+
+    /// Mask of the inputs inverted when shifted in.
+    input_inversion: [u8; CHAIN_LENGTH],
+
+    /// Mask of the outputs inverted at shift time.
+    output_inversion: [u8; CHAIN_LENGTH],
+
+    /// Sampling mode of the inputs.
+    sampling: Sampling,
+
+    /// Maximum age of the input data in ticks.
+    max_age: Option<u32>,
+
+    /// Clock configuration of the bit-banged transports.
+    clock: ClockConfig,
+
+    /// Polarity of the latch or load signal.
+    latch_polarity: LatchPolarity,
+
+    /// Settle time of the bit-banged transports with a delay.
+    settle_ns: u32,
+}
+
+impl<const CHAIN_LENGTH: usize> ChainBuilder<CHAIN_LENGTH> {
+    /// Creates a new builder with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            bit_order: BitOrder::default(),
+            chip_order: ChipOrder::default(),
+            input_inversion: [0; CHAIN_LENGTH],
+            output_inversion: [0; CHAIN_LENGTH],
+            sampling: Sampling::default(),
+            max_age: None,
+            clock: ClockConfig::default(),
+            latch_polarity: LatchPolarity::default(),
+            settle_ns: 0,
+        }
+    }
+
+    /// Sets the order in which the bits of each chip are shifted.
+    pub fn bit_order(mut self, bit_order: BitOrder) -> Self {
+        self.bit_order = bit_order;
+        self
+    }
+
+    /// Sets the order in which the chips are numbered.
+    pub fn chip_order(mut self, chip_order: ChipOrder) -> Self {
+        self.chip_order = chip_order;
+        self
+    }
+
+    /// Sets the mask of the inputs inverted when shifted in, see
+    /// `set_input_inversion()` of the chains.
+    pub fn input_inversion(mut self, mask: [u8; CHAIN_LENGTH]) -> Self {
+        self.input_inversion = mask;
+        self
+    }
+
+    /// Sets the mask of the outputs inverted at shift time, see
+    /// `set_output_inversion()` of the chains.
+    pub fn output_inversion(mut self, mask: [u8; CHAIN_LENGTH]) -> Self {
+        self.output_inversion = mask;
+        self
+    }
+
+    /// Sets the sampling mode of the inputs of the input chains.
+    pub fn sampling(mut self, sampling: Sampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Sets the maximum age of the input data in ticks, `None` disables
+    /// the check.
+    pub fn max_age(mut self, max_age: Option<u32>) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Sets the clock configuration of the bit-banged transports.
+    pub fn clock_config(mut self, clock: ClockConfig) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the polarity of the latch or load signal.
+    pub fn latch_polarity(mut self, polarity: LatchPolarity) -> Self {
+        self.latch_polarity = polarity;
+        self
+    }
+
+    /// Sets the time in nanoseconds waited around each signal change by the
+    /// chains built with a delay.
+    pub fn settle_ns(mut self, settle_ns: u32) -> Self {
+        self.settle_ns = settle_ns;
+        self
+    }
+
+    /// Builds a bit-banged input chain by consuming the pins.
+    pub fn input_chain<ClockPin, LatchPin, DataPin>(
+        &self,
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_pin: DataPin,
+    ) -> input::BitBangChain<ClockPin, LatchPin, DataPin, CHAIN_LENGTH>
+    where
+        ClockPin: OutputPin,
+        LatchPin: OutputPin,
+        DataPin: InputPin,
+    {
+        let mut chain = input::BitBangChain::new(clock_pin, latch_pin, data_pin);
+        chain.set_clock_config(self.clock);
+        chain.set_latch_polarity(self.latch_polarity);
+        self.configure_input(&mut chain);
+        chain
+    }
+
+    /// Builds a bit-banged input chain by consuming the pins and a delay
+    /// waiting the settle time around each signal change.
+    pub fn input_chain_with_delay<ClockPin, LatchPin, DataPin, Delay>(
+        &self,
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_pin: DataPin,
+        delay: Delay,
+    ) -> input::Chain<input::BitBangTransport<ClockPin, LatchPin, DataPin, Delay>, CHAIN_LENGTH>
+    where
+        ClockPin: OutputPin,
+        LatchPin: OutputPin,
+        DataPin: InputPin,
+        Delay: DelayNs,
+    {
+        let mut chain =
+            input::Chain::<input::BitBangTransport<_, _, _, Delay>, CHAIN_LENGTH>::with_delay(
+                clock_pin,
+                latch_pin,
+                data_pin,
+                delay,
+                self.settle_ns,
+            );
+        chain.set_clock_config(self.clock);
+        chain.set_latch_polarity(self.latch_polarity);
+        self.configure_input(&mut chain);
+        chain
+    }
+
+    /// Builds an SPI input chain by consuming the bus and the load pin.
+    pub fn input_spi_chain<Spi, LoadPin>(
+        &self,
+        spi: Spi,
+        load_pin: LoadPin,
+    ) -> input::SpiChain<Spi, LoadPin, CHAIN_LENGTH>
+    where
+        LoadPin: OutputPin,
+    {
+        let mut chain = input::SpiChain::new(spi, load_pin);
+        chain.set_latch_polarity(self.latch_polarity);
+        self.configure_input(&mut chain);
+        chain
+    }
+
+    /// Builds a bit-banged output chain by consuming the pins.
+    pub fn output_chain<ClockPin, LatchPin, DataPin>(
+        &self,
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_pin: DataPin,
+    ) -> output::BitBangChain<ClockPin, LatchPin, DataPin, CHAIN_LENGTH>
+    where
+        ClockPin: OutputPin,
+        LatchPin: OutputPin,
+        DataPin: OutputPin,
+    {
+        let mut chain = output::BitBangChain::new(clock_pin, latch_pin, data_pin);
+        chain.set_clock_config(self.clock);
+        chain.set_latch_polarity(self.latch_polarity);
+        self.configure_output(&mut chain);
+        chain
+    }
+
+    /// Builds a bit-banged output chain by consuming the pins and a delay
+    /// waiting the settle time around each signal change.
+    pub fn output_chain_with_delay<ClockPin, LatchPin, DataPin, Delay>(
+        &self,
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_pin: DataPin,
+        delay: Delay,
+    ) -> output::Chain<output::BitBangTransport<ClockPin, LatchPin, DataPin, Delay>, CHAIN_LENGTH>
+    where
+        ClockPin: OutputPin,
+        LatchPin: OutputPin,
+        DataPin: OutputPin,
+        Delay: DelayNs,
+    {
+        let mut chain =
+            output::Chain::<output::BitBangTransport<_, _, _, Delay>, CHAIN_LENGTH>::with_delay(
+                clock_pin,
+                latch_pin,
+                data_pin,
+                delay,
+                self.settle_ns,
+            );
+        chain.set_clock_config(self.clock);
+        chain.set_latch_polarity(self.latch_polarity);
+        self.configure_output(&mut chain);
+        chain
+    }
+
+    /// Builds an SPI output chain by consuming the bus and the latch pin.
+    pub fn output_spi_chain<Spi, LatchPin>(
+        &self,
+        spi: Spi,
+        latch_pin: LatchPin,
+    ) -> output::SpiChain<Spi, LatchPin, CHAIN_LENGTH>
+    where
+        LatchPin: OutputPin,
+    {
+        let mut chain = output::SpiChain::new(spi, latch_pin);
+        chain.set_latch_polarity(self.latch_polarity);
+        self.configure_output(&mut chain);
+        chain
+    }
+
+    /// Builds a bit-banged dual chain by consuming the pins.
+    pub fn dual_chain<ClockPin, LatchPin, DataInPin, DataOutPin>(
+        &self,
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_in_pin: DataInPin,
+        data_out_pin: DataOutPin,
+    ) -> inout::BitBangDualChain<ClockPin, LatchPin, DataInPin, DataOutPin, CHAIN_LENGTH>
+    where
+        ClockPin: OutputPin,
+        LatchPin: OutputPin,
+        DataInPin: InputPin,
+        DataOutPin: OutputPin,
+    {
+        let mut chain =
+            inout::BitBangDualChain::new(clock_pin, latch_pin, data_in_pin, data_out_pin);
+        chain.set_clock_config(self.clock);
+        chain.set_latch_polarity(self.latch_polarity);
+        self.configure_dual(&mut chain);
+        chain
+    }
+
+    /// Builds a bit-banged dual chain by consuming the pins and a delay
+    /// waiting the settle time around each signal change.
+    pub fn dual_chain_with_delay<ClockPin, LatchPin, DataInPin, DataOutPin, Delay>(
+        &self,
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_in_pin: DataInPin,
+        data_out_pin: DataOutPin,
+        delay: Delay,
+    ) -> inout::DualChain<
+        inout::BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, Delay>,
+        CHAIN_LENGTH,
+    >
+    where
+        ClockPin: OutputPin,
+        LatchPin: OutputPin,
+        DataInPin: InputPin,
+        DataOutPin: OutputPin,
+        Delay: DelayNs,
+    {
+        let mut chain = inout::DualChain::<inout::BitBangTransport<_, _, _, _, Delay>, CHAIN_LENGTH>::with_delay(
+            clock_pin,
+            latch_pin,
+            data_in_pin,
+            data_out_pin,
+            delay,
+            self.settle_ns,
+        );
+        chain.set_clock_config(self.clock);
+        chain.set_latch_polarity(self.latch_polarity);
+        self.configure_dual(&mut chain);
+        chain
+    }
+
+    /// Builds an SPI dual chain by consuming the bus and the latch pin.
+    pub fn dual_spi_chain<Spi, LatchPin>(
+        &self,
+        spi: Spi,
+        latch_pin: LatchPin,
+    ) -> inout::DualSpiChain<Spi, LatchPin, CHAIN_LENGTH>
+    where
+        LatchPin: OutputPin,
+    {
+        let mut chain = inout::DualSpiChain::new(spi, latch_pin);
+        chain.set_latch_polarity(self.latch_polarity);
+        self.configure_dual(&mut chain);
+        chain
+    }
+
+    /// Applies the options of the input chains.
+    fn configure_input<Transport>(&self, chain: &mut input::Chain<Transport, CHAIN_LENGTH>) {
+        chain.set_bit_order(self.bit_order);
+        chain.set_chip_order(self.chip_order);
+        chain.set_sampling(self.sampling);
+        chain.set_max_age(self.max_age);
+
+        // The mask has the length of the chain, so this can't fail.
+        chain.set_input_inversion(&self.input_inversion).ok();
+    }
+
+    /// Applies the options of the output chains.
+    fn configure_output<Transport>(&self, chain: &mut output::Chain<Transport, CHAIN_LENGTH>) {
+        chain.set_bit_order(self.bit_order);
+        chain.set_chip_order(self.chip_order);
+
+        // The mask has the length of the chain, so this can't fail.
+        chain.set_output_inversion(&self.output_inversion).ok();
+    }
+
+    /// Applies the options of the dual chains.
+    fn configure_dual<Transport>(&self, chain: &mut inout::DualChain<Transport, CHAIN_LENGTH>) {
+        chain.set_bit_order(self.bit_order);
+        chain.set_chip_order(self.chip_order);
+        chain.set_max_age(self.max_age);
+
+        // The masks have the length of the chain, so this can't fail.
+        chain.set_input_inversion(&self.input_inversion).ok();
+        chain.set_output_inversion(&self.output_inversion).ok();
+    }
+}
+
+impl<const CHAIN_LENGTH: usize> Default for ChainBuilder<CHAIN_LENGTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,66 @@
+//! Clock sources for timestamps and durations
+//!
+//! The event and debounce subsystems read the time via the [`Now`] trait as
+//! a wrapping tick count. Its unit is up to the implementation, e.g. a
+//! SysTick counter, `embassy_time::Instant::now().as_ticks()` via a closure
+//! or a [`ManualClock`] in tests.
+
+use core::cell::Cell;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Trait to be implemented by clock sources.
+pub trait Now {
+    /// Returns the current time in ticks, wrapping on overflow.
+    fn now(&self) -> u32;
+
+    /// Returns the ticks elapsed since `timestamp`, correct across one
+    /// wrap of the counter.
+    fn elapsed_since(&self, timestamp: u32) -> u32 {
+        self.now().wrapping_sub(timestamp)
+    }
+}
+
+/// Closures returning the tick count, e.g. `|| read_systick()`.
+impl<F> Now for F
+where
+    F: Fn() -> u32,
+{
+    fn now(&self) -> u32 {
+        self()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Clock advanced manually, e.g. from a timer interrupt or in tests.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    /// Current time in ticks.
+    ticks: Cell<u32>,
+}
+
+impl ManualClock {
+    /// Creates a new clock starting at `ticks`.
+    pub const fn new(ticks: u32) -> Self {
+        Self {
+            ticks: Cell::new(ticks),
+        }
+    }
+
+    /// Advances the clock by `ticks`, wrapping on overflow.
+    pub fn advance(&self, ticks: u32) {
+        self.ticks.set(self.ticks.get().wrapping_add(ticks));
+    }
+
+    /// Sets the current time.
+    pub fn set(&self, ticks: u32) {
+        self.ticks.set(ticks);
+    }
+}
+
+impl Now for ManualClock {
+    fn now(&self) -> u32 {
+        self.ticks.get()
+    }
+}
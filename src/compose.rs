@@ -0,0 +1,310 @@
+//! Composition of several chains into one logical pin space
+//!
+//! A [`Compose`] wraps two chains and numbers their pins consecutively, so
+//! physically separate chains can be addressed as one contiguous I/O map.
+//! More chains are combined by nesting, e.g. `Compose<Compose<A, B>, C>`.
+
+use core::fmt;
+
+#[cfg(feature = "async")]
+use crate::AsyncUpdate;
+use crate::{
+    input::GetInput,
+    output::{GetOutput, SetOutput},
+    Error, Length, Update,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Error of an update of a composed chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum UpdateError<FirstE, SecondE> {
+    /// Error of the first chain.
+    First(FirstE),
+
+    /// Error of the second chain.
+    Second(SecondE),
+}
+
+impl<FirstE, SecondE> fmt::Display for UpdateError<FirstE, SecondE>
+where
+    FirstE: fmt::Display,
+    SecondE: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::First(error) => write!(f, "first chain error: {error}"),
+            Self::Second(error) => write!(f, "second chain error: {error}"),
+        }
+    }
+}
+
+impl<FirstE, SecondE> core::error::Error for UpdateError<FirstE, SecondE>
+where
+    FirstE: core::error::Error + 'static,
+    SecondE: core::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::First(error) => Some(error),
+            Self::Second(error) => Some(error),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Two chains addressed as one.
+///
+/// The pins of the first chain come first, followed by the pins of the
+/// second chain, so input pin `first.input_len() * 8` is input pin 0 of the
+/// second chain, and likewise for the outputs. The lengths are the sums of
+/// both lengths.
+pub struct Compose<First, Second> {
+    /// Chain holding the lower pins.
+    first: First,
+
+    /// Chain holding the upper pins.
+    second: Second,
+}
+
+impl<First, Second> Compose<First, Second> {
+    /// Creates a new composed chain.
+    pub fn new(first: First, second: Second) -> Self {
+        Self { first, second }
+    }
+
+    /// Returns the chain holding the lower pins.
+    pub fn first(&self) -> &First {
+        &self.first
+    }
+
+    /// Returns the chain holding the lower pins mutably, e.g. to update it.
+    pub fn first_mut(&mut self) -> &mut First {
+        &mut self.first
+    }
+
+    /// Returns the chain holding the upper pins.
+    pub fn second(&self) -> &Second {
+        &self.second
+    }
+
+    /// Returns the chain holding the upper pins mutably, e.g. to update it.
+    pub fn second_mut(&mut self) -> &mut Second {
+        &mut self.second
+    }
+
+    /// Frees both chains.
+    pub fn into_parts(self) -> (First, Second) {
+        (self.first, self.second)
+    }
+}
+
+impl<First, Second> Compose<First, Second>
+where
+    First: Length,
+{
+    /// Returns the number of input pins of the first chain, which is the
+    /// offset of the input pins of the second chain.
+    fn input_offset(&self) -> usize {
+        self.first.input_len() * 8
+    }
+
+    /// Returns the number of output pins of the first chain, which is the
+    /// offset of the output pins of the second chain.
+    fn output_offset(&self) -> usize {
+        self.first.output_len() * 8
+    }
+}
+
+impl<First, Second> GetInput for Compose<First, Second>
+where
+    First: GetInput + Length,
+    Second: GetInput + Length,
+{
+    fn get_input(&self, pin: usize) -> Result<bool, Error> {
+        match pin.checked_sub(self.input_offset()) {
+            None => self.first.get_input(pin),
+            Some(pin) => self.second.get_input(pin),
+        }
+    }
+
+    fn get_input_unchecked(&self, pin: usize) -> bool {
+        match pin.checked_sub(self.input_offset()) {
+            None => self.first.get_input_unchecked(pin),
+            Some(pin) => self.second.get_input_unchecked(pin),
+        }
+    }
+
+    fn read_inputs(&self, data: &mut [u8]) -> Result<(), Error> {
+        if data.len() > self.input_len() {
+            return Err(Error::InvalidRange);
+        }
+
+        let (first, second) = data.split_at_mut(data.len().min(self.first.input_len()));
+
+        self.first.read_inputs(first)?;
+
+        if !second.is_empty() {
+            self.second.read_inputs(second)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_inputs(&self) -> Result<(), Error> {
+        self.first.check_inputs()?;
+        self.second.check_inputs()
+    }
+}
+
+impl<First, Second> SetOutput for Compose<First, Second>
+where
+    First: SetOutput + Length,
+    Second: SetOutput + Length,
+{
+    fn set_output(&mut self, pin: usize, state: bool) -> Result<(), Error> {
+        match pin.checked_sub(self.output_offset()) {
+            None => self.first.set_output(pin, state),
+            Some(pin) => self.second.set_output(pin, state),
+        }
+    }
+
+    fn set_output_unchecked(&mut self, pin: usize, state: bool) {
+        match pin.checked_sub(self.output_offset()) {
+            None => self.first.set_output_unchecked(pin, state),
+            Some(pin) => self.second.set_output_unchecked(pin, state),
+        }
+    }
+
+    fn set_all_outputs(&mut self) {
+        self.first.set_all_outputs();
+        self.second.set_all_outputs();
+    }
+
+    fn clear_all_outputs(&mut self) {
+        self.first.clear_all_outputs();
+        self.second.clear_all_outputs();
+    }
+
+    /// Copies `data` into the output buffers, continuing with the second
+    /// chain after the first one.
+    fn write_outputs(&mut self, data: &[u8]) -> Result<(), Error> {
+        if data.len() > self.output_len() {
+            return Err(Error::InvalidRange);
+        }
+
+        let (first, second) = data.split_at(data.len().min(self.first.output_len()));
+
+        self.first.write_outputs(first)?;
+
+        if !second.is_empty() {
+            self.second.write_outputs(second)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<First, Second> GetOutput for Compose<First, Second>
+where
+    First: GetOutput + Length,
+    Second: GetOutput + Length,
+{
+    fn get_output(&self, pin: usize) -> Result<bool, Error> {
+        match pin.checked_sub(self.output_offset()) {
+            None => self.first.get_output(pin),
+            Some(pin) => self.second.get_output(pin),
+        }
+    }
+
+    fn get_output_unchecked(&self, pin: usize) -> bool {
+        match pin.checked_sub(self.output_offset()) {
+            None => self.first.get_output_unchecked(pin),
+            Some(pin) => self.second.get_output_unchecked(pin),
+        }
+    }
+}
+
+/// Updates the first chain, then the second one.
+///
+/// The second chain is not updated if the update of the first one failed.
+impl<First, Second> Update for Compose<First, Second>
+where
+    First: Update,
+    Second: Update,
+{
+    type Error = UpdateError<First::Error, Second::Error>;
+
+    fn update(&mut self) -> Result<(), Self::Error> {
+        self.first.update().map_err(UpdateError::First)?;
+        self.second.update().map_err(UpdateError::Second)
+    }
+}
+
+/// Updates the first chain, then the second one, asynchronously.
+///
+/// The second chain is not updated if the update of the first one failed.
+#[cfg(feature = "async")]
+impl<First, Second> AsyncUpdate for Compose<First, Second>
+where
+    First: AsyncUpdate,
+    Second: AsyncUpdate,
+{
+    type Error = UpdateError<First::Error, Second::Error>;
+
+    async fn update_async(&mut self) -> Result<(), Self::Error> {
+        self.first
+            .update_async()
+            .await
+            .map_err(UpdateError::First)?;
+        self.second
+            .update_async()
+            .await
+            .map_err(UpdateError::Second)
+    }
+}
+
+impl<First, Second> Length for Compose<First, Second>
+where
+    First: Length,
+    Second: Length,
+{
+    /// Returns the sum of both chain lengths.
+    fn len(&self) -> usize {
+        self.first.len() + self.second.len()
+    }
+
+    /// Returns the sum of both input lengths.
+    fn input_len(&self) -> usize {
+        self.first.input_len() + self.second.input_len()
+    }
+
+    /// Returns the sum of both output lengths.
+    fn output_len(&self) -> usize {
+        self.first.output_len() + self.second.output_len()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inout::DualChain, mock::Recorder};
+
+    #[test]
+    fn offsets_per_direction() {
+        let first = DualChain::<_, 2, 1>::with_transport(Recorder::default());
+        let second = DualChain::<_, 1, 2>::with_transport(Recorder::default());
+        let mut chain = Compose::new(first, second);
+
+        assert_eq!((chain.input_len(), chain.output_len()), (3, 3));
+        assert_eq!(chain.set_output(8, true), Ok(()));
+        assert_eq!(chain.second().get_output(0), Ok(true));
+        assert_eq!(chain.set_output(24, true), Err(Error::PinOutOfRange));
+        assert_eq!(chain.get_input(16), Ok(false));
+        assert_eq!(chain.get_input(24), Err(Error::PinOutOfRange));
+    }
+}
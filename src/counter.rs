@@ -0,0 +1,105 @@
+//! Counting of the edges of input pins
+//!
+//! [`EdgeCounters`] count the rising and falling edges of each pin across
+//! scans, e.g. the pulses of a flow meter or the actuations of a tally
+//! switch, without the need of an event queue.
+
+use crate::{input::GetInput, Error};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Rising and falling edge counters of the pins of the first `CHAIN_LENGTH`
+/// chips.
+///
+/// The counters wrap on overflow.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EdgeCounters<const CHAIN_LENGTH: usize> {
+    /// States at the previous scan, `None` before the first one.
+    states: Option<[u8; CHAIN_LENGTH]>,
+
+    /// Rising edges per pin.
+    rising: [[u32; 8]; CHAIN_LENGTH],
+
+    /// Falling edges per pin.
+    falling: [[u32; 8]; CHAIN_LENGTH],
+}
+
+impl<const CHAIN_LENGTH: usize> EdgeCounters<CHAIN_LENGTH> {
+    /// Creates new counters, all 0.
+    pub const fn new() -> Self {
+        Self {
+            states: None,
+            rising: [[0; 8]; CHAIN_LENGTH],
+            falling: [[0; 8]; CHAIN_LENGTH],
+        }
+    }
+
+    /// Reads the inputs from `chain` and counts the edges since the previous
+    /// scan.
+    ///
+    /// Intended to be called after each update of the chain. The first scan
+    /// only captures the states. Returns an error if the chain is shorter
+    /// than `CHAIN_LENGTH` or the inputs are not valid.
+    pub fn scan<Chain>(&mut self, chain: &Chain) -> Result<(), Error>
+    where
+        Chain: GetInput + ?Sized,
+    {
+        let mut states = [0; CHAIN_LENGTH];
+        chain.read_inputs(&mut states)?;
+
+        if let Some(previous) = self.states.replace(states) {
+            for (index, (state, previous)) in states.into_iter().zip(previous).enumerate() {
+                for bit in 0..8 {
+                    let mask = 1 << bit;
+
+                    if state & !previous & mask != 0 {
+                        self.rising[index][bit] = self.rising[index][bit].wrapping_add(1);
+                    } else if !state & previous & mask != 0 {
+                        self.falling[index][bit] = self.falling[index][bit].wrapping_add(1);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the rising edges of a pin or `None` if out of range.
+    pub fn rising(&self, pin: usize) -> Option<u32> {
+        self.rising.get(pin / 8).map(|counters| counters[pin % 8])
+    }
+
+    /// Returns the falling edges of a pin or `None` if out of range.
+    pub fn falling(&self, pin: usize) -> Option<u32> {
+        self.falling.get(pin / 8).map(|counters| counters[pin % 8])
+    }
+
+    /// Returns the rising edges of a pin and clears them, `None` if out of
+    /// range.
+    pub fn take_rising(&mut self, pin: usize) -> Option<u32> {
+        self.rising
+            .get_mut(pin / 8)
+            .map(|counters| core::mem::take(&mut counters[pin % 8]))
+    }
+
+    /// Returns the falling edges of a pin and clears them, `None` if out of
+    /// range.
+    pub fn take_falling(&mut self, pin: usize) -> Option<u32> {
+        self.falling
+            .get_mut(pin / 8)
+            .map(|counters| core::mem::take(&mut counters[pin % 8]))
+    }
+
+    /// Clears all counters, keeping the captured states.
+    pub fn clear(&mut self) {
+        self.rising = [[0; 8]; CHAIN_LENGTH];
+        self.falling = [[0; 8]; CHAIN_LENGTH];
+    }
+}
+
+impl<const CHAIN_LENGTH: usize> Default for EdgeCounters<CHAIN_LENGTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,830 @@
+//! Debouncing of the input states of a chain
+//!
+//! A [`DebouncedInputs`] wraps a chain reading switches and integrates
+//! consecutive scans, so its stable states only change after a pin kept its
+//! new state for a number of samples. It implements `GetInput` for the
+//! stable states, so the `Pin` types can be used with it.
+
+use crate::{
+    clock::Now,
+    event::Events,
+    input::{GetInput, Snapshot},
+    Error, Length, Update,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Default number of samples required to change a state.
+const DEFAULT_SAMPLES: u8 = 4;
+
+/// Numbers of samples required to change the state of a pin.
+///
+/// With 0 or 1 samples, changes are accepted immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Thresholds {
+    /// Samples required to change from low to high, e.g. when pressed.
+    pub press: u8,
+
+    /// Samples required to change from high to low, e.g. when released.
+    pub release: u8,
+}
+
+impl Thresholds {
+    /// Creates thresholds requiring `samples` samples in both directions.
+    pub const fn symmetric(samples: u8) -> Self {
+        Self {
+            press: samples,
+            release: samples,
+        }
+    }
+}
+
+impl Default for Thresholds {
+    /// Returns thresholds requiring 4 samples in both directions.
+    fn default() -> Self {
+        Self::symmetric(DEFAULT_SAMPLES)
+    }
+}
+
+/// Timing of the auto-repeat of held pins in ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AutoRepeat {
+    /// Ticks from the press to the first repeat.
+    pub delay: u32,
+
+    /// Ticks between the following repeats, 0 repeats on every scan.
+    pub interval: u32,
+}
+
+impl AutoRepeat {
+    /// Returns the ticks after the press when the repeat following `count`
+    /// reported ones is due.
+    fn due(self, count: u32) -> u32 {
+        self.delay
+            .saturating_add(self.interval.saturating_mul(count))
+    }
+
+    /// Returns the number of repeats due after the pin was held for `held`
+    /// ticks, which must be at least the delay.
+    fn count(self, held: u32) -> u32 {
+        match self.interval {
+            0 => 1,
+            interval => (held - self.delay) / interval + 1,
+        }
+    }
+}
+
+/// Algorithm integrating the samples of the pins.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Strategy {
+    /// A state changes after the threshold number of consecutive samples
+    /// with the opposite state, any sample with the stable state restarts
+    /// the count.
+    #[default]
+    Consecutive,
+
+    /// Integrator after Kenneth A. Kuhn, counting samples with the opposite
+    /// state up and samples with the stable state down. A state changes when
+    /// the count reaches the threshold, so short glitches within a bouncing
+    /// phase only delay the change instead of restarting it, e.g. for the
+    /// asymmetric bounce of membrane switches.
+    Integrator,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Chain with debounced inputs.
+///
+/// The stable states of the first `CHAIN_LENGTH` chips are tracked, starting
+/// with all pins low. A state changes after the pin read the opposite state
+/// in the number of samples set by the thresholds of the pin, counted as
+/// selected by the strategy.
+pub struct DebouncedInputs<Chain, const CHAIN_LENGTH: usize> {
+    /// Wrapped chain.
+    chain: Chain,
+
+    /// Stable states, 8 pins per byte starting with pin 0 in byte 0.
+    states: [u8; CHAIN_LENGTH],
+
+    /// Number of samples differing from the stable state.
+    counters: [[u8; 8]; CHAIN_LENGTH],
+
+    /// Numbers of samples required to change the states.
+    thresholds: [[Thresholds; 8]; CHAIN_LENGTH],
+
+    /// Algorithm integrating the samples.
+    strategy: Strategy,
+
+    /// Stable states changed by the last scan.
+    changes: Snapshot<CHAIN_LENGTH>,
+
+    /// Times of the last changes of the stable states recorded by
+    /// `scan_at()`, `None` before the first one.
+    changed_at: [[Option<u32>; 8]; CHAIN_LENGTH],
+
+    /// Ticks a pin must be held high for a long press, `None` if disabled.
+    long_press: Option<u32>,
+
+    /// Pins whose current press was already reported as long press.
+    long_pressed: [u8; CHAIN_LENGTH],
+
+    /// Pins reported as long press by the last scan.
+    long_presses: Snapshot<CHAIN_LENGTH>,
+
+    /// Window in ticks for the second press of a double click, `None` if
+    /// disabled.
+    double_click: Option<u32>,
+
+    /// Times of the first press of a possible double click.
+    first_press_at: [[Option<u32>; 8]; CHAIN_LENGTH],
+
+    /// Pins reported as double click by the last scan.
+    double_clicks: Snapshot<CHAIN_LENGTH>,
+
+    /// Timing of the auto-repeat, `None` if disabled.
+    auto_repeat: Option<AutoRepeat>,
+
+    /// Pins with auto-repeat enabled.
+    repeating: [u8; CHAIN_LENGTH],
+
+    /// Number of repeats reported since the pins were pressed.
+    repeat_counts: [[u32; 8]; CHAIN_LENGTH],
+
+    /// Pins reported as repeat by the last scan.
+    repeats: Snapshot<CHAIN_LENGTH>,
+}
+
+impl<Chain, const CHAIN_LENGTH: usize> DebouncedInputs<Chain, CHAIN_LENGTH>
+where
+    Chain: Length,
+{
+    /// Creates a new debouncer by consuming a chain.
+    ///
+    /// Returns an error if the chain is shorter than `CHAIN_LENGTH`.
+    pub fn new(chain: Chain) -> Result<Self, Error> {
+        if chain.input_len() < CHAIN_LENGTH {
+            return Err(Error::InvalidRange);
+        }
+
+        Ok(Self {
+            chain,
+            states: [0; CHAIN_LENGTH],
+            counters: [[0; 8]; CHAIN_LENGTH],
+            thresholds: [[Thresholds::default(); 8]; CHAIN_LENGTH],
+            strategy: Strategy::Consecutive,
+            changes: Snapshot::from_bytes([0; CHAIN_LENGTH]),
+            changed_at: [[None; 8]; CHAIN_LENGTH],
+            long_press: None,
+            long_pressed: [0; CHAIN_LENGTH],
+            long_presses: Snapshot::from_bytes([0; CHAIN_LENGTH]),
+            double_click: None,
+            first_press_at: [[None; 8]; CHAIN_LENGTH],
+            double_clicks: Snapshot::from_bytes([0; CHAIN_LENGTH]),
+            auto_repeat: None,
+            repeating: [0; CHAIN_LENGTH],
+            repeat_counts: [[0; 8]; CHAIN_LENGTH],
+            repeats: Snapshot::from_bytes([0; CHAIN_LENGTH]),
+        })
+    }
+}
+
+impl<Chain, const CHAIN_LENGTH: usize> DebouncedInputs<Chain, CHAIN_LENGTH> {
+    /// Frees the debouncer and returns the chain.
+    pub fn free(self) -> Chain {
+        self.chain
+    }
+
+    /// Returns a reference to the chain.
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+
+    /// Returns a mutable reference to the chain.
+    pub fn chain_mut(&mut self) -> &mut Chain {
+        &mut self.chain
+    }
+
+    /// Returns the algorithm integrating the samples.
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    /// Sets the algorithm integrating the samples, `Strategy::Consecutive`
+    /// by default.
+    pub fn set_strategy(&mut self, strategy: Strategy) {
+        self.strategy = strategy;
+    }
+
+    /// Returns the ticks a pin must be held high for a long press, `None` if
+    /// disabled.
+    pub fn long_press(&self) -> Option<u32> {
+        self.long_press
+    }
+
+    /// Sets the ticks a pin must be held high for a long press, `None`
+    /// disables the detection, which is the default.
+    ///
+    /// Long presses are detected by `scan_at()`, measured from the time the
+    /// stable state changed to high.
+    pub fn set_long_press(&mut self, ticks: Option<u32>) {
+        self.long_press = ticks;
+    }
+
+    /// Returns the pins reported as long press by the last scan.
+    pub fn long_presses(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.long_presses
+    }
+
+    /// Returns the window in ticks for the second press of a double click,
+    /// `None` if disabled.
+    pub fn double_click(&self) -> Option<u32> {
+        self.double_click
+    }
+
+    /// Sets the window in ticks for the second press of a double click,
+    /// `None` disables the detection, which is the default.
+    ///
+    /// Double clicks are detected by `scan_at()` if a press follows the
+    /// previous one within the window, measured between the presses. The
+    /// next press after a double click starts a new one.
+    pub fn set_double_click(&mut self, window: Option<u32>) {
+        self.double_click = window;
+        self.first_press_at = [[None; 8]; CHAIN_LENGTH];
+    }
+
+    /// Returns the pins reported as double click by the last scan.
+    pub fn double_clicks(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.double_clicks
+    }
+
+    /// Returns the timing of the auto-repeat, `None` if disabled.
+    pub fn auto_repeat(&self) -> Option<AutoRepeat> {
+        self.auto_repeat
+    }
+
+    /// Sets the timing of the auto-repeat for the pins enabled via
+    /// `set_repeating()`, `None` disables it, which is the default.
+    pub fn set_auto_repeat(&mut self, auto_repeat: Option<AutoRepeat>) {
+        self.auto_repeat = auto_repeat;
+    }
+
+    /// Returns whether auto-repeat is enabled for a pin, `None` if out of
+    /// range.
+    pub fn is_repeating(&self, pin: usize) -> Option<bool> {
+        self.repeating
+            .get(pin / 8)
+            .map(|repeating| repeating & (1 << (pin % 8)) != 0)
+    }
+
+    /// Enables or disables auto-repeat for a pin, disabled by default.
+    pub fn set_repeating(&mut self, pin: usize, repeating: bool) -> Result<(), Error> {
+        let chip = self
+            .repeating
+            .get_mut(pin / 8)
+            .ok_or(Error::PinOutOfRange)?;
+
+        if repeating {
+            *chip |= 1 << (pin % 8);
+        } else {
+            *chip &= !(1 << (pin % 8));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the pins reported as repeat by the last scan.
+    pub fn repeats(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.repeats
+    }
+
+    /// Returns the thresholds of a pin or `None` if out of range.
+    pub fn thresholds(&self, pin: usize) -> Option<Thresholds> {
+        self.thresholds
+            .get(pin / 8)
+            .map(|thresholds| thresholds[pin % 8])
+    }
+
+    /// Sets the thresholds of a pin, 4 samples in both directions by default.
+    pub fn set_thresholds(&mut self, pin: usize, thresholds: Thresholds) -> Result<(), Error> {
+        let chip = self
+            .thresholds
+            .get_mut(pin / 8)
+            .ok_or(Error::PinOutOfRange)?;
+        chip[pin % 8] = thresholds;
+
+        Ok(())
+    }
+
+    /// Sets the thresholds of all pins.
+    pub fn set_all_thresholds(&mut self, thresholds: Thresholds) {
+        self.thresholds = [[thresholds; 8]; CHAIN_LENGTH];
+    }
+
+    /// Sets the thresholds of the pins from a table in pin order, e.g. a
+    /// constant.
+    ///
+    /// Pins beyond `table` keep their thresholds. Returns an error if `table`
+    /// has more entries than pins.
+    pub fn set_threshold_table(&mut self, table: &[Thresholds]) -> Result<(), Error> {
+        if table.len() > CHAIN_LENGTH * 8 {
+            return Err(Error::InvalidRange);
+        }
+
+        for (pin, &thresholds) in table.iter().enumerate() {
+            self.thresholds[pin / 8][pin % 8] = thresholds;
+        }
+
+        Ok(())
+    }
+
+    /// Returns a snapshot of the stable states.
+    pub fn snapshot(&self) -> Snapshot<CHAIN_LENGTH> {
+        Snapshot::from_bytes(self.states)
+    }
+
+    /// Returns the stable states changed by the last scan.
+    pub fn changes(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.changes
+    }
+
+    /// Returns the time of the last change of the stable state of a pin,
+    /// `None` if out of range or not recorded yet.
+    pub fn changed_at(&self, pin: usize) -> Option<u32> {
+        self.changed_at
+            .get(pin / 8)
+            .and_then(|changed_at| changed_at[pin % 8])
+    }
+
+    /// Returns the ticks the stable state of a pin has been unchanged,
+    /// `None` if out of range or no change was recorded yet.
+    pub fn unchanged_for(&self, pin: usize, clock: &impl Now) -> Option<u32> {
+        self.changed_at(pin)
+            .map(|timestamp| clock.elapsed_since(timestamp))
+    }
+
+    /// Returns an iterator over the transitions of the stable states, the
+    /// double clicks, the long presses and the repeats by the last scan.
+    pub fn events(&self) -> Events<CHAIN_LENGTH> {
+        let states = self.snapshot();
+        Events::with_gestures(
+            &states,
+            &states.changes(&self.changes),
+            &[self.double_clicks, self.long_presses, self.repeats],
+        )
+    }
+}
+
+impl<Chain, const CHAIN_LENGTH: usize> DebouncedInputs<Chain, CHAIN_LENGTH>
+where
+    Chain: GetInput,
+{
+    /// Integrates the buffered input states of the chain as one sample.
+    ///
+    /// Intended to be called after each update of the chain, if it is not
+    /// updated via `update()`. Returns a snapshot with the states set for all
+    /// pins whose stable state changed.
+    pub fn scan(&mut self) -> Snapshot<CHAIN_LENGTH> {
+        let previous = self.snapshot();
+
+        for (index, ((states, counters), thresholds)) in self
+            .states
+            .iter_mut()
+            .zip(self.counters.iter_mut())
+            .zip(self.thresholds)
+            .enumerate()
+        {
+            for (bit, (counter, thresholds)) in counters.iter_mut().zip(thresholds).enumerate() {
+                let state = *states & (1 << bit) != 0;
+
+                if self.chain.get_input_unchecked(index * 8 + bit) == state {
+                    *counter = match self.strategy {
+                        Strategy::Consecutive => 0,
+                        Strategy::Integrator => counter.saturating_sub(1),
+                    };
+                    continue;
+                }
+
+                *counter = counter.saturating_add(1);
+
+                let threshold = if state {
+                    thresholds.release
+                } else {
+                    thresholds.press
+                };
+
+                if *counter >= threshold {
+                    *states ^= 1 << bit;
+                    *counter = 0;
+                }
+            }
+        }
+
+        self.changes = self.snapshot().changes(&previous);
+        self.double_clicks = Snapshot::default();
+        self.long_presses = Snapshot::default();
+        self.repeats = Snapshot::default();
+
+        self.changes
+    }
+
+    /// Integrates the buffered input states like `scan()` and records the
+    /// current time of `clock` for all pins whose stable state changed.
+    ///
+    /// Pins held high for the long press duration are reported once per
+    /// press via `long_presses()` and `events()`, as are double clicks via
+    /// `double_clicks()` and auto-repeats via `repeats()`.
+    pub fn scan_at(&mut self, clock: &impl Now) -> Snapshot<CHAIN_LENGTH> {
+        let changes = self.scan();
+        let now = clock.now();
+        let mut double_clicks = [0; CHAIN_LENGTH];
+        let mut long_presses = [0; CHAIN_LENGTH];
+        let mut repeats = [0; CHAIN_LENGTH];
+
+        for pin in 0..CHAIN_LENGTH * 8 {
+            let (index, bit) = (pin / 8, pin % 8);
+            let mask = 1 << bit;
+            let pressed = self.states[index] & mask != 0;
+
+            if changes.as_bytes()[index] & mask != 0 {
+                self.changed_at[index][bit] = Some(now);
+                self.repeat_counts[index][bit] = 0;
+
+                // Released pins can be reported again on the next press.
+                self.long_pressed[index] &= !mask;
+
+                if let (true, Some(window)) = (pressed, self.double_click) {
+                    let first_press_at = &mut self.first_press_at[index][bit];
+
+                    match *first_press_at {
+                        Some(timestamp) if now.wrapping_sub(timestamp) <= window => {
+                            double_clicks[index] |= mask;
+                            *first_press_at = None;
+                        }
+                        _ => *first_press_at = Some(now),
+                    }
+                }
+            }
+
+            let held = match self.changed_at[index][bit] {
+                Some(timestamp) if pressed => now.wrapping_sub(timestamp),
+                _ => continue,
+            };
+
+            if let Some(ticks) = self.long_press {
+                if held >= ticks && self.long_pressed[index] & mask == 0 {
+                    self.long_pressed[index] |= mask;
+                    long_presses[index] |= mask;
+                }
+            }
+
+            if let Some(auto_repeat) = self.auto_repeat {
+                let count = &mut self.repeat_counts[index][bit];
+
+                if self.repeating[index] & mask != 0 && held >= auto_repeat.due(*count) {
+                    // Repeats missed by slow scans are skipped.
+                    *count = auto_repeat.count(held);
+                    repeats[index] |= mask;
+                }
+            }
+        }
+
+        self.double_clicks = Snapshot::from_bytes(double_clicks);
+        self.long_presses = Snapshot::from_bytes(long_presses);
+        self.repeats = Snapshot::from_bytes(repeats);
+
+        changes
+    }
+}
+
+impl<Chain, const CHAIN_LENGTH: usize> DebouncedInputs<Chain, CHAIN_LENGTH>
+where
+    Chain: GetInput + Update,
+{
+    /// Updates the chain and integrates the input states as one sample.
+    ///
+    /// Returns an error if the update of the chain failed, the sample is
+    /// skipped in this case. On success, a snapshot with the states set for
+    /// all pins whose stable state changed is returned.
+    pub fn update(&mut self) -> Result<Snapshot<CHAIN_LENGTH>, Chain::Error> {
+        self.chain.update()?;
+
+        Ok(self.scan())
+    }
+}
+
+impl<Chain, const CHAIN_LENGTH: usize> GetInput for DebouncedInputs<Chain, CHAIN_LENGTH>
+where
+    Chain: GetInput,
+{
+    /// Returns the stable state for a pin.
+    fn get_input(&self, pin: usize) -> Result<bool, Error> {
+        if pin >= CHAIN_LENGTH * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        self.check_inputs()?;
+
+        Ok(self.get_input_unchecked(pin))
+    }
+
+    /// Returns the stable state for a pin without pin boundary checks.
+    fn get_input_unchecked(&self, pin: usize) -> bool {
+        self.states[pin / 8] & (1 << (pin % 8)) != 0
+    }
+
+    /// Copies the stable states into `data`, starting with the first chip.
+    fn read_inputs(&self, data: &mut [u8]) -> Result<(), Error> {
+        if data.len() > CHAIN_LENGTH {
+            return Err(Error::InvalidRange);
+        }
+
+        self.check_inputs()?;
+
+        data.copy_from_slice(&self.states[..data.len()]);
+
+        Ok(())
+    }
+
+    /// Checks whether the buffered input states of the chain are valid.
+    fn check_inputs(&self) -> Result<(), Error> {
+        self.chain.check_inputs()
+    }
+}
+
+impl<Chain, const CHAIN_LENGTH: usize> Update for DebouncedInputs<Chain, CHAIN_LENGTH>
+where
+    Chain: GetInput + Update,
+{
+    type Error = Chain::Error;
+
+    fn update(&mut self) -> Result<(), Self::Error> {
+        DebouncedInputs::update(self).map(|_| ())
+    }
+}
+
+impl<Chain, const CHAIN_LENGTH: usize> Length for DebouncedInputs<Chain, CHAIN_LENGTH> {
+    /// Returns the number of debounced chips.
+    fn len(&self) -> usize {
+        CHAIN_LENGTH
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{clock::ManualClock, input, mock::Recorder};
+
+    type Debouncer = DebouncedInputs<input::Chain<Recorder, 1>, 1>;
+
+    /// Returns a debouncer of a single chip.
+    fn debouncer() -> Result<Debouncer, Error> {
+        DebouncedInputs::new(input::Chain::with_transport(Recorder::default()))
+    }
+
+    /// Updates the debouncer with pins 0 to 7 read as the bits of `inputs`
+    /// and returns the stable states.
+    fn sample(debouncer: &mut Debouncer, inputs: u8) -> u8 {
+        debouncer.chain_mut().transport_mut().read[0] = inputs;
+        assert!(debouncer.update().is_ok());
+
+        debouncer.snapshot().as_bytes()[0]
+    }
+
+    /// Returns a debouncer of a single chip changing the states immediately.
+    fn immediate_debouncer() -> Result<Debouncer, Error> {
+        let mut debouncer = debouncer()?;
+        debouncer.set_all_thresholds(Thresholds::symmetric(1));
+
+        Ok(debouncer)
+    }
+
+    /// Updates the debouncer like `sample()` at the time of `clock`, then
+    /// advances the clock by one tick.
+    fn sample_at(debouncer: &mut Debouncer, clock: &ManualClock, inputs: u8) -> u8 {
+        debouncer.chain_mut().transport_mut().read[0] = inputs;
+        assert!(debouncer.chain_mut().update().is_ok());
+        debouncer.scan_at(clock);
+        clock.advance(1);
+
+        debouncer.snapshot().as_bytes()[0]
+    }
+
+    #[test]
+    fn asymmetric_thresholds() -> Result<(), Error> {
+        let mut debouncer = debouncer()?;
+        debouncer.set_thresholds(
+            0,
+            Thresholds {
+                press: 3,
+                release: 1,
+            },
+        )?;
+
+        assert_eq!(sample(&mut debouncer, 0x03), 0x00);
+        assert_eq!(sample(&mut debouncer, 0x03), 0x00);
+        assert_eq!(sample(&mut debouncer, 0x03), 0x01);
+        assert_eq!(debouncer.changes().as_bytes(), &[0x01]);
+        assert_eq!(sample(&mut debouncer, 0x03), 0x03);
+
+        assert_eq!(sample(&mut debouncer, 0x00), 0x02);
+        assert_eq!(debouncer.changes().as_bytes(), &[0x01]);
+        assert_eq!(sample(&mut debouncer, 0x00), 0x02);
+        assert_eq!(sample(&mut debouncer, 0x00), 0x02);
+        assert_eq!(sample(&mut debouncer, 0x00), 0x00);
+
+        Ok(())
+    }
+
+    #[test]
+    fn threshold_not_reached() -> Result<(), Error> {
+        let mut debouncer = debouncer()?;
+
+        for _ in 0..3 {
+            assert_eq!(sample(&mut debouncer, 0x01), 0x00);
+        }
+
+        assert_eq!(sample(&mut debouncer, 0x00), 0x00);
+
+        for _ in 0..3 {
+            assert_eq!(sample(&mut debouncer, 0x01), 0x00);
+        }
+
+        assert_eq!(sample(&mut debouncer, 0x01), 0x01);
+        assert_eq!(debouncer.changes().as_bytes(), &[0x01]);
+        assert_eq!(sample(&mut debouncer, 0x01), 0x01);
+        assert_eq!(debouncer.changes().as_bytes(), &[0x00]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn immediate_thresholds() -> Result<(), Error> {
+        let mut debouncer = debouncer()?;
+        debouncer.set_thresholds(0, Thresholds::symmetric(0))?;
+        debouncer.set_thresholds(1, Thresholds::symmetric(1))?;
+
+        assert_eq!(sample(&mut debouncer, 0x07), 0x03);
+        assert_eq!(sample(&mut debouncer, 0x04), 0x00);
+
+        Ok(())
+    }
+
+    #[test]
+    fn integrator_tolerates_bounces() -> Result<(), Error> {
+        const BOUNCING: [u8; 6] = [1, 1, 0, 1, 1, 1];
+
+        let mut consecutive = debouncer()?;
+        let mut integrator = debouncer()?;
+        integrator.set_strategy(Strategy::Integrator);
+
+        let consecutive_states = BOUNCING.map(|inputs| sample(&mut consecutive, inputs));
+        let integrator_states = BOUNCING.map(|inputs| sample(&mut integrator, inputs));
+
+        assert_eq!(consecutive_states, [0, 0, 0, 0, 0, 0]);
+        assert_eq!(integrator_states, [0, 0, 0, 0, 0, 1]);
+
+        // The bounce restarted the consecutive count, so it changes later.
+        assert_eq!(sample(&mut consecutive, 1), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn integrator_counts_down_without_restart() -> Result<(), Error> {
+        let mut debouncer = debouncer()?;
+        debouncer.set_strategy(Strategy::Integrator);
+        debouncer.set_all_thresholds(Thresholds::symmetric(2));
+
+        assert_eq!(sample(&mut debouncer, 1), 0);
+        assert_eq!(sample(&mut debouncer, 0), 0);
+        assert_eq!(sample(&mut debouncer, 0), 0);
+        assert_eq!(sample(&mut debouncer, 1), 0);
+        assert_eq!(sample(&mut debouncer, 1), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn long_press_across_wrap() -> Result<(), Error> {
+        let mut debouncer = immediate_debouncer()?;
+        debouncer.set_long_press(Some(10));
+        let clock = ManualClock::new(u32::MAX - 4);
+
+        assert_eq!(sample_at(&mut debouncer, &clock, 0x01), 0x01);
+        assert_eq!(debouncer.changed_at(0), Some(u32::MAX - 4));
+
+        for _ in 1..10 {
+            sample_at(&mut debouncer, &clock, 0x01);
+            assert_eq!(debouncer.long_presses().as_bytes(), &[0x00]);
+        }
+
+        assert_eq!(debouncer.unchanged_for(0, &clock), Some(10));
+        sample_at(&mut debouncer, &clock, 0x01);
+        assert_eq!(debouncer.long_presses().as_bytes(), &[0x01]);
+        sample_at(&mut debouncer, &clock, 0x01);
+        assert_eq!(debouncer.long_presses().as_bytes(), &[0x00]);
+
+        sample_at(&mut debouncer, &clock, 0x00);
+        sample_at(&mut debouncer, &clock, 0x01);
+        clock.advance(9);
+        sample_at(&mut debouncer, &clock, 0x01);
+        assert_eq!(debouncer.long_presses().as_bytes(), &[0x01]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn double_click_window() -> Result<(), Error> {
+        let mut debouncer = immediate_debouncer()?;
+        debouncer.set_double_click(Some(5));
+        let clock = ManualClock::new(u32::MAX - 2);
+
+        // Presses at MAX - 2 and 2 after the wrap, at the end of the window.
+        sample_at(&mut debouncer, &clock, 0x01);
+        sample_at(&mut debouncer, &clock, 0x00);
+        clock.advance(3);
+        sample_at(&mut debouncer, &clock, 0x01);
+        assert_eq!(debouncer.double_clicks().as_bytes(), &[0x01]);
+
+        // The next press starts a new double click.
+        sample_at(&mut debouncer, &clock, 0x00);
+        sample_at(&mut debouncer, &clock, 0x01);
+        assert_eq!(debouncer.double_clicks().as_bytes(), &[0x00]);
+
+        // A press after the window starts a new one as well.
+        sample_at(&mut debouncer, &clock, 0x00);
+        clock.advance(4);
+        sample_at(&mut debouncer, &clock, 0x01);
+        assert_eq!(debouncer.double_clicks().as_bytes(), &[0x00]);
+
+        sample_at(&mut debouncer, &clock, 0x00);
+        sample_at(&mut debouncer, &clock, 0x01);
+        assert_eq!(debouncer.double_clicks().as_bytes(), &[0x01]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn auto_repeat_across_wrap() -> Result<(), Error> {
+        let mut debouncer = immediate_debouncer()?;
+        debouncer.set_auto_repeat(Some(AutoRepeat {
+            delay: 10,
+            interval: 4,
+        }));
+        debouncer.set_repeating(0, true)?;
+        let clock = ManualClock::new(u32::MAX - 7);
+
+        let mut repeated = [false; 20];
+
+        for repeated in repeated.iter_mut() {
+            sample_at(&mut debouncer, &clock, 0x03);
+            *repeated = debouncer.repeats().as_bytes() == [0x01];
+        }
+
+        let expected: [bool; 20] = core::array::from_fn(|held| held >= 10 && (held - 10) % 4 == 0);
+        assert_eq!(repeated, expected);
+
+        // A slow scan reports the missed repeats once and stays on the grid
+        // of the interval, held for 30 ticks with the next repeat due at 34.
+        clock.advance(10);
+        sample_at(&mut debouncer, &clock, 0x03);
+        assert_eq!(debouncer.repeats().as_bytes(), &[0x01]);
+        clock.advance(2);
+        sample_at(&mut debouncer, &clock, 0x03);
+        assert_eq!(debouncer.repeats().as_bytes(), &[0x00]);
+        sample_at(&mut debouncer, &clock, 0x03);
+        assert_eq!(debouncer.repeats().as_bytes(), &[0x01]);
+
+        // Releasing restarts the delay.
+        sample_at(&mut debouncer, &clock, 0x00);
+        sample_at(&mut debouncer, &clock, 0x01);
+        assert_eq!(debouncer.repeats().as_bytes(), &[0x00]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn auto_repeat_without_interval() -> Result<(), Error> {
+        let mut debouncer = immediate_debouncer()?;
+        debouncer.set_auto_repeat(Some(AutoRepeat {
+            delay: 2,
+            interval: 0,
+        }));
+        debouncer.set_repeating(0, true)?;
+        let clock = ManualClock::new(0);
+
+        let repeated = [(); 5].map(|_| {
+            sample_at(&mut debouncer, &clock, 0x01);
+            debouncer.repeats().as_bytes() == [0x01]
+        });
+
+        assert_eq!(repeated, [false, false, true, true, true]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+//! Multiplexed 7-segment displays
+//!
+//! A [`SevenSegment`] display drives the segment lines of all digits via one
+//! range of output pins and the digit commons via another. Each
+//! [`SevenSegment::tick()`] writes the segments of the next digit into the
+//! chain buffer and enables its common, so the digits are scanned at the
+//! tick rate divided by their number.
+
+use crate::{output::SetOutput, Error, Length};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Segments of the hex digits 0 to F, with bits 0 to 6 being segments a to
+/// g.
+const HEX_DIGITS: [u8; 16] = [
+    0x3F, 0x06, 0x5B, 0x4F, 0x66, 0x6D, 0x7D, 0x07, //
+    0x7F, 0x6F, 0x77, 0x7C, 0x39, 0x5E, 0x79, 0x71, //
+];
+
+/// Segments of a minus sign.
+const MINUS: u8 = 0x40;
+
+/// Segment of the decimal point.
+const DECIMAL_POINT: u8 = 0x80;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Type of the digit commons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Common {
+    /// Common cathode, enabled low with segments lit high.
+    Cathode,
+
+    /// Common anode, enabled high with segments lit low.
+    Anode,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Multiplexed display of `DIGITS` 7-segment digits, digit 0 being the
+/// leftmost one.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SevenSegment<const DIGITS: usize> {
+    /// Output pins of the segments a to g and the decimal point.
+    segment_pins: [usize; 8],
+
+    /// Output pins of the digit commons.
+    digit_pins: [usize; DIGITS],
+
+    /// Whether lit segments are driven low.
+    segments_active_low: bool,
+
+    /// Whether enabled digits are driven low.
+    digits_active_low: bool,
+
+    /// Segments of the digits, with bits 0 to 7 being segments a to g and
+    /// the decimal point.
+    buffer: [u8; DIGITS],
+
+    /// Digit written by the next tick.
+    digit: usize,
+}
+
+impl<const DIGITS: usize> SevenSegment<DIGITS> {
+    /// Creates a new blank display with the segment pins in the order a to
+    /// g and the decimal point, the digit pins from left to right, and the
+    /// polarities of the common type.
+    ///
+    /// Returns an error if a pin is out of range for the chain.
+    pub fn new(
+        chain: &impl Length,
+        segment_pins: [usize; 8],
+        digit_pins: [usize; DIGITS],
+        common: Common,
+    ) -> Result<Self, Error> {
+        if segment_pins
+            .iter()
+            .chain(digit_pins.iter())
+            .any(|&pin| pin >= chain.output_len() * 8)
+        {
+            return Err(Error::PinOutOfRange);
+        }
+
+        Ok(Self {
+            segment_pins,
+            digit_pins,
+            segments_active_low: common == Common::Anode,
+            digits_active_low: common == Common::Cathode,
+            buffer: [0; DIGITS],
+            digit: 0,
+        })
+    }
+
+    /// Returns `true` if lit segments are driven low.
+    pub fn segments_active_low(&self) -> bool {
+        self.segments_active_low
+    }
+
+    /// Sets whether lit segments are driven low, e.g. for segment drivers
+    /// inverting the outputs.
+    pub fn set_segments_active_low(&mut self, active_low: bool) {
+        self.segments_active_low = active_low;
+    }
+
+    /// Returns `true` if enabled digits are driven low.
+    pub fn digits_active_low(&self) -> bool {
+        self.digits_active_low
+    }
+
+    /// Sets whether enabled digits are driven low, e.g. for transistors
+    /// switching the commons.
+    pub fn set_digits_active_low(&mut self, active_low: bool) {
+        self.digits_active_low = active_low;
+    }
+
+    /// Returns the segments of a digit, with bits 0 to 7 being segments a
+    /// to g and the decimal point.
+    pub fn segments(&self, digit: usize) -> Result<u8, Error> {
+        self.buffer.get(digit).copied().ok_or(Error::InvalidRange)
+    }
+
+    /// Sets the segments of a digit, with bits 0 to 7 being segments a to
+    /// g and the decimal point.
+    pub fn set_segments(&mut self, digit: usize, segments: u8) -> Result<(), Error> {
+        *self.buffer.get_mut(digit).ok_or(Error::InvalidRange)? = segments;
+
+        Ok(())
+    }
+
+    /// Shows a hex digit, keeping the decimal point.
+    ///
+    /// Returns an error if the digit is out of range or `value` exceeds 15.
+    pub fn set_digit(&mut self, digit: usize, value: u8) -> Result<(), Error> {
+        let segments = *HEX_DIGITS
+            .get(usize::from(value))
+            .ok_or(Error::InvalidRange)?;
+        let decimal_point = self.segments(digit)? & DECIMAL_POINT;
+
+        self.set_segments(digit, segments | decimal_point)
+    }
+
+    /// Sets the decimal point of a digit.
+    pub fn set_decimal_point(&mut self, digit: usize, lit: bool) -> Result<(), Error> {
+        let segments = self.segments(digit)?;
+
+        if lit {
+            self.set_segments(digit, segments | DECIMAL_POINT)
+        } else {
+            self.set_segments(digit, segments & !DECIMAL_POINT)
+        }
+    }
+
+    /// Shows a decimal number right-aligned without leading zeros,
+    /// clearing the decimal points.
+    ///
+    /// Returns an error if the number does not fit, the digits are left
+    /// unchanged in this case.
+    pub fn show_number(&mut self, number: i32) -> Result<(), Error> {
+        let mut buffer = [0; DIGITS];
+        let mut value = number.unsigned_abs();
+        let mut digit = DIGITS;
+
+        loop {
+            digit = digit.checked_sub(1).ok_or(Error::InvalidRange)?;
+            buffer[digit] = HEX_DIGITS[(value % 10) as usize];
+            value /= 10;
+
+            if value == 0 {
+                break;
+            }
+        }
+
+        if number < 0 {
+            digit = digit.checked_sub(1).ok_or(Error::InvalidRange)?;
+            buffer[digit] = MINUS;
+        }
+
+        self.buffer = buffer;
+
+        Ok(())
+    }
+
+    /// Blanks all digits.
+    pub fn clear(&mut self) {
+        self.buffer = [0; DIGITS];
+    }
+
+    /// Writes the segments of the next digit into the chain buffer and
+    /// enables its common, disabling the others.
+    ///
+    /// Intended to be called periodically, each followed by an update of
+    /// the chain. Returns an error if a pin is out of range.
+    pub fn tick(&mut self, chain: &mut (impl SetOutput + ?Sized)) -> Result<(), Error> {
+        let Some(&segments) = self.buffer.get(self.digit) else {
+            return Ok(());
+        };
+
+        for (digit, &pin) in self.digit_pins.iter().enumerate() {
+            chain.set_output(pin, (digit == self.digit) != self.digits_active_low)?;
+        }
+
+        for (segment, &pin) in self.segment_pins.iter().enumerate() {
+            let lit = segments & (1 << segment) != 0;
+            chain.set_output(pin, lit != self.segments_active_low)?;
+        }
+
+        self.digit = (self.digit + 1) % DIGITS;
+
+        Ok(())
+    }
+
+    /// Disables all digits in the chain buffer and restarts the scan with
+    /// the leftmost digit.
+    pub fn blank(&mut self, chain: &mut (impl SetOutput + ?Sized)) -> Result<(), Error> {
+        for &pin in self.digit_pins.iter() {
+            chain.set_output(pin, self.digits_active_low)?;
+        }
+
+        self.digit = 0;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,375 @@
+//! Decoding of quadrature rotary encoders
+//!
+//! An [`Encoder`] reads the A and B pins of a rotary encoder from a chain on
+//! each scan and returns the signed number of steps turned since the
+//! previous one. With the full-quadrature state table, single missed states
+//! due to a low scan rate are still counted in the direction of the last
+//! movement.
+//!
+//! With a clock, `scan_at()` measures the time between the steps and
+//! multiplies fast spins by the factors of an [`Acceleration`] curve.
+
+use crate::{clock::Now, input::GetInput, Error};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Marker in the transition table for a skipped state, both pins changed.
+const SKIPPED: i8 = 2;
+
+/// Counts of the transitions between two states, indexed by
+/// `previous << 2 | current` with the state being `a << 1 | b`.
+///
+/// Clockwise rotation follows the sequence 00, 01, 11, 10.
+const TRANSITIONS: [i8; 16] = [
+    0, 1, -1, SKIPPED, //
+    -1, 0, SKIPPED, 1, //
+    1, SKIPPED, 0, -1, //
+    SKIPPED, -1, 1, 0, //
+];
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Point of an acceleration curve.
+///
+/// A curve is a slice of points, e.g. a constant. Steps following the
+/// previous one within `interval` ticks are multiplied by `factor`, using the
+/// largest factor of all matching points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Acceleration {
+    /// Maximum ticks between two steps.
+    pub interval: u32,
+
+    /// Factor applied to the steps.
+    pub factor: u16,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Rotary encoder connected to two input pins.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Encoder {
+    /// Pin of the A signal.
+    pin_a: usize,
+
+    /// Pin of the B signal.
+    pin_b: usize,
+
+    /// State of the pins at the previous scan, `None` before the first one.
+    state: Option<u8>,
+
+    /// Direction of the last transition, 0 if not moved yet.
+    direction: i8,
+
+    /// Counts not reported as a whole step yet.
+    counts: i32,
+
+    /// Counts per reported step.
+    counts_per_step: u8,
+
+    /// Acceleration curve applied by `scan_at()`, empty if disabled.
+    acceleration: &'static [Acceleration],
+
+    /// Time of the last step, `None` if not recorded yet.
+    step_at: Option<u32>,
+
+    /// Ticks per step measured at the last step, `None` if unknown.
+    step_interval: Option<u32>,
+
+    /// Direction of the last step, 0 if not recorded yet.
+    step_direction: i32,
+}
+
+impl Encoder {
+    /// Creates a new encoder reading the A and B signals from two pins.
+    ///
+    /// One step is reported per 4 counts by default, i.e. per full
+    /// quadrature cycle.
+    pub const fn new(pin_a: usize, pin_b: usize) -> Self {
+        Self {
+            pin_a,
+            pin_b,
+            state: None,
+            direction: 0,
+            counts: 0,
+            counts_per_step: 4,
+            acceleration: &[],
+            step_at: None,
+            step_interval: None,
+            step_direction: 0,
+        }
+    }
+
+    /// Returns the pins of the A and B signals.
+    pub fn pins(&self) -> (usize, usize) {
+        (self.pin_a, self.pin_b)
+    }
+
+    /// Returns the counts per reported step.
+    pub fn counts_per_step(&self) -> u8 {
+        self.counts_per_step
+    }
+
+    /// Sets the counts per reported step, e.g. 4 for encoders with one
+    /// detent per quadrature cycle or 1 to report every edge.
+    ///
+    /// Returns an error if `counts` is 0.
+    pub fn set_counts_per_step(&mut self, counts: u8) -> Result<(), Error> {
+        if counts == 0 {
+            return Err(Error::InvalidRange);
+        }
+
+        self.counts_per_step = counts;
+        self.counts = 0;
+
+        Ok(())
+    }
+
+    /// Returns the acceleration curve applied by `scan_at()`.
+    pub fn acceleration(&self) -> &'static [Acceleration] {
+        self.acceleration
+    }
+
+    /// Sets the acceleration curve applied by `scan_at()`, empty by default
+    /// which disables the acceleration.
+    pub fn set_acceleration(&mut self, curve: &'static [Acceleration]) {
+        self.acceleration = curve;
+    }
+
+    /// Returns the ticks per step measured by `scan_at()` at the last step,
+    /// `None` if unknown, e.g. after a change of direction.
+    pub fn step_interval(&self) -> Option<u32> {
+        self.step_interval
+    }
+
+    /// Returns the velocity in steps per `ticks` ticks measured by
+    /// `scan_at()` at the last step, e.g. steps per second with the ticks
+    /// per second of the clock. Returns 0 if unknown.
+    pub fn velocity(&self, ticks: u32) -> u32 {
+        match self.step_interval {
+            Some(interval) => ticks / interval.max(1),
+            None => 0,
+        }
+    }
+
+    /// Reads the pins from `chain` and returns the steps turned since the
+    /// previous scan, positive for clockwise rotation.
+    ///
+    /// Intended to be called after each update of the chain. The first scan
+    /// only captures the state and returns 0. Returns an error if a pin is
+    /// out of range or the inputs are not valid.
+    pub fn scan<Chain>(&mut self, chain: &Chain) -> Result<i32, Error>
+    where
+        Chain: GetInput + ?Sized,
+    {
+        let a = chain.get_input(self.pin_a)?;
+        let b = chain.get_input(self.pin_b)?;
+
+        Ok(self.decode(u8::from(a) << 1 | u8::from(b)))
+    }
+
+    /// Reads the pins from `chain` like `scan()` and returns the steps
+    /// multiplied by the acceleration curve.
+    ///
+    /// The velocity is measured from the time of `clock` between the steps.
+    /// A change of direction restarts the measurement.
+    pub fn scan_at<Chain>(&mut self, chain: &Chain, clock: &impl Now) -> Result<i32, Error>
+    where
+        Chain: GetInput + ?Sized,
+    {
+        let steps = self.scan(chain)?;
+
+        if steps == 0 {
+            return Ok(0);
+        }
+
+        let now = clock.now();
+
+        self.step_interval = match self.step_at {
+            Some(timestamp) if steps.signum() == self.step_direction => {
+                Some(now.wrapping_sub(timestamp) / steps.unsigned_abs())
+            }
+            _ => None,
+        };
+        self.step_at = Some(now);
+        self.step_direction = steps.signum();
+
+        let factor = self.step_interval.map_or(1, |interval| {
+            self.acceleration
+                .iter()
+                .filter(|point| interval <= point.interval)
+                .map(|point| point.factor)
+                .max()
+                .unwrap_or(1)
+        });
+
+        Ok(steps.saturating_mul(i32::from(factor)))
+    }
+
+    /// Discards the counts not reported yet, the captured state and the
+    /// measured velocity.
+    pub fn reset(&mut self) {
+        self.state = None;
+        self.direction = 0;
+        self.counts = 0;
+        self.step_at = None;
+        self.step_interval = None;
+        self.step_direction = 0;
+    }
+
+    /// Decodes the transition to `state` and returns the completed steps.
+    fn decode(&mut self, state: u8) -> i32 {
+        let Some(previous) = self.state.replace(state) else {
+            return 0;
+        };
+
+        let counts = match TRANSITIONS[usize::from(previous << 2 | state)] {
+            // Assume the skipped state was passed in the last direction.
+            SKIPPED => self.direction * 2,
+            counts => {
+                if counts != 0 {
+                    self.direction = counts;
+                }
+                counts
+            }
+        };
+
+        self.counts += i32::from(counts);
+
+        let steps = self.counts / i32::from(self.counts_per_step);
+        self.counts -= steps * i32::from(self.counts_per_step);
+
+        steps
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{clock::ManualClock, input, mock::Recorder};
+
+    /// States of a clockwise quadrature cycle as `a << 1 | b`.
+    const CLOCKWISE: [u8; 4] = [0b00, 0b01, 0b11, 0b10];
+
+    /// Decodes `states` after capturing `initial` and returns the steps of
+    /// each transition.
+    fn decode<const N: usize>(encoder: &mut Encoder, initial: u8, states: [u8; N]) -> [i32; N] {
+        assert_eq!(encoder.decode(initial), 0);
+        states.map(|state| encoder.decode(state))
+    }
+
+    #[test]
+    fn clockwise_cycle() {
+        let mut encoder = Encoder::new(0, 1);
+
+        assert_eq!(
+            decode(&mut encoder, 0b00, [0b01, 0b11, 0b10, 0b00]),
+            [0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn counter_clockwise_cycle() {
+        let mut encoder = Encoder::new(0, 1);
+
+        assert_eq!(
+            decode(&mut encoder, 0b00, [0b10, 0b11, 0b01, 0b00]),
+            [0, 0, 0, -1]
+        );
+    }
+
+    #[test]
+    fn single_counts() {
+        let mut encoder = Encoder::new(0, 1);
+        assert_eq!(encoder.set_counts_per_step(1), Ok(()));
+
+        for (index, &previous) in CLOCKWISE.iter().enumerate() {
+            let next = CLOCKWISE[(index + 1) % 4];
+            let back = CLOCKWISE[(index + 3) % 4];
+
+            encoder.reset();
+            assert_eq!(decode(&mut encoder, previous, [previous, next]), [0, 1]);
+            encoder.reset();
+            assert_eq!(decode(&mut encoder, previous, [back]), [-1]);
+        }
+    }
+
+    #[test]
+    fn partial_cycles_accumulate() {
+        let mut encoder = Encoder::new(0, 1);
+        assert_eq!(encoder.counts_per_step(), 4);
+        assert_eq!(encoder.set_counts_per_step(0), Err(Error::InvalidRange));
+
+        // Bouncing between two states does not add up.
+        assert_eq!(
+            decode(&mut encoder, 0b00, [0b01, 0b00, 0b01, 0b11, 0b10, 0b00]),
+            [0, 0, 0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn skipped_state() {
+        let mut encoder = Encoder::new(0, 1);
+        assert_eq!(encoder.set_counts_per_step(1), Ok(()));
+
+        // Without a previous movement, the direction is unknown.
+        assert_eq!(decode(&mut encoder, 0b00, [0b11]), [0]);
+
+        assert_eq!(decode(&mut encoder, 0b00, [0b10, 0b01]), [-1, -2]);
+        assert_eq!(encoder.decode(0b10), -2);
+        assert_eq!(encoder.decode(0b00), 1);
+        assert_eq!(encoder.decode(0b11), 2);
+
+        // A skipped state completes a whole cycle with 4 counts per step.
+        assert_eq!(encoder.set_counts_per_step(4), Ok(()));
+        encoder.reset();
+        assert_eq!(decode(&mut encoder, 0b00, [0b01, 0b10, 0b00]), [0, 0, 1]);
+    }
+
+    #[test]
+    fn acceleration_factors() {
+        const CURVE: [Acceleration; 2] = [
+            Acceleration {
+                interval: 10,
+                factor: 2,
+            },
+            Acceleration {
+                interval: 4,
+                factor: 5,
+            },
+        ];
+
+        let mut chain = input::Chain::<_, 1>::with_transport(Recorder::default());
+        let clock = ManualClock::new(u32::MAX - 20);
+        let mut encoder = Encoder::new(0, 1);
+        assert_eq!(encoder.set_counts_per_step(1), Ok(()));
+        encoder.set_acceleration(&CURVE);
+
+        // Moves to `state` after `ticks` and returns the accelerated steps.
+        let mut turn = |encoder: &mut Encoder, state: u8, ticks: u32| {
+            chain.transport_mut().read[0] = (state >> 1) | (state & 1) << 1;
+            assert!(chain.update().is_ok());
+            clock.advance(ticks);
+            encoder.scan_at(&chain, &clock)
+        };
+
+        assert_eq!(turn(&mut encoder, 0b00, 0), Ok(0));
+        assert_eq!(turn(&mut encoder, 0b01, 1), Ok(1));
+        assert_eq!(turn(&mut encoder, 0b11, 20), Ok(1));
+        assert_eq!(turn(&mut encoder, 0b10, 10), Ok(2));
+        assert_eq!(turn(&mut encoder, 0b00, 5), Ok(2));
+        assert_eq!(turn(&mut encoder, 0b01, 4), Ok(5));
+        assert_eq!(turn(&mut encoder, 0b01, 1), Ok(0));
+        assert_eq!(turn(&mut encoder, 0b11, 2), Ok(5));
+        assert_eq!(encoder.step_interval(), Some(3));
+        assert_eq!(encoder.velocity(30), 10);
+
+        // A change of direction restarts the measurement.
+        assert_eq!(turn(&mut encoder, 0b01, 1), Ok(-1));
+        assert_eq!(encoder.step_interval(), None);
+        assert_eq!(turn(&mut encoder, 0b00, 1), Ok(-5));
+    }
+}
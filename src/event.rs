@@ -0,0 +1,730 @@
+//! Edge events of input pins
+//!
+//! Instead of diffing snapshots manually, the transitions of an update or
+//! scan can be iterated as [`Event`]s, each holding the pin and whether it
+//! was pressed or released.
+//!
+//! A [`Chords`] filter replaces the events of pins pressed together, e.g.
+//! Shift + F1, by a dedicated chord event.
+//!
+//! An [`EventQueue`] collects them with timestamps across scans, so a slower
+//! task can process them later without missing short presses. With the
+//! `async` feature, a task can await the events of a shared queue via
+//! `EventQueue::next_event()`.
+
+#[cfg(feature = "async")]
+use core::{
+    cell::RefCell,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "async")]
+use crate::waker::WakerRegistration;
+use crate::{clock::Now, input::Snapshot, Error};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Kind of an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Edge {
+    /// Transition from low to high, i.e. a rising edge.
+    Pressed,
+
+    /// Transition from high to low, i.e. a falling edge.
+    Released,
+
+    /// Pin held high for the long press duration, reported once per press
+    /// by `DebouncedInputs::scan_at()`.
+    LongPress,
+
+    /// Second press within the double click window of the previous one,
+    /// reported after its `Pressed` event by `DebouncedInputs::scan_at()`.
+    DoubleClick,
+
+    /// Repeated press of a pin held beyond the auto-repeat delay, reported
+    /// by `DebouncedInputs::scan_at()` at the auto-repeat interval.
+    Repeat,
+
+    /// Press completing the chord with the given index, reported by
+    /// `Chords::filter()` instead of the `Pressed` event of the pin.
+    Chord(usize),
+}
+
+/// Transition of a pin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Event {
+    /// Pin number.
+    pub pin: usize,
+
+    /// Kind of the event.
+    pub edge: Edge,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Kinds of the events following the transition of a pin, in the order they
+/// are reported.
+const GESTURES: [Edge; 3] = [Edge::DoubleClick, Edge::LongPress, Edge::Repeat];
+
+/// Iterator over the transitions between two snapshots in pin order.
+///
+/// Double clicks, long presses and repeats follow the transition of the same
+/// pin, if any.
+#[derive(Debug, Clone)]
+pub struct Events<const CHAIN_LENGTH: usize> {
+    /// States after the transitions.
+    states: Snapshot<CHAIN_LENGTH>,
+
+    /// Pins that changed and are not reported yet.
+    changes: [u8; CHAIN_LENGTH],
+
+    /// Pins with gestures not reported yet, in the order of `GESTURES`.
+    gestures: [[u8; CHAIN_LENGTH]; GESTURES.len()],
+
+    /// Next pin to be checked.
+    pin: usize,
+}
+
+impl<const CHAIN_LENGTH: usize> Events<CHAIN_LENGTH> {
+    /// Creates an iterator over the transitions from `previous` to `states`.
+    pub fn new(states: &Snapshot<CHAIN_LENGTH>, previous: &Snapshot<CHAIN_LENGTH>) -> Self {
+        Self::with_gestures(states, previous, &[Snapshot::default(); GESTURES.len()])
+    }
+
+    /// Creates an iterator over the transitions from `previous` to `states`,
+    /// followed by the gestures of the pins set in `gestures`, in the order
+    /// of `GESTURES`.
+    pub(crate) fn with_gestures(
+        states: &Snapshot<CHAIN_LENGTH>,
+        previous: &Snapshot<CHAIN_LENGTH>,
+        gestures: &[Snapshot<CHAIN_LENGTH>; GESTURES.len()],
+    ) -> Self {
+        let mut events = Self {
+            states: *states,
+            changes: [0; CHAIN_LENGTH],
+            gestures: [[0; CHAIN_LENGTH]; GESTURES.len()],
+            pin: 0,
+        };
+
+        events
+            .changes
+            .copy_from_slice(states.changes(previous).as_bytes());
+
+        for (pins, gesture) in events.gestures.iter_mut().zip(gestures) {
+            pins.copy_from_slice(gesture.as_bytes());
+        }
+
+        events
+    }
+
+    /// Returns the states after the transitions.
+    pub fn states(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.states
+    }
+}
+
+impl<const CHAIN_LENGTH: usize> Iterator for Events<CHAIN_LENGTH> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        while self.pin < CHAIN_LENGTH * 8 {
+            let pin = self.pin;
+            let (index, mask) = (pin / 8, 1 << (pin % 8));
+
+            if self.changes[index] & mask != 0 {
+                self.changes[index] &= !mask;
+
+                let edge = if self.states[pin] {
+                    Edge::Pressed
+                } else {
+                    Edge::Released
+                };
+
+                return Some(Event { pin, edge });
+            }
+
+            for (pins, edge) in self.gestures.iter_mut().zip(GESTURES) {
+                if pins[index] & mask != 0 {
+                    pins[index] &= !mask;
+
+                    return Some(Event { pin, edge });
+                }
+            }
+
+            let pending = self
+                .gestures
+                .iter()
+                .fold(self.changes[index], |pending, pins| pending | pins[index]);
+
+            if pending >> (pin % 8) == 0 {
+                // Skip the rest of the chip.
+                self.pin = (index + 1) * 8;
+            } else {
+                self.pin += 1;
+            }
+        }
+
+        None
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Chords of pins that must be high simultaneously, e.g. Shift + F1.
+///
+/// Up to `CAPACITY` chords are defined by `add()`. When a press completes a
+/// chord, `filter()` reports `Edge::Chord` with the chord index instead of the
+/// `Pressed` event of that pin and suppresses all further events of the pins
+/// of the chord until each of them is released. Events of the pins pressed
+/// before completing the chord are passed, as the chord is not known yet.
+#[derive(Debug, Clone)]
+pub struct Chords<const CHAIN_LENGTH: usize, const CAPACITY: usize> {
+    /// Pins of the chords.
+    chords: [Snapshot<CHAIN_LENGTH>; CAPACITY],
+
+    /// Number of defined chords.
+    count: usize,
+
+    /// Pins whose events are suppressed until released.
+    suppressed: [u8; CHAIN_LENGTH],
+}
+
+impl<const CHAIN_LENGTH: usize, const CAPACITY: usize> Chords<CHAIN_LENGTH, CAPACITY> {
+    /// Creates an empty set of chords.
+    pub const fn new() -> Self {
+        Self {
+            chords: [Snapshot::from_bytes([0; CHAIN_LENGTH]); CAPACITY],
+            count: 0,
+            suppressed: [0; CHAIN_LENGTH],
+        }
+    }
+
+    /// Defines a chord of `pins` and returns its index.
+    ///
+    /// Returns an error if a pin is out of range, fewer than 2 distinct pins
+    /// are given or all chords are defined already.
+    pub fn add(&mut self, pins: &[usize]) -> Result<usize, Error> {
+        let mut chord = [0u8; CHAIN_LENGTH];
+
+        for &pin in pins {
+            if pin >= CHAIN_LENGTH * 8 {
+                return Err(Error::PinOutOfRange);
+            }
+
+            chord[pin / 8] |= 1 << (pin % 8);
+        }
+
+        if pin_count(&chord) < 2 {
+            return Err(Error::InvalidRange);
+        }
+
+        let index = self.count;
+        *self.chords.get_mut(index).ok_or(Error::BufferTooSmall)? = Snapshot::from_bytes(chord);
+        self.count += 1;
+
+        Ok(index)
+    }
+
+    /// Returns the pins of a chord or `None` if not defined.
+    pub fn get(&self, index: usize) -> Option<Snapshot<CHAIN_LENGTH>> {
+        self.chords[..self.count].get(index).copied()
+    }
+
+    /// Returns the number of defined chords.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns `true` if no chords are defined.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Removes all chords and ends the suppression of events.
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.suppressed = [0; CHAIN_LENGTH];
+    }
+
+    /// Returns an iterator over `events` with the chords applied.
+    ///
+    /// If a press completes several chords, the one with the most pins is
+    /// reported, or the first defined one of those.
+    pub fn filter<'a>(
+        &'a mut self,
+        events: Events<CHAIN_LENGTH>,
+    ) -> impl Iterator<Item = Event> + 'a {
+        let states = events.states();
+        events.filter_map(move |event| self.apply(event, &states))
+    }
+
+    /// Applies the chords to an event, returning `None` if it is suppressed.
+    fn apply(&mut self, event: Event, states: &Snapshot<CHAIN_LENGTH>) -> Option<Event> {
+        let (index, mask) = (event.pin / 8, 1 << (event.pin % 8));
+
+        if self.suppressed[index] & mask != 0 {
+            if event.edge == Edge::Released {
+                self.suppressed[index] &= !mask;
+            }
+
+            return None;
+        }
+
+        if event.edge != Edge::Pressed {
+            return Some(event);
+        }
+
+        let chord = self.chords[..self.count]
+            .iter()
+            .enumerate()
+            .filter(|(_, chord)| {
+                chord.as_bytes()[index] & mask != 0
+                    && chord.rising_edges(states) == Snapshot::default()
+            })
+            .max_by_key(|(chord_index, chord)| {
+                (
+                    pin_count(chord.as_bytes()),
+                    core::cmp::Reverse(*chord_index),
+                )
+            });
+
+        let Some((chord_index, &chord)) = chord else {
+            return Some(event);
+        };
+
+        for (suppressed, pins) in self.suppressed.iter_mut().zip(chord.as_bytes()) {
+            *suppressed |= pins;
+        }
+
+        Some(Event {
+            pin: event.pin,
+            edge: Edge::Chord(chord_index),
+        })
+    }
+}
+
+impl<const CHAIN_LENGTH: usize, const CAPACITY: usize> Default for Chords<CHAIN_LENGTH, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the number of pins set in `data`.
+fn pin_count(data: &[u8]) -> u32 {
+    data.iter().map(|byte| byte.count_ones()).sum()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Transition of a pin with the time it was detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InputEvent {
+    /// Pin number.
+    pub pin: usize,
+
+    /// Kind of the event.
+    pub edge: Edge,
+
+    /// Time of the scan detecting the transition, in ticks.
+    pub timestamp: u32,
+}
+
+impl InputEvent {
+    /// Placeholder for unused queue slots.
+    const EMPTY: Self = Self {
+        pin: 0,
+        edge: Edge::Pressed,
+        timestamp: 0,
+    };
+}
+
+/// Bounded queue of input events, accumulating the transitions of several
+/// scans until they are drained, e.g. by a slower application task.
+///
+/// The queue holds up to `CAPACITY` events without allocating. Events
+/// pushed while it is full are dropped and counted.
+#[derive(Debug, Clone)]
+pub struct EventQueue<const CAPACITY: usize> {
+    /// Ring buffer of the events.
+    events: [InputEvent; CAPACITY],
+
+    /// Index of the oldest event.
+    head: usize,
+
+    /// Number of queued events.
+    len: usize,
+
+    /// Number of events dropped since the last call of `take_dropped()`.
+    dropped: usize,
+
+    /// Waker of the task waiting for an event.
+    #[cfg(feature = "async")]
+    waker: WakerRegistration,
+}
+
+impl<const CAPACITY: usize> EventQueue<CAPACITY> {
+    /// Creates an empty queue.
+    pub const fn new() -> Self {
+        Self {
+            events: [InputEvent::EMPTY; CAPACITY],
+            head: 0,
+            len: 0,
+            dropped: 0,
+            #[cfg(feature = "async")]
+            waker: WakerRegistration::new(),
+        }
+    }
+
+    /// Appends an event.
+    ///
+    /// Returns the event as error if the queue is full.
+    pub fn push(&mut self, event: InputEvent) -> Result<(), InputEvent> {
+        if self.len >= CAPACITY {
+            self.dropped = self.dropped.saturating_add(1);
+            return Err(event);
+        }
+
+        self.events[(self.head + self.len) % CAPACITY] = event;
+        self.len += 1;
+
+        #[cfg(feature = "async")]
+        self.waker.wake();
+
+        Ok(())
+    }
+
+    /// Appends the transitions of a scan, all with the same timestamp.
+    ///
+    /// Returns the number of dropped events.
+    pub fn push_events(&mut self, events: impl Iterator<Item = Event>, timestamp: u32) -> usize {
+        events
+            .filter(|event| {
+                self.push(InputEvent {
+                    pin: event.pin,
+                    edge: event.edge,
+                    timestamp,
+                })
+                .is_err()
+            })
+            .count()
+    }
+
+    /// Appends the transitions of a scan, timestamped with the current time
+    /// of `clock`.
+    ///
+    /// Returns the number of dropped events.
+    pub fn record(&mut self, events: impl Iterator<Item = Event>, clock: &impl Now) -> usize {
+        self.push_events(events, clock.now())
+    }
+
+    /// Removes and returns the oldest event, `None` if the queue is empty.
+    pub fn pop(&mut self) -> Option<InputEvent> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let event = self.events[self.head];
+        self.head = (self.head + 1) % CAPACITY;
+        self.len -= 1;
+
+        Some(event)
+    }
+
+    /// Returns the oldest event without removing it.
+    pub fn peek(&self) -> Option<&InputEvent> {
+        (self.len > 0).then(|| &self.events[self.head])
+    }
+
+    /// Returns an iterator removing the events from oldest to newest.
+    pub fn drain(&mut self) -> impl Iterator<Item = InputEvent> + '_ {
+        core::iter::from_fn(|| self.pop())
+    }
+
+    /// Returns the number of queued events.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no events are queued.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if no more events can be queued.
+    pub fn is_full(&self) -> bool {
+        self.len >= CAPACITY
+    }
+
+    /// Returns the maximum number of queued events.
+    pub fn capacity(&self) -> usize {
+        CAPACITY
+    }
+
+    /// Removes all events.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Returns the number of events dropped because the queue was full and
+    /// resets it.
+    pub fn take_dropped(&mut self) -> usize {
+        core::mem::take(&mut self.dropped)
+    }
+
+    /// Removes and returns the oldest event, or registers the waker of `cx`
+    /// to be woken by the next push if the queue is empty.
+    #[cfg(feature = "async")]
+    pub fn poll_next_event(&mut self, cx: &mut Context<'_>) -> Poll<InputEvent> {
+        match self.pop() {
+            Some(event) => Poll::Ready(event),
+            None => {
+                self.waker.register(cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Waits for the next event of a queue shared with the scanning task and
+    /// removes it.
+    #[cfg(feature = "async")]
+    pub async fn next_event(queue: &RefCell<Self>) -> InputEvent {
+        core::future::poll_fn(|cx| queue.borrow_mut().poll_next_event(cx)).await
+    }
+}
+
+impl<const CAPACITY: usize> Default for EventQueue<CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// Returns the events of the transitions from `previous` to `states`.
+    fn events<const CHAIN_LENGTH: usize>(
+        states: [u8; CHAIN_LENGTH],
+        previous: [u8; CHAIN_LENGTH],
+    ) -> Events<CHAIN_LENGTH> {
+        Events::new(
+            &Snapshot::from_bytes(states),
+            &Snapshot::from_bytes(previous),
+        )
+    }
+
+    /// Returns an event.
+    fn event(pin: usize, edge: Edge) -> Event {
+        Event { pin, edge }
+    }
+
+    #[test]
+    fn transitions_in_pin_order() {
+        let events: Vec<_> = events([0x02, 0x00, 0x80], [0x01, 0x00, 0x00]).collect();
+
+        assert_eq!(
+            events,
+            [
+                event(0, Edge::Released),
+                event(1, Edge::Pressed),
+                event(23, Edge::Pressed),
+            ]
+        );
+    }
+
+    #[test]
+    fn pins_in_last_chip() {
+        let states = Snapshot::from_bytes([0x00, 0x00, 0x81]);
+        let gestures = [
+            Snapshot::default(),
+            Snapshot::from_bytes([0x00, 0x00, 0x40]),
+            Snapshot::default(),
+        ];
+        let events: Vec<_> =
+            Events::with_gestures(&states, &Snapshot::default(), &gestures).collect();
+
+        assert_eq!(
+            events,
+            [
+                event(16, Edge::Pressed),
+                event(22, Edge::LongPress),
+                event(23, Edge::Pressed),
+            ]
+        );
+    }
+
+    #[test]
+    fn gestures_follow_transition() {
+        let states = Snapshot::from_bytes([0x08, 0x02]);
+        let gestures = [
+            Snapshot::from_bytes([0x08, 0x00]),
+            Snapshot::from_bytes([0x00, 0x02]),
+            Snapshot::from_bytes([0x08, 0x02]),
+        ];
+        let events: Vec<_> =
+            Events::with_gestures(&states, &Snapshot::from_bytes([0x00, 0x02]), &gestures)
+                .collect();
+
+        assert_eq!(
+            events,
+            [
+                event(3, Edge::Pressed),
+                event(3, Edge::DoubleClick),
+                event(3, Edge::Repeat),
+                event(9, Edge::LongPress),
+                event(9, Edge::Repeat),
+            ]
+        );
+    }
+
+    #[test]
+    fn largest_chord_wins() {
+        let mut chords = Chords::<1, 4>::new();
+        assert_eq!(chords.add(&[0, 1]), Ok(0));
+        assert_eq!(chords.add(&[0, 1, 2]), Ok(1));
+        assert_eq!(chords.add(&[1, 2]), Ok(2));
+        assert_eq!(chords.add(&[4, 5]), Ok(3));
+
+        let pressed: Vec<_> = chords.filter(events([0x05], [0x00])).collect();
+        assert_eq!(pressed, [event(0, Edge::Pressed), event(2, Edge::Pressed)]);
+
+        let completed: Vec<_> = chords.filter(events([0x07], [0x05])).collect();
+        assert_eq!(completed, [event(1, Edge::Chord(1))]);
+    }
+
+    #[test]
+    fn first_chord_wins_tie() {
+        let mut chords = Chords::<1, 2>::new();
+        assert_eq!(chords.add(&[3, 4]), Ok(0));
+        assert_eq!(chords.add(&[4, 5]), Ok(1));
+
+        assert_eq!(chords.filter(events([0x28], [0x00])).count(), 2);
+
+        let completed: Vec<_> = chords.filter(events([0x38], [0x28])).collect();
+        assert_eq!(completed, [event(4, Edge::Chord(0))]);
+    }
+
+    #[test]
+    fn chord_suppressed_until_released() {
+        let mut chords = Chords::<1, 1>::new();
+        assert_eq!(chords.add(&[0, 1]), Ok(0));
+
+        let completed: Vec<_> = chords.filter(events([0x03], [0x00])).collect();
+        assert_eq!(completed, [event(0, Edge::Chord(0))]);
+
+        // Gestures and the release of a chord pin are suppressed.
+        let states = Snapshot::from_bytes([0x02]);
+        let gestures = [
+            Snapshot::default(),
+            Snapshot::from_bytes([0x02]),
+            Snapshot::default(),
+        ];
+        let held = Events::with_gestures(&states, &Snapshot::from_bytes([0x03]), &gestures);
+        assert_eq!(chords.filter(held).count(), 0);
+
+        // Pin 0 was released, so its next press is reported again.
+        let passed: Vec<_> = chords.filter(events([0x06], [0x02])).collect();
+        assert_eq!(passed, [event(2, Edge::Pressed)]);
+        let passed: Vec<_> = chords.filter(events([0x02], [0x06])).collect();
+        assert_eq!(passed, [event(2, Edge::Released)]);
+        let passed: Vec<_> = chords.filter(events([0x00], [0x02])).collect();
+        assert_eq!(passed, []);
+        let passed: Vec<_> = chords.filter(events([0x02], [0x00])).collect();
+        assert_eq!(passed, [event(1, Edge::Pressed)]);
+    }
+
+    #[test]
+    fn chord_definitions() {
+        let mut chords = Chords::<1, 2>::new();
+
+        assert_eq!(chords.add(&[0, 8]), Err(Error::PinOutOfRange));
+        assert_eq!(chords.add(&[0, 0]), Err(Error::InvalidRange));
+        assert_eq!(chords.add(&[0, 1]), Ok(0));
+        assert_eq!(chords.add(&[2, 3, 4]), Ok(1));
+        assert_eq!(chords.add(&[5, 6]), Err(Error::BufferTooSmall));
+        assert_eq!(chords.len(), 2);
+        assert_eq!(chords.get(1), Some(Snapshot::from_bytes([0x1C])));
+        assert_eq!(chords.get(2), None);
+
+        chords.clear();
+        assert!(chords.is_empty());
+        assert_eq!(chords.add(&[5, 6]), Ok(0));
+    }
+
+    /// Returns an input event of a press.
+    fn input_event(pin: usize, timestamp: u32) -> InputEvent {
+        InputEvent {
+            pin,
+            edge: Edge::Pressed,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn queue_wraps_around() {
+        let mut queue = EventQueue::<3>::new();
+
+        for pin in 0..3 {
+            assert_eq!(queue.push(input_event(pin, 0)), Ok(()));
+        }
+
+        assert!(queue.is_full());
+        assert_eq!(queue.pop(), Some(input_event(0, 0)));
+        assert_eq!(queue.pop(), Some(input_event(1, 0)));
+        assert_eq!(queue.push(input_event(3, 1)), Ok(()));
+        assert_eq!(queue.push(input_event(4, 1)), Ok(()));
+        assert_eq!(queue.peek(), Some(&input_event(2, 0)));
+        assert_eq!(queue.len(), 3);
+
+        let drained: Vec<_> = queue.drain().map(|event| event.pin).collect();
+        assert_eq!(drained, [2, 3, 4]);
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn full_queue_counts_dropped_events() {
+        let mut queue = EventQueue::<1>::new();
+
+        assert_eq!(queue.push(input_event(0, 0)), Ok(()));
+        assert_eq!(queue.push(input_event(1, 0)), Err(input_event(1, 0)));
+        assert_eq!(queue.push(input_event(2, 0)), Err(input_event(2, 0)));
+        assert_eq!(queue.take_dropped(), 2);
+        assert_eq!(queue.take_dropped(), 0);
+        assert_eq!(queue.pop(), Some(input_event(0, 0)));
+    }
+
+    #[test]
+    fn push_events_returns_dropped() {
+        let mut queue = EventQueue::<2>::new();
+
+        assert_eq!(queue.push_events(events([0x07], [0x00]), 42), 1);
+        assert_eq!(queue.take_dropped(), 1);
+
+        let queued: Vec<_> = queue.drain().collect();
+        assert_eq!(queued, [input_event(0, 42), input_event(1, 42)]);
+    }
+
+    #[test]
+    fn queue_without_capacity() {
+        let mut queue = EventQueue::<0>::new();
+
+        assert!(queue.is_empty());
+        assert!(queue.is_full());
+        assert_eq!(queue.push(input_event(0, 0)), Err(input_event(0, 0)));
+        assert_eq!(queue.push_events(events([0x03], [0x00]), 0), 2);
+        assert_eq!(queue.take_dropped(), 3);
+        assert_eq!(queue.pop(), None);
+        assert_eq!(queue.peek(), None);
+    }
+}
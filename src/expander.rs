@@ -0,0 +1,150 @@
+//! Port driver abstraction shared with I/O expanders
+//!
+//! The trait mirrors the port driver of the `port-expander` crate, which is
+//! not public there, so code generic over expander ports can be adapted to
+//! the chains with a thin wrapper. The masks are limited to 32 bits, so only
+//! the first 32 pins of a chain can be accessed this way.
+
+use core::convert::Infallible;
+
+use crate::{
+    inout::DualChain,
+    input::{self, GetInput},
+    output::{self, GetOutput, SetOutput},
+    Length,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Trait for accessing several pins of a port at once via bit masks.
+///
+/// Bit `n` of a mask refers to pin `n`, numbered the same way as for the
+/// `Pin` types.
+pub trait PortDriver {
+    /// Error type of the driver.
+    type Error;
+
+    /// Sets the pins in `mask_high` to high and the pins in `mask_low` to low.
+    fn set(&mut self, mask_high: u32, mask_low: u32) -> Result<(), Self::Error>;
+
+    /// Returns the pins from the masks whose commanded state matches.
+    fn is_set(&mut self, mask_high: u32, mask_low: u32) -> Result<u32, Self::Error>;
+
+    /// Returns the pins from the masks whose actual state matches.
+    fn get(&mut self, mask_high: u32, mask_low: u32) -> Result<u32, Self::Error>;
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Returns the number of pins of `chips` chips accessible via the masks.
+fn pin_count(chips: usize) -> usize {
+    (chips * 8).min(32)
+}
+
+/// Sets the outputs for all pins in `mask_high` and `mask_low`.
+fn set_outputs(chain: &mut (impl SetOutput + Length), mask_high: u32, mask_low: u32) {
+    for pin in 0..pin_count(chain.output_len()) {
+        if mask_high & (1 << pin) != 0 {
+            chain.set_output_unchecked(pin, true);
+        } else if mask_low & (1 << pin) != 0 {
+            chain.set_output_unchecked(pin, false);
+        }
+    }
+}
+
+/// Returns a mask with the bits set for all pins meeting the expected state.
+fn match_states(pins: usize, state: impl Fn(usize) -> bool, mask_high: u32, mask_low: u32) -> u32 {
+    (0..pins).fold(0, |result, pin| {
+        let state = state(pin);
+
+        if (mask_high & (1 << pin) != 0 && state) || (mask_low & (1 << pin) != 0 && !state) {
+            result | (1 << pin)
+        } else {
+            result
+        }
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Input chains can't drive their pins, so `set()` has no effect and
+/// `is_set()` reports the input states the same way as `get()`.
+impl<Transport, const CHAIN_LENGTH: usize> PortDriver for input::Chain<Transport, CHAIN_LENGTH> {
+    type Error = Infallible;
+
+    fn set(&mut self, _mask_high: u32, _mask_low: u32) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn is_set(&mut self, mask_high: u32, mask_low: u32) -> Result<u32, Self::Error> {
+        self.get(mask_high, mask_low)
+    }
+
+    fn get(&mut self, mask_high: u32, mask_low: u32) -> Result<u32, Self::Error> {
+        let state = |pin| self.get_input_unchecked(pin);
+        Ok(match_states(
+            pin_count(self.input_len()),
+            state,
+            mask_high,
+            mask_low,
+        ))
+    }
+}
+
+/// The states are buffered, so `get()` reports the same as `is_set()`.
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin> PortDriver
+    for output::Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+{
+    type Error = Infallible;
+
+    fn set(&mut self, mask_high: u32, mask_low: u32) -> Result<(), Self::Error> {
+        set_outputs(self, mask_high, mask_low);
+        Ok(())
+    }
+
+    fn is_set(&mut self, mask_high: u32, mask_low: u32) -> Result<u32, Self::Error> {
+        let state = |pin| self.get_output_unchecked(pin);
+        Ok(match_states(
+            pin_count(self.output_len()),
+            state,
+            mask_high,
+            mask_low,
+        ))
+    }
+
+    fn get(&mut self, mask_high: u32, mask_low: u32) -> Result<u32, Self::Error> {
+        self.is_set(mask_high, mask_low)
+    }
+}
+
+/// `set()` and `is_set()` access the outputs, `get()` reports the inputs.
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin> PortDriver
+    for DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    type Error = Infallible;
+
+    fn set(&mut self, mask_high: u32, mask_low: u32) -> Result<(), Self::Error> {
+        set_outputs(self, mask_high, mask_low);
+        Ok(())
+    }
+
+    fn is_set(&mut self, mask_high: u32, mask_low: u32) -> Result<u32, Self::Error> {
+        let state = |pin| self.get_output_unchecked(pin);
+        Ok(match_states(
+            pin_count(self.output_len()),
+            state,
+            mask_high,
+            mask_low,
+        ))
+    }
+
+    fn get(&mut self, mask_high: u32, mask_low: u32) -> Result<u32, Self::Error> {
+        let state = |pin| self.get_input_unchecked(pin);
+        Ok(match_states(
+            pin_count(self.input_len()),
+            state,
+            mask_high,
+            mask_low,
+        ))
+    }
+}
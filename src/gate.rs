@@ -0,0 +1,210 @@
+//! Gating of updates by an activity signal
+//!
+//! Some boards OR all inputs into an activity line, e.g. via a 74HC30 or
+//! open-drain diodes, connected to a GPIO of the controller. An
+//! [`ActivityGate`] only updates a chain after activity was signalled by
+//! such a pin or by a flag set from an interrupt handler, which saves the
+//! shifting of long idle chains. A maximum idle interval forces updates
+//! regardless, e.g. to catch missed interrupts.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use embedded_hal::digital::InputPin;
+
+use crate::{clock::Now, Update};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Trait to be implemented by activity sources.
+pub trait Activity {
+    /// Returns `true` if activity was signalled since the previous call.
+    fn take_activity(&mut self) -> bool;
+}
+
+/// Flags set by an interrupt handler, cleared when taken.
+impl Activity for &AtomicBool {
+    fn take_activity(&mut self) -> bool {
+        // Load and store instead of swap, which is not available on all
+        // targets. Activity signalled in between is caught by the update.
+        let activity = self.load(Ordering::Acquire);
+
+        if activity {
+            self.store(false, Ordering::Release);
+        }
+
+        activity
+    }
+}
+
+/// Flags set by an interrupt handler, cleared when taken.
+#[cfg(feature = "portable-atomic")]
+impl Activity for &portable_atomic::AtomicBool {
+    fn take_activity(&mut self) -> bool {
+        self.swap(false, Ordering::AcqRel)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Activity line connected to a GPIO pin, signalling activity while at its
+/// active level.
+#[derive(Debug)]
+pub struct ActivityPin<Pin> {
+    /// Pin reading the activity line.
+    pin: Pin,
+
+    /// Whether the line is low while active.
+    active_low: bool,
+}
+
+impl<Pin> ActivityPin<Pin> {
+    /// Creates a new activity pin, active while high.
+    pub fn new(pin: Pin) -> Self {
+        Self {
+            pin,
+            active_low: false,
+        }
+    }
+
+    /// Creates a new activity pin, active while low.
+    pub fn new_active_low(pin: Pin) -> Self {
+        Self {
+            pin,
+            active_low: true,
+        }
+    }
+
+    /// Frees the activity pin and returns the pin.
+    pub fn free(self) -> Pin {
+        self.pin
+    }
+}
+
+impl<Pin> Activity for ActivityPin<Pin>
+where
+    Pin: InputPin,
+{
+    /// Returns `true` while the line is at its active level or if the pin
+    /// could not be read.
+    fn take_activity(&mut self) -> bool {
+        self.pin
+            .is_high()
+            .map_or(true, |high| high != self.active_low)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Gate updating a chain only after activity was signalled.
+#[derive(Debug)]
+pub struct ActivityGate<Source> {
+    /// Source of the activity signal.
+    source: Source,
+
+    /// Maximum ticks between two updates, `None` if unlimited.
+    max_idle: Option<u32>,
+
+    /// Time of the last update, `None` if not updated yet.
+    updated_at: Option<u32>,
+
+    /// Whether activity was taken but not followed by an update yet.
+    pending: bool,
+}
+
+impl<Source> ActivityGate<Source> {
+    /// Creates a new gate with an activity source.
+    ///
+    /// The first check is always due, so the chain is read initially.
+    pub fn new(source: Source) -> Self {
+        Self {
+            source,
+            max_idle: None,
+            updated_at: None,
+            pending: false,
+        }
+    }
+
+    /// Frees the gate and returns the activity source.
+    pub fn free(self) -> Source {
+        self.source
+    }
+
+    /// Returns a reference to the activity source.
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Returns a mutable reference to the activity source.
+    pub fn source_mut(&mut self) -> &mut Source {
+        &mut self.source
+    }
+
+    /// Returns the maximum ticks between two updates, `None` if unlimited.
+    pub fn max_idle(&self) -> Option<u32> {
+        self.max_idle
+    }
+
+    /// Sets the maximum ticks between two updates, `None` by default.
+    ///
+    /// Once elapsed, an update is due without activity.
+    pub fn set_max_idle(&mut self, max_idle: Option<u32>) {
+        self.max_idle = max_idle;
+    }
+
+    /// Marks the chain as updated at the time of `clock`, e.g. after an
+    /// incremental or asynchronous update done by the caller.
+    pub fn mark_updated(&mut self, clock: &impl Now) {
+        self.updated_at = Some(clock.now());
+        self.pending = false;
+    }
+
+    /// Requests an update regardless of the activity source.
+    pub fn trigger(&mut self) {
+        self.pending = true;
+    }
+}
+
+impl<Source> ActivityGate<Source>
+where
+    Source: Activity,
+{
+    /// Returns `true` if an update is due, either because activity was
+    /// signalled or the maximum idle interval has elapsed.
+    ///
+    /// Taken activity is kept until `mark_updated()` is called.
+    pub fn is_due(&mut self, clock: &impl Now) -> bool {
+        if self.source.take_activity() {
+            self.pending = true;
+        }
+
+        self.pending
+            || match (self.updated_at, self.max_idle) {
+                (None, _) => true,
+                (Some(timestamp), Some(max_idle)) => clock.elapsed_since(timestamp) >= max_idle,
+                (Some(_), None) => false,
+            }
+    }
+
+    /// Updates `chain` if an update is due and returns `true` if it was
+    /// updated.
+    ///
+    /// If the update fails, the error is returned and the next call
+    /// retries it.
+    pub fn update<Chain>(
+        &mut self,
+        chain: &mut Chain,
+        clock: &impl Now,
+    ) -> Result<bool, Chain::Error>
+    where
+        Chain: Update + ?Sized,
+    {
+        if !self.is_due(clock) {
+            return Ok(false);
+        }
+
+        chain.update()?;
+        self.mark_updated(clock);
+
+        Ok(true)
+    }
+}
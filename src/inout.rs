@@ -1,16 +1,2184 @@
 //! Dual chain of 8-bit PISO & SIPO shift registers (e.g. 74HC165/74HC595) for digital output
 
-use embedded_hal::digital::{InputPin, OutputPin};
+use core::cell::RefCell;
+#[cfg(any(feature = "eh1", feature = "eh02"))]
+use core::convert::Infallible;
+#[cfg(feature = "async")]
+use core::task::Waker;
 
-use crate::{input::GetInput, output::SetOutput, Error, Length};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+    pwm::SetDutyCycle,
+    spi::SpiBus,
+};
+
+use crate::{
+    age::UpdateAge,
+    event::Events,
+    input::{invert, GetInput, Snapshot},
+    output::{encode, ClearError, EncodeOutput, GetOutput, SetOutput, Word},
+    pulse::Pulses,
+    register_bytes,
+    transport::{
+        ClockConfig, LatchPolarity, NoDelay, NoPin, Settle, ShiftTransport, SpiError, StepTransport,
+    },
+    BitOrder, ChipOrder, Error, Length, Update,
+};
+#[cfg(feature = "async")]
+use crate::{
+    input::WakeOnUpdate, transport::AsyncShiftTransport, waker::WakerRegistration, AsyncUpdate,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Function called by a dual chain after an update changed inputs, with the
+/// changes made by the update.
+pub type ChangeObserver<const CHAIN_LENGTH: usize> = fn(change_set: &ChangeSet<CHAIN_LENGTH>);
+
+/// Changes made by an update of a dual chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChangeSet<const CHAIN_LENGTH: usize> {
+    /// Input states before the update.
+    previous_inputs: Snapshot<CHAIN_LENGTH>,
+
+    /// Input states after the update.
+    inputs: Snapshot<CHAIN_LENGTH>,
+
+    /// Whether the update shifted changed output states.
+    outputs_changed: bool,
+}
+
+impl<const CHAIN_LENGTH: usize> ChangeSet<CHAIN_LENGTH> {
+    /// Creates an empty change set with all inputs low.
+    const fn new() -> Self {
+        Self {
+            previous_inputs: Snapshot::from_bytes([0; CHAIN_LENGTH]),
+            inputs: Snapshot::from_bytes([0; CHAIN_LENGTH]),
+            outputs_changed: false,
+        }
+    }
+
+    /// Returns the input states after the update.
+    pub fn inputs(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.inputs
+    }
+
+    /// Returns the input states before the update.
+    pub fn previous_inputs(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.previous_inputs
+    }
+
+    /// Returns the inputs changed by the update.
+    pub fn changes(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.inputs.changes(&self.previous_inputs)
+    }
+
+    /// Returns the inputs that changed from low to high by the update.
+    pub fn rising_edges(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.inputs.rising_edges(&self.previous_inputs)
+    }
+
+    /// Returns the inputs that changed from high to low by the update.
+    pub fn falling_edges(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.inputs.falling_edges(&self.previous_inputs)
+    }
+
+    /// Returns an iterator over the input transitions of the update.
+    pub fn events(&self) -> Events<CHAIN_LENGTH> {
+        self.inputs.events(&self.previous_inputs)
+    }
+
+    /// Returns `true` if the update flushed changed output states, i.e. the
+    /// output buffer differed from the one shifted by the previous update.
+    ///
+    /// Always `true` for the first update and after a failed one.
+    pub fn outputs_changed(&self) -> bool {
+        self.outputs_changed
+    }
+
+    /// Returns `true` if neither inputs nor outputs changed.
+    pub fn is_empty(&self) -> bool {
+        self.inputs == self.previous_inputs && !self.outputs_changed
+    }
+}
+
+impl<const CHAIN_LENGTH: usize> Default for ChangeSet<CHAIN_LENGTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Dual chain of SIPO/PISO shift registers.
+///
+/// The PISO chain has `IN_LEN` and the SIPO chain `OUT_LEN` chips, the same
+/// number by default. With different lengths, the updates shift the longer
+/// of both and the output data is preceded by padding if the PISO chain is
+/// longer, so it ends up in the SIPO chips.
+///
+/// Optional output enable and clear pins can be attached with
+/// `with_oe_pin()` and `with_clear_pin()`.
+pub struct DualChain<
+    Transport,
+    const IN_LEN: usize,
+    const OUT_LEN: usize = IN_LEN,
+    OePin = NoPin,
+    ClearPin = NoPin,
+> {
+    /// Transport shifting the data from and into the chips.
+    transport: Transport,
+
+    /// Buffer storing the data read from pins.
+    data_in_buffer: [u8; IN_LEN],
+
+    /// Buffer storing the data to output.
+    data_out_buffer: [u8; OUT_LEN],
+
+    /// Number of bits already shifted by an incremental update.
+    shift_position: usize,
+
+    /// Input data shifted in by an incremental update, in wire order.
+    shift_buffer: [u8; IN_LEN],
+
+    /// Order in which the bits of each chip are shifted.
+    bit_order: BitOrder,
+
+    /// Order in which the chips are numbered.
+    chip_order: ChipOrder,
+
+    /// Number of bytes per chip.
+    register_bytes: usize,
+
+    /// Outputs inverted at shift time, in the order of the buffer.
+    output_inversion: [u8; OUT_LEN],
+
+    /// Inputs inverted when shifted in, in pin order.
+    input_inversion: [u8; IN_LEN],
+
+    /// Age of the input data.
+    age: UpdateAge,
+
+    /// Changes made by the last completed update.
+    change_set: ChangeSet<IN_LEN>,
+
+    /// Inputs high after any update since they were cleared.
+    latched_inputs: [u8; IN_LEN],
+
+    /// Function called after an update changed inputs.
+    change_observer: Option<ChangeObserver<IN_LEN>>,
+
+    /// Output buffer shifted by the last completed update, `None` if the
+    /// outputs are unknown.
+    shifted_outputs: Option<[u8; OUT_LEN]>,
+
+    /// Whether the current update shifts changed output states.
+    outputs_changed: bool,
+
+    /// Timed pulses of the outputs.
+    pulses: Pulses<OUT_LEN>,
+
+    /// Input pin connected to the serial output of the last SIPO chip.
+    loopback_pin: Option<usize>,
+
+    /// State expected at the loopback pin after the current update.
+    loopback_expected: Option<bool>,
+
+    /// First bit shifted out by the current update.
+    first_out_bit: bool,
+
+    /// Task waiting for the next update.
+    #[cfg(feature = "async")]
+    waker: WakerRegistration,
+
+    /// Data as shifted, transferred by DMA.
+    #[cfg(feature = "embedded-dma")]
+    dma_buffers: DmaBuffers<IN_LEN, OUT_LEN>,
+
+    /// Pin for the active-low output enable signal.
+    oe_pin: OePin,
+
+    /// Pin for the active-low shift register clear signal.
+    clear_pin: ClearPin,
+}
+
+/// Dual chain of SIPO/PISO shift registers using bit-banged pins.
+pub type BitBangDualChain<
+    ClockPin,
+    LatchPin,
+    DataInPin,
+    DataOutPin,
+    const IN_LEN: usize,
+    const OUT_LEN: usize = IN_LEN,
+> = DualChain<BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin>, IN_LEN, OUT_LEN>;
+
+/// Dual chain of SIPO/PISO shift registers using bit-banged pins with
+/// separate load and latch pins.
+pub type BitBangSplitDualChain<
+    ClockPin,
+    LoadPin,
+    LatchPin,
+    DataInPin,
+    DataOutPin,
+    const IN_LEN: usize,
+    const OUT_LEN: usize = IN_LEN,
+> = DualChain<
+    BitBangSplitTransport<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin>,
+    IN_LEN,
+    OUT_LEN,
+>;
+
+/// Dual chain of SIPO/PISO shift registers driven by a hardware SPI bus.
+pub type DualSpiChain<Spi, LatchPin, const IN_LEN: usize, const OUT_LEN: usize = IN_LEN> =
+    DualChain<SpiTransport<Spi, LatchPin>, IN_LEN, OUT_LEN>;
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize> DualChain<Transport, IN_LEN, OUT_LEN> {
+    /// Creates a new chain by consuming a transport.
+    pub fn with_transport(transport: Transport) -> Self {
+        Self {
+            transport,
+            data_in_buffer: [0; IN_LEN],
+            data_out_buffer: [0; OUT_LEN],
+            shift_position: 0,
+            shift_buffer: [0; IN_LEN],
+            bit_order: BitOrder::MsbFirst,
+            chip_order: ChipOrder::NearestFirst,
+            register_bytes: 1,
+            output_inversion: [0; OUT_LEN],
+            input_inversion: [0; IN_LEN],
+            age: UpdateAge::new(),
+            change_set: ChangeSet::new(),
+            latched_inputs: [0; IN_LEN],
+            change_observer: None,
+            shifted_outputs: None,
+            outputs_changed: false,
+            pulses: Pulses::new(),
+            loopback_pin: None,
+            loopback_expected: None,
+            first_out_bit: false,
+            #[cfg(feature = "async")]
+            waker: WakerRegistration::new(),
+            #[cfg(feature = "embedded-dma")]
+            dma_buffers: DmaBuffers::new(),
+            oe_pin: NoPin,
+            clear_pin: NoPin,
+        }
+    }
+
+    /// Frees the chain and returns the transport.
+    pub fn into_transport(self) -> Transport {
+        self.transport
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, NoPin, ClearPin>
+{
+    /// Attaches a pin driving the active-low output enable input (/OE) of
+    /// the SIPO chips.
+    ///
+    /// The pin is not changed, so the outputs can be kept disabled by a
+    /// pull-up until `enable_outputs()` is called after the first update.
+    /// A PWM channel can be attached instead of a pin to dim the outputs
+    /// with `set_brightness()`.
+    pub fn with_oe_pin<OePin>(
+        self,
+        oe_pin: OePin,
+    ) -> DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin> {
+        self.map_parts(|transport, _, clear_pin| (transport, oe_pin, clear_pin, ()))
+            .0
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, NoPin>
+{
+    /// Attaches a pin driving the active-low shift register clear input
+    /// (/SRCLR or /MR) of the SIPO chips, used by `hardware_clear()`.
+    ///
+    /// The pin is not changed, it has to be high for the chips to shift.
+    pub fn with_clear_pin<ClearPin>(
+        self,
+        clear_pin: ClearPin,
+    ) -> DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+    where
+        ClearPin: OutputPin,
+    {
+        self.map_parts(|transport, oe_pin, _| (transport, oe_pin, clear_pin, ()))
+            .0
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+where
+    OePin: OutputPin,
+{
+    /// Enables the outputs of the SIPO chips by driving the output enable
+    /// pin low.
+    pub fn enable_outputs(&mut self) -> Result<(), OePin::Error> {
+        self.oe_pin.set_low()
+    }
+
+    /// Disables the outputs of the SIPO chips by driving the output enable
+    /// pin high, putting them in high-impedance state.
+    pub fn disable_outputs(&mut self) -> Result<(), OePin::Error> {
+        self.oe_pin.set_high()
+    }
+
+    /// Detaches the output enable pin and returns it with the chain.
+    pub fn release_oe_pin(
+        self,
+    ) -> (
+        DualChain<Transport, IN_LEN, OUT_LEN, NoPin, ClearPin>,
+        OePin,
+    ) {
+        self.map_parts(|transport, oe_pin, clear_pin| (transport, NoPin, clear_pin, oe_pin))
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+where
+    OePin: SetDutyCycle,
+{
+    /// Sets the brightness of the outputs of the SIPO chips by the duty
+    /// cycle of the PWM channel attached as output enable pin.
+    ///
+    /// `brightness` ranges from 0 (off) to `max_brightness()` (fully on),
+    /// larger values are clamped.
+    pub fn set_brightness(&mut self, brightness: u16) -> Result<(), OePin::Error> {
+        let max_duty_cycle = self.oe_pin.max_duty_cycle();
+
+        // The output enable signal is active-low
+        self.oe_pin
+            .set_duty_cycle(max_duty_cycle - brightness.min(max_duty_cycle))
+    }
+
+    /// Returns the brightness of fully enabled outputs.
+    pub fn max_brightness(&self) -> u16 {
+        self.oe_pin.max_duty_cycle()
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+where
+    ClearPin: OutputPin,
+{
+    /// Detaches the clear pin and returns it with the chain.
+    pub fn release_clear_pin(self) -> (DualChain<Transport, IN_LEN, OUT_LEN, OePin>, ClearPin) {
+        self.map_parts(|transport, oe_pin, clear_pin| (transport, oe_pin, NoPin, clear_pin))
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+where
+    Transport: ShiftTransport,
+    ClearPin: OutputPin,
+{
+    /// Clears all outputs by pulsing the clear pin and latching the cleared
+    /// SIPO shift registers, which is faster than shifting zeros through the
+    /// chain. The inputs are not read.
+    ///
+    /// The outputs are driven low, so the output buffer is cleared except
+    /// for inverted outputs, which read as set afterwards.
+    pub fn hardware_clear(&mut self) -> Result<(), ClearError<ClearPin::Error, Transport::Error>> {
+        self.shift_position = 0;
+        self.shifted_outputs = None;
+
+        self.clear_pin.set_low().map_err(ClearError::Pin)?;
+        self.clear_pin.set_high().map_err(ClearError::Pin)?;
+        self.transport
+            .transfer(&mut [], &[])
+            .map_err(ClearError::Transport)?;
+
+        self.data_out_buffer = self.output_inversion;
+        self.shifted_outputs = Some(self.data_out_buffer);
+
+        Ok(())
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    /// Moves the chain into a chain with another transport or other control
+    /// pins, returning an additional value from `f`.
+    fn map_parts<NewTransport, NewOePin, NewClearPin, R>(
+        self,
+        f: impl FnOnce(Transport, OePin, ClearPin) -> (NewTransport, NewOePin, NewClearPin, R),
+    ) -> (
+        DualChain<NewTransport, IN_LEN, OUT_LEN, NewOePin, NewClearPin>,
+        R,
+    ) {
+        let (transport, oe_pin, clear_pin, value) = f(self.transport, self.oe_pin, self.clear_pin);
+
+        let chain = DualChain {
+            transport,
+            data_in_buffer: self.data_in_buffer,
+            data_out_buffer: self.data_out_buffer,
+            shift_position: self.shift_position,
+            shift_buffer: self.shift_buffer,
+            bit_order: self.bit_order,
+            chip_order: self.chip_order,
+            register_bytes: self.register_bytes,
+            output_inversion: self.output_inversion,
+            input_inversion: self.input_inversion,
+            age: self.age,
+            change_set: self.change_set,
+            latched_inputs: self.latched_inputs,
+            change_observer: self.change_observer,
+            shifted_outputs: self.shifted_outputs,
+            outputs_changed: self.outputs_changed,
+            pulses: self.pulses,
+            loopback_pin: self.loopback_pin,
+            loopback_expected: self.loopback_expected,
+            first_out_bit: self.first_out_bit,
+            #[cfg(feature = "async")]
+            waker: self.waker,
+            #[cfg(feature = "embedded-dma")]
+            dma_buffers: self.dma_buffers,
+            oe_pin,
+            clear_pin,
+        };
+
+        (chain, value)
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    /// Returns the input buffer.
+    ///
+    /// Byte `i` holds the inputs of chip `i`, counted as set by the chip
+    /// order, with bit `n` being the state of pin `i * 8 + n`.
+    pub fn as_input_bytes(&self) -> &[u8] {
+        &self.data_in_buffer
+    }
+
+    /// Returns a snapshot of the input states.
+    pub fn snapshot(&self) -> Snapshot<IN_LEN> {
+        Snapshot::from_bytes(self.data_in_buffer)
+    }
+
+    /// Returns the changes made by the last completed update.
+    pub fn change_set(&self) -> ChangeSet<IN_LEN> {
+        self.change_set
+    }
+
+    /// Returns the inputs changed by the last completed update.
+    ///
+    /// The inputs are compared to the states after the previous update, or
+    /// all low before the first one.
+    pub fn changes(&self) -> Snapshot<IN_LEN> {
+        self.change_set.changes()
+    }
+
+    /// Returns the inputs that changed from low to high by the last
+    /// completed update.
+    pub fn rising_edges(&self) -> Snapshot<IN_LEN> {
+        self.change_set.rising_edges()
+    }
+
+    /// Returns the inputs that changed from high to low by the last
+    /// completed update.
+    pub fn falling_edges(&self) -> Snapshot<IN_LEN> {
+        self.change_set.falling_edges()
+    }
+
+    /// Returns an iterator over the input transitions of the last completed
+    /// update.
+    pub fn events(&self) -> Events<IN_LEN> {
+        self.change_set.events()
+    }
+
+    /// Sets a function called after each completed update that changed
+    /// inputs, e.g. to notify subsystems directly, `None` by default.
+    pub fn set_change_observer(&mut self, observer: Option<ChangeObserver<IN_LEN>>) {
+        self.change_observer = observer;
+    }
+
+    /// Returns the inputs that were high after any update since they were
+    /// cleared, so short pulses between two reads are not missed.
+    pub fn latched_inputs(&self) -> Snapshot<IN_LEN> {
+        Snapshot::from_bytes(self.latched_inputs)
+    }
+
+    /// Returns the latched inputs and clears them.
+    pub fn take_latched_inputs(&mut self) -> Snapshot<IN_LEN> {
+        Snapshot::from_bytes(core::mem::replace(&mut self.latched_inputs, [0; IN_LEN]))
+    }
+
+    /// Clears the latched state of an input.
+    pub fn clear_latched_input(&mut self, pin: usize) -> Result<(), Error> {
+        let chip = self
+            .latched_inputs
+            .get_mut(pin / 8)
+            .ok_or(Error::PinOutOfRange)?;
+        *chip &= !(1 << (pin % 8));
+
+        Ok(())
+    }
+
+    /// Returns the input buffer mutably, e.g. to be filled by external means.
+    ///
+    /// Byte `i` holds the inputs of chip `i`, counted as set by the chip
+    /// order, with bit `n` being the state of pin `i * 8 + n`.
+    pub fn as_mut_input_bytes(&mut self) -> &mut [u8] {
+        &mut self.data_in_buffer
+    }
+
+    /// Returns the number of PISO chips.
+    pub fn input_len(&self) -> usize {
+        IN_LEN
+    }
+
+    /// Returns the number of SIPO chips.
+    pub fn output_len(&self) -> usize {
+        OUT_LEN
+    }
+
+    /// Returns the output buffer.
+    ///
+    /// With `ChipOrder::NearestFirst`, the bytes are in the order they are
+    /// shifted out, so the first byte holds the outputs of the last chip.
+    /// Bit `n` of byte `i` is the state of pin `(OUT_LEN - i - 1) * 8 + n`
+    /// regardless of the chip order.
+    pub fn as_output_bytes(&self) -> &[u8] {
+        &self.data_out_buffer
+    }
+
+    /// Returns the output buffer mutably.
+    ///
+    /// With `ChipOrder::NearestFirst`, the bytes are in the order they are
+    /// shifted out, so the first byte holds the outputs of the last chip.
+    /// Bit `n` of byte `i` is the state of pin `(OUT_LEN - i - 1) * 8 + n`
+    /// regardless of the chip order.
+    pub fn as_mut_output_bytes(&mut self) -> &mut [u8] {
+        &mut self.data_out_buffer
+    }
+
+    /// Returns the order in which the bits of each chip are shifted.
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Sets the order in which the bits of each chip are shifted.
+    ///
+    /// Bit `n` of each buffer byte holds pin `n` of the chip regardless of
+    /// the bit order.
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) {
+        self.bit_order = bit_order;
+        self.shifted_outputs = None;
+    }
+
+    /// Returns the order in which the chips are numbered.
+    pub fn chip_order(&self) -> ChipOrder {
+        self.chip_order
+    }
+
+    /// Sets the order in which the chips are numbered, for the inputs and
+    /// the outputs alike.
+    ///
+    /// By default, pin 0 is on the chip nearest to the controller in both
+    /// directions. `ChipOrder::FarthestFirst` numbers the pins from the far
+    /// end of the chain instead. The buffers keep their layout, the order
+    /// is applied when shifting.
+    pub fn set_chip_order(&mut self, chip_order: ChipOrder) {
+        self.chip_order = chip_order;
+        self.shifted_outputs = None;
+    }
+
+    /// Returns the number of bits per chip.
+    pub fn register_bits(&self) -> usize {
+        self.register_bytes * 8
+    }
+
+    /// Sets the number of bits per chip at runtime, 8 by default.
+    ///
+    /// The chain length remains the number of bytes and the pins are numbered
+    /// consecutively across the chips. The width only changes how the bit
+    /// order applies, so chains of chips with different widths can be
+    /// used with `BitOrder::MsbFirst` and the default width. Returns an error
+    /// if the width is not a multiple of 8 or either chain length is not a
+    /// multiple of it.
+    pub fn set_register_bits(&mut self, bits: usize) -> Result<(), Error> {
+        self.register_bytes = register_bytes(bits, OUT_LEN)
+            .and(register_bytes(bits, IN_LEN))
+            .ok_or(Error::InvalidRange)?;
+        self.shifted_outputs = None;
+
+        Ok(())
+    }
+
+    /// Sets the outputs inverted at shift time, e.g. for active-low relays
+    /// or LEDs, so the buffer holds the logical states.
+    ///
+    /// Bit `n` of byte `i` inverts pin `i * 8 + n`, pins beyond `mask` are
+    /// not inverted. Returns an error if `mask` is longer than the chain.
+    pub fn set_output_inversion(&mut self, mask: &[u8]) -> Result<(), Error> {
+        if mask.len() > OUT_LEN {
+            return Err(Error::InvalidRange);
+        }
+
+        self.output_inversion = [0; OUT_LEN];
+
+        for (index, &mask) in mask.iter().enumerate() {
+            self.output_inversion[OUT_LEN - index - 1] = mask;
+        }
+
+        self.shifted_outputs = None;
+
+        Ok(())
+    }
+
+    /// Sets whether an output is inverted at shift time.
+    pub fn set_inverted_output(&mut self, pin: usize, inverted: bool) -> Result<(), Error> {
+        if pin >= OUT_LEN * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        let index = OUT_LEN - (pin / 8) - 1;
+        let bit = pin % 8;
+
+        if inverted {
+            self.output_inversion[index] |= 1 << bit;
+        } else {
+            self.output_inversion[index] &= !(1 << bit);
+        }
+
+        self.shifted_outputs = None;
+
+        Ok(())
+    }
+
+    /// Sets the inputs inverted when shifted in, e.g. for switches pulling
+    /// low when pressed, so the buffer holds the logical states.
+    ///
+    /// Bit `n` of byte `i` inverts pin `i * 8 + n`, pins beyond `mask` are
+    /// not inverted. Returns an error if `mask` is longer than the chain.
+    /// The buffer is not changed until the next update.
+    pub fn set_input_inversion(&mut self, mask: &[u8]) -> Result<(), Error> {
+        if mask.len() > IN_LEN {
+            return Err(Error::InvalidRange);
+        }
+
+        self.input_inversion = [0; IN_LEN];
+        self.input_inversion[..mask.len()].copy_from_slice(mask);
+
+        Ok(())
+    }
+
+    /// Sets whether an input is inverted when shifted in.
+    ///
+    /// The buffer is not changed until the next update.
+    pub fn set_inverted_input(&mut self, pin: usize, inverted: bool) -> Result<(), Error> {
+        if pin >= IN_LEN * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        if inverted {
+            self.input_inversion[pin / 8] |= 1 << (pin % 8);
+        } else {
+            self.input_inversion[pin / 8] &= !(1 << (pin % 8));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the output buffer as shifted out, with the inversion, the
+    /// bit order and the chip order applied.
+    fn wire_output(&self) -> [u8; OUT_LEN] {
+        let mut data = self.data_out_buffer;
+
+        for (data, inversion) in data.iter_mut().zip(self.output_inversion) {
+            *data ^= inversion;
+        }
+
+        self.bit_order
+            .reorder_registers(&mut data, self.register_bytes);
+        self.chip_order.reorder(&mut data, self.register_bytes);
+
+        data
+    }
+
+    /// Converts input data shifted in into pin order, with the bit order,
+    /// the chip order and the inversion applied.
+    fn decode_input(&self, data: &mut [u8; IN_LEN]) {
+        self.bit_order.reorder_registers(data, self.register_bytes);
+        self.chip_order.reorder(data, self.register_bytes);
+        invert(data, &self.input_inversion);
+    }
+
+    /// Returns the byte of the output buffer and the bit within the byte
+    /// that is shifted at `position`.
+    fn locate_output(&self, position: usize) -> (usize, usize) {
+        let (index, bit) = self.bit_order.locate(position, self.register_bytes);
+
+        (
+            self.chip_order.locate(index, OUT_LEN, self.register_bytes),
+            bit,
+        )
+    }
+
+    /// Sets the input pin connected to the serial output (QH') of the last
+    /// SIPO chip for a loopback integrity check, or disables the check.
+    ///
+    /// At the start of an update, the serial output presents the first bit
+    /// shifted out by the previous update. The updates compare the state of
+    /// the pin with it and return `Error::ChainFault` if they differ, e.g.
+    /// because a chip is broken or mis-seated. The check is skipped for the
+    /// first update after calling this method.
+    pub fn set_loopback_pin(&mut self, pin: Option<usize>) -> Result<(), Error> {
+        if pin.is_some_and(|pin| pin >= IN_LEN * 8) {
+            return Err(Error::PinOutOfRange);
+        }
+
+        self.loopback_pin = pin;
+        self.loopback_expected = None;
+
+        Ok(())
+    }
+
+    /// Advances the age of the input data and the output pulses by one
+    /// tick, clearing the outputs whose pulse ended.
+    ///
+    /// Intended to be called periodically, e.g. from a timer, to detect
+    /// stale data in case the updates stopped.
+    pub fn tick(&mut self) {
+        self.age.tick();
+
+        let buffer = &mut self.data_out_buffer;
+
+        self.pulses.tick(|pin| {
+            buffer[OUT_LEN - (pin / 8) - 1] &= !(1 << (pin % 8));
+        });
+    }
+
+    /// Sets an output high and clears it after `ticks` calls of `tick()`,
+    /// e.g. to fire a solenoid or a buzzer.
+    ///
+    /// A running pulse of the pin is restarted. Setting the pin otherwise
+    /// does not stop the pulse. Returns an error if the pin is out of range.
+    pub fn pulse(&mut self, pin: usize, ticks: u16) -> Result<(), Error> {
+        self.set_output(pin, true)?;
+        self.pulses.start(pin, ticks);
+
+        Ok(())
+    }
+
+    /// Stops the pulse of an output and clears it.
+    ///
+    /// Returns an error if the pin is out of range.
+    pub fn cancel_pulse(&mut self, pin: usize) -> Result<(), Error> {
+        self.set_output(pin, false)?;
+        self.pulses.cancel(pin);
+
+        Ok(())
+    }
+
+    /// Returns `true` if an output is pulsing or `None` if out of range.
+    pub fn is_pulsing(&self, pin: usize) -> Option<bool> {
+        (pin < OUT_LEN * 8).then(|| self.pulses.is_running(pin))
+    }
+
+    /// Returns the ticks since the last successful update, `None` if the
+    /// chain has not been updated yet.
+    pub fn age(&self) -> Option<u32> {
+        self.age.age()
+    }
+
+    /// Sets the maximum age of the input data in ticks, `None` disables
+    /// the check.
+    ///
+    /// With a maximum age set, `get_input()` returns `Error::NotUpdated`
+    /// before the first update and `Error::Stale` if the data is older.
+    pub fn set_max_age(&mut self, max_age: Option<u32>) {
+        self.age.set_max_age(max_age);
+    }
+
+    /// Prepares an update before any data is shifted.
+    fn begin_update(&mut self) {
+        self.first_out_bit = self.first_out_bit();
+        self.outputs_changed = self.shifted_outputs.take() != Some(self.data_out_buffer);
+    }
+
+    /// Finishes an update after all data has been shifted.
+    fn complete_update(&mut self) {
+        self.age.reset();
+
+        self.shifted_outputs = Some(self.data_out_buffer);
+        self.change_set = ChangeSet {
+            previous_inputs: self.change_set.inputs,
+            inputs: self.snapshot(),
+            outputs_changed: self.outputs_changed,
+        };
+
+        for (latched, data) in self.latched_inputs.iter_mut().zip(self.data_in_buffer) {
+            *latched |= data;
+        }
+
+        if let Some(observer) = self.change_observer {
+            if self.change_set.changes() != Snapshot::default() {
+                observer(&self.change_set);
+            }
+        }
+
+        #[cfg(feature = "async")]
+        self.waker.wake();
+    }
+
+    /// Checks the state of the loopback pin after an update.
+    fn check_loopback<E>(&mut self) -> Result<(), Error<E>> {
+        let Some(pin) = self.loopback_pin else {
+            return Ok(());
+        };
+
+        let expected = self.loopback_expected.replace(self.first_out_bit);
+
+        // Compare the level at the pin, regardless of its inversion
+        let inverted = self.input_inversion[pin / 8] & (1 << (pin % 8)) != 0;
+        let state = self.get_input_unchecked(pin) != inverted;
+
+        if expected.is_some_and(|expected| expected != state) {
+            return Err(Error::ChainFault);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the first bit shifted out by an update.
+    fn first_out_bit(&self) -> bool {
+        self.wire_output()
+            .first()
+            .is_some_and(|data| data & 0x80 != 0)
+    }
+}
+
+impl<ClockPin, LatchPin, DataInPin, DataOutPin, const IN_LEN: usize, const OUT_LEN: usize>
+    DualChain<BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin>, IN_LEN, OUT_LEN>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+{
+    /// Creates a new chain by consuming the pins.
+    pub fn new(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_in_pin: DataInPin,
+        data_out_pin: DataOutPin,
+    ) -> Self {
+        Self::with_transport(BitBangTransport::new(
+            clock_pin,
+            latch_pin,
+            data_in_pin,
+            data_out_pin,
+        ))
+    }
+}
+
+impl<
+        ClockPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        Delay,
+        const IN_LEN: usize,
+        const OUT_LEN: usize,
+    > DualChain<BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, Delay>, IN_LEN, OUT_LEN>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new chain by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_in_pin: DataInPin,
+        data_out_pin: DataOutPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self::with_transport(BitBangTransport::with_delay(
+            clock_pin,
+            latch_pin,
+            data_in_pin,
+            data_out_pin,
+            delay,
+            settle_ns,
+        ))
+    }
+}
+
+impl<
+        ClockPin,
+        ClockOutPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        const IN_LEN: usize,
+        const OUT_LEN: usize,
+    >
+    DualChain<
+        BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, NoDelay, ClockOutPin>,
+        IN_LEN,
+        OUT_LEN,
+    >
+where
+    ClockPin: OutputPin,
+    ClockOutPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+{
+    /// Creates a new chain by consuming the pins, with separate clock pins
+    /// for the PISO and the SIPO chips.
+    pub fn with_clock_pins(
+        clock_in_pin: ClockPin,
+        clock_out_pin: ClockOutPin,
+        latch_pin: LatchPin,
+        data_in_pin: DataInPin,
+        data_out_pin: DataOutPin,
+    ) -> Self {
+        Self::with_transport(
+            BitBangTransport::new(clock_in_pin, latch_pin, data_in_pin, data_out_pin)
+                .with_clock_out_pin(clock_out_pin),
+        )
+    }
+}
+
+impl<
+        ClockPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        Delay,
+        ClockOutPin,
+        const IN_LEN: usize,
+        const OUT_LEN: usize,
+    >
+    DualChain<
+        BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, Delay, ClockOutPin>,
+        IN_LEN,
+        OUT_LEN,
+    >
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+    Delay: DelayNs,
+    ClockOutPin: OutputPin,
+{
+    /// Frees the chain and returns the pins, including a separate clock pin
+    /// of the SIPO chips if attached.
+    pub fn free(
+        self,
+    ) -> (
+        ClockPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        Option<ClockOutPin>,
+    ) {
+        self.transport.free()
+    }
+
+    /// Frees the chain and returns the pins and the delay.
+    pub fn free_with_delay(
+        self,
+    ) -> (
+        ClockPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        Option<ClockOutPin>,
+        Delay,
+    ) {
+        self.transport.free_with_delay()
+    }
+}
+
+impl<
+        ClockPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        Delay,
+        ClockOutPin,
+        const IN_LEN: usize,
+        const OUT_LEN: usize,
+        OePin,
+        ClearPin,
+    >
+    DualChain<
+        BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, Delay, ClockOutPin>,
+        IN_LEN,
+        OUT_LEN,
+        OePin,
+        ClearPin,
+    >
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+    Delay: DelayNs,
+    ClockOutPin: OutputPin,
+{
+    /// Attaches a separate clock pin for the SIPO chips, see
+    /// `BitBangTransport::with_clock_out_pin()`.
+    pub fn with_clock_out_pin<NewClockOutPin>(
+        self,
+        clock_out_pin: NewClockOutPin,
+    ) -> DualChain<
+        BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, Delay, NewClockOutPin>,
+        IN_LEN,
+        OUT_LEN,
+        OePin,
+        ClearPin,
+    >
+    where
+        NewClockOutPin: OutputPin,
+    {
+        self.map_parts(|transport, oe_pin, clear_pin| {
+            (
+                transport.with_clock_out_pin(clock_out_pin),
+                oe_pin,
+                clear_pin,
+                (),
+            )
+        })
+        .0
+    }
+
+    /// Detaches the separate clock pin of the SIPO chips and returns it.
+    pub fn take_clock_out_pin(&mut self) -> Option<ClockOutPin> {
+        self.transport.take_clock_out_pin()
+    }
+
+    /// Returns the clock configuration of the transport.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.transport.clock_config()
+    }
+
+    /// Sets the clock configuration of the transport.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.transport.set_clock_config(clock);
+    }
+
+    /// Returns the polarity of the latch signal of the transport.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.transport.latch_polarity()
+    }
+
+    /// Sets the polarity of the latch signal of the transport.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_latch_polarity(polarity);
+    }
+
+    /// Returns the settle time of the transport in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.transport.settle_ns()
+    }
+
+    /// Sets the settle time of the transport in nanoseconds, 0 disables
+    /// the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.transport.set_settle_ns(settle_ns);
+    }
+}
+
+impl<
+        ClockPin,
+        LoadPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        const IN_LEN: usize,
+        const OUT_LEN: usize,
+    >
+    DualChain<
+        BitBangSplitTransport<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin>,
+        IN_LEN,
+        OUT_LEN,
+    >
+where
+    ClockPin: OutputPin,
+    LoadPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+{
+    /// Creates a new chain by consuming the pins.
+    pub fn new(
+        clock_pin: ClockPin,
+        load_pin: LoadPin,
+        latch_pin: LatchPin,
+        data_in_pin: DataInPin,
+        data_out_pin: DataOutPin,
+    ) -> Self {
+        Self::with_transport(BitBangSplitTransport::new(
+            clock_pin,
+            load_pin,
+            latch_pin,
+            data_in_pin,
+            data_out_pin,
+        ))
+    }
+}
+
+impl<
+        ClockPin,
+        LoadPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        Delay,
+        const IN_LEN: usize,
+        const OUT_LEN: usize,
+    >
+    DualChain<
+        BitBangSplitTransport<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin, Delay>,
+        IN_LEN,
+        OUT_LEN,
+    >
+where
+    ClockPin: OutputPin,
+    LoadPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new chain by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        load_pin: LoadPin,
+        latch_pin: LatchPin,
+        data_in_pin: DataInPin,
+        data_out_pin: DataOutPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self::with_transport(BitBangSplitTransport::with_delay(
+            clock_pin,
+            load_pin,
+            latch_pin,
+            data_in_pin,
+            data_out_pin,
+            delay,
+            settle_ns,
+        ))
+    }
+
+    /// Frees the chain and returns the pins.
+    pub fn free(self) -> (ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin) {
+        self.transport.free()
+    }
+
+    /// Frees the chain and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin, Delay) {
+        self.transport.free_with_delay()
+    }
+}
+
+impl<
+        ClockPin,
+        LoadPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        Delay,
+        const IN_LEN: usize,
+        const OUT_LEN: usize,
+        OePin,
+        ClearPin,
+    >
+    DualChain<
+        BitBangSplitTransport<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin, Delay>,
+        IN_LEN,
+        OUT_LEN,
+        OePin,
+        ClearPin,
+    >
+where
+    ClockPin: OutputPin,
+    LoadPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Returns the clock configuration of the transport.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.transport.clock_config()
+    }
+
+    /// Sets the clock configuration of the transport.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.transport.set_clock_config(clock);
+    }
+
+    /// Returns the polarity of the load signal of the transport.
+    pub fn load_polarity(&self) -> LatchPolarity {
+        self.transport.load_polarity()
+    }
+
+    /// Sets the polarity of the load signal of the transport.
+    pub fn set_load_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_load_polarity(polarity);
+    }
+
+    /// Returns the polarity of the latch signal of the transport.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.transport.latch_polarity()
+    }
+
+    /// Sets the polarity of the latch signal of the transport.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_latch_polarity(polarity);
+    }
+
+    /// Returns the settle time of the transport in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.transport.settle_ns()
+    }
+
+    /// Sets the settle time of the transport in nanoseconds, 0 disables
+    /// the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.transport.set_settle_ns(settle_ns);
+    }
+}
+
+impl<Spi, LatchPin, const IN_LEN: usize, const OUT_LEN: usize>
+    DualChain<SpiTransport<Spi, LatchPin>, IN_LEN, OUT_LEN>
+{
+    /// Creates a new chain by consuming the SPI bus and the latch pin.
+    pub fn new(spi: Spi, latch_pin: LatchPin) -> Self {
+        Self::with_transport(SpiTransport::new(spi, latch_pin))
+    }
+
+    /// Frees the chain and returns the SPI bus and the latch pin.
+    pub fn free(self) -> (Spi, LatchPin) {
+        self.transport.free()
+    }
+}
+
+impl<Spi, LatchPin, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<SpiTransport<Spi, LatchPin>, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    /// Returns the polarity of the latch signal of the transport.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.transport.latch_polarity()
+    }
+
+    /// Sets the polarity of the latch signal of the transport.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_latch_polarity(polarity);
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+where
+    Transport: ShiftTransport,
+{
+    /// Updates the chain inputs and outputs simultaneously by shifting
+    /// the data from and to the buffers.
+    ///
+    /// Returns an error if the transport failed, the input buffer is left
+    /// unchanged and the outputs may be in an undefined state in this case.
+    /// Returns `Error::ChainFault` if the loopback check failed.
+    ///
+    /// On success, the changes made by the update are returned.
+    pub fn update(&mut self) -> Result<ChangeSet<IN_LEN>, Error<Transport::Error>> {
+        self.shift_position = 0;
+        self.begin_update();
+        let data_out = self.wire_output();
+        let mut padded = [0; IN_LEN];
+        let mut data_in = [0; IN_LEN];
+        self.transport
+            .transfer(&mut data_in, pad_output(&data_out, &mut padded))
+            .map_err(Error::Transport)?;
+        self.decode_input(&mut data_in);
+        self.data_in_buffer = data_in;
+        self.complete_update();
+        self.check_loopback()?;
+
+        Ok(self.change_set)
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+where
+    Transport: StepTransport,
+{
+    /// Updates the chain inputs and outputs incrementally by shifting up to
+    /// `bits` bits per call.
+    ///
+    /// Returns `WouldBlock` until the whole chain has been shifted. The input
+    /// buffer keeps the previous inputs until the update has completed and is
+    /// left unchanged if the transport fails, in which case an error is
+    /// returned and the next call starts a new update. Changes to the output
+    /// buffer while an update is in progress may only be partially applied.
+    ///
+    /// On success, the changes made by the update are returned.
+    pub fn poll_update(
+        &mut self,
+        bits: usize,
+    ) -> nb::Result<ChangeSet<IN_LEN>, Error<Transport::Error>> {
+        self.step_update(bits).inspect_err(|error| {
+            if let nb::Error::Other(_) = error {
+                self.shift_position = 0;
+            }
+        })
+    }
+
+    /// Shifts up to `bits` bits for `poll_update()`.
+    fn step_update(
+        &mut self,
+        bits: usize,
+    ) -> nb::Result<ChangeSet<IN_LEN>, Error<Transport::Error>> {
+        if self.shift_position == 0 {
+            self.begin_update();
+            self.transport.begin().map_err(Error::Transport)?;
+        }
+
+        // The longer chain is shifted, the output data is preceded by padding
+        // if the PISO chain is longer
+        let total = IN_LEN.max(OUT_LEN) * 8;
+        let padding = total - OUT_LEN * 8;
+        let end = (self.shift_position + bits).min(total);
+
+        for position in self.shift_position..end {
+            let out_state = position.checked_sub(padding).is_some_and(|position| {
+                let (index, bit) = self.locate_output(position);
+                ((self.data_out_buffer[index] ^ self.output_inversion[index]) & (1 << bit)) != 0
+            });
+
+            let in_state = self
+                .transport
+                .shift_bit(out_state)
+                .map_err(Error::Transport)?;
+
+            if position >= IN_LEN * 8 {
+                continue;
+            }
+
+            // Bits are shifted in MSB first, like the bytes of `transfer()`
+            let (index, bit) = (position / 8, 7 - position % 8);
+
+            if in_state {
+                self.shift_buffer[index] |= 1 << bit;
+            } else {
+                self.shift_buffer[index] &= !(1 << bit);
+            }
+        }
+
+        self.shift_position = end;
+
+        if end < total {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.transport.end().map_err(Error::Transport)?;
+        self.shift_position = 0;
+        let mut data_in = self.shift_buffer;
+        self.decode_input(&mut data_in);
+        self.data_in_buffer = data_in;
+        self.complete_update();
+        self.check_loopback()?;
+
+        Ok(self.change_set)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+where
+    Transport: AsyncShiftTransport,
+{
+    /// Updates the chain inputs and outputs simultaneously by shifting
+    /// the data from and to the buffers asynchronously.
+    ///
+    /// Returns an error if the transport failed, the input buffer is left
+    /// unchanged and the outputs may be in an undefined state in this case.
+    /// Returns `Error::ChainFault` if the loopback check failed.
+    ///
+    /// On success, the changes made by the update are returned.
+    pub async fn update_async(&mut self) -> Result<ChangeSet<IN_LEN>, Error<Transport::Error>> {
+        self.shift_position = 0;
+        self.begin_update();
+        let data_out = self.wire_output();
+        let mut padded = [0; IN_LEN];
+        let mut data_in = [0; IN_LEN];
+        self.transport
+            .transfer(&mut data_in, pad_output(&data_out, &mut padded))
+            .await
+            .map_err(Error::Transport)?;
+        self.decode_input(&mut data_in);
+        self.data_in_buffer = data_in;
+        self.complete_update();
+        self.check_loopback()?;
+
+        Ok(self.change_set)
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin> GetInput
+    for DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    /// Returns the input state for a pin.
+    ///
+    /// The state is buffered and not read immediately because the bits
+    /// have to be shifted in by calling `update()` first.
+    fn get_input(&self, pin: usize) -> Result<bool, Error> {
+        if pin >= IN_LEN * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        self.check_inputs()?;
+
+        Ok(self.get_input_unchecked(pin))
+    }
+
+    /// Return the input state for a pin without pin boundary checks.
+    ///
+    /// The state is buffered and not read immediately because the bits
+    /// have to be shifted in by calling `update()` first.
+    fn get_input_unchecked(&self, pin: usize) -> bool {
+        // Calculate index and bit position within buffer array
+        let index = pin / 8;
+        let bit = pin % 8;
+
+        (self.data_in_buffer[index] & (1 << bit)) != 0
+    }
+
+    /// Copies the input buffer into `data`, starting with the first chip.
+    ///
+    /// The states are buffered and not read immediately because the bits
+    /// have to be shifted in by calling `update()` first.
+    fn read_inputs(&self, data: &mut [u8]) -> Result<(), Error> {
+        if data.len() > IN_LEN {
+            return Err(Error::InvalidRange);
+        }
+
+        self.check_inputs()?;
+
+        data.copy_from_slice(&self.data_in_buffer[..data.len()]);
+
+        Ok(())
+    }
+
+    /// Checks whether the buffered input states are not stale.
+    fn check_inputs(&self) -> Result<(), Error> {
+        self.age.check()
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin> SetOutput
+    for DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    /// Sets the output state for a pin.
+    ///
+    /// The output state is buffered and not set immediately because the bits
+    /// have to be shifted out by calling `update()` first.
+    fn set_output(&mut self, pin: usize, state: bool) -> Result<(), Error> {
+        if pin >= OUT_LEN * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        self.set_output_unchecked(pin, state);
+
+        Ok(())
+    }
+
+    /// Sets the output state for a pin without pin boundary checks.
+    ///
+    /// The output state is buffered and not set immediately because the bits
+    /// have to be shifted out by calling `update()` first.
+    fn set_output_unchecked(&mut self, pin: usize, state: bool) {
+        // Calculate index and bit position within buffer array
+        let index = OUT_LEN - (pin / 8) - 1;
+        let bit = pin % 8;
+
+        if state {
+            self.data_out_buffer[index] |= 1 << bit;
+        } else {
+            self.data_out_buffer[index] &= !(1 << bit);
+        }
+    }
+
+    /// Toggles the output state for a pin.
+    ///
+    /// The output state is buffered and not set immediately because the bits
+    /// have to be shifted out by calling `update()` first.
+    fn toggle_output(&mut self, pin: usize) -> Result<(), Error> {
+        if pin >= OUT_LEN * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        self.toggle_output_unchecked(pin);
+
+        Ok(())
+    }
+
+    /// Toggles the output state for a pin without pin boundary checks.
+    ///
+    /// The bit is flipped in place in the buffer and shifted out by the
+    /// next call to `update()`.
+    fn toggle_output_unchecked(&mut self, pin: usize) {
+        // Calculate index and bit position within buffer array
+        let index = OUT_LEN - (pin / 8) - 1;
+        let bit = pin % 8;
+
+        self.data_out_buffer[index] ^= 1 << bit;
+    }
+
+    /// Sets the output states for all pins to high.
+    ///
+    /// The output states are buffered and not set immediately because the
+    /// bits have to be shifted out by calling `update()` first.
+    fn set_all_outputs(&mut self) {
+        self.data_out_buffer.fill(0xFF);
+    }
+
+    /// Sets the output states for all pins to low.
+    ///
+    /// The output states are buffered and not set immediately because the
+    /// bits have to be shifted out by calling `update()` first.
+    fn clear_all_outputs(&mut self) {
+        self.data_out_buffer.fill(0);
+    }
+
+    /// Inverts the output states for all pins.
+    ///
+    /// The output states are buffered and not set immediately because the
+    /// bits have to be shifted out by calling `update()` first.
+    fn invert_all_outputs(&mut self) {
+        for data in self.data_out_buffer.iter_mut() {
+            *data = !*data;
+        }
+    }
+
+    /// Copies `data` into the output buffer, starting with the first chip.
+    ///
+    /// The output states are buffered and not set immediately because the
+    /// bits have to be shifted out by calling `update()` first.
+    fn write_outputs(&mut self, data: &[u8]) -> Result<(), Error> {
+        if data.len() > OUT_LEN {
+            return Err(Error::InvalidRange);
+        }
+
+        // The buffer is in wire order with the first chip at the end
+        for (target, source) in self.data_out_buffer.iter_mut().rev().zip(data) {
+            *target = *source;
+        }
+
+        Ok(())
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin> GetOutput
+    for DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    /// Returns the buffered output state for a pin.
+    ///
+    /// This is the state commanded by the last call to `set_output()` and
+    /// not necessarily the state of the output until `update()` is called.
+    fn get_output(&self, pin: usize) -> Result<bool, Error> {
+        if pin >= OUT_LEN * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        Ok(self.get_output_unchecked(pin))
+    }
+
+    /// Returns the buffered output state for a pin without pin boundary checks.
+    fn get_output_unchecked(&self, pin: usize) -> bool {
+        // Calculate index and bit position within buffer array
+        let index = OUT_LEN - (pin / 8) - 1;
+        let bit = pin % 8;
+
+        (self.data_out_buffer[index] & (1 << bit)) != 0
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin> Update
+    for DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+where
+    Transport: ShiftTransport,
+{
+    type Error = Error<Transport::Error>;
+
+    fn update(&mut self) -> Result<(), Self::Error> {
+        DualChain::update(self).map(|_| ())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin> AsyncUpdate
+    for DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+where
+    Transport: AsyncShiftTransport,
+{
+    type Error = Error<Transport::Error>;
+
+    async fn update_async(&mut self) -> Result<(), Self::Error> {
+        DualChain::update_async(self).await.map(|_| ())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin> WakeOnUpdate
+    for DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    fn register_waker(&mut self, waker: &Waker) {
+        self.waker.register(waker);
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin> Length
+    for DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    /// Returns the length of the longer chain, which is the number of bytes
+    /// shifted by an update.
+    fn len(&self) -> usize {
+        IN_LEN.max(OUT_LEN)
+    }
+
+    /// Returns the length of the input chain.
+    fn input_len(&self) -> usize {
+        IN_LEN
+    }
+
+    /// Returns the length of the output chain.
+    fn output_len(&self) -> usize {
+        OUT_LEN
+    }
+}
+
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin> EncodeOutput
+    for DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    fn encode_output<W: Word>(&self, words: &mut [W]) -> Result<usize, Error> {
+        let data = self.wire_output();
+        encode(&data, words)
+    }
+}
+
+#[cfg(feature = "embedded-dma")]
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    /// Encodes the output buffer into the DMA buffer as shifted out, with
+    /// the inversion, the bit order and the chip order applied.
+    ///
+    /// The returned transfer borrows the chain until `finish()` is called
+    /// after the DMA transfer.
+    pub fn prepare_dma(&mut self) -> DmaTransfer<'_, Transport, IN_LEN, OUT_LEN, OePin, ClearPin> {
+        self.shift_position = 0;
+        self.begin_update();
+
+        let data = self.wire_output();
+        let (output, _) = self.dma_buffers.split_mut();
+        let padding = output.len() - OUT_LEN;
+        output[..padding].fill(0);
+        output[padding..].copy_from_slice(&data);
+
+        DmaTransfer { chain: self }
+    }
+}
+
+/// DMA transfer of a dual chain, returned by `prepare_dma()`.
+#[cfg(feature = "embedded-dma")]
+pub struct DmaTransfer<
+    'a,
+    Transport,
+    const IN_LEN: usize,
+    const OUT_LEN: usize = IN_LEN,
+    OePin = NoPin,
+    ClearPin = NoPin,
+> {
+    /// Chain the DMA buffers belong to.
+    chain: &'a mut DualChain<Transport, IN_LEN, OUT_LEN, OePin, ClearPin>,
+}
+
+#[cfg(feature = "embedded-dma")]
+impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    DmaTransfer<'_, Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    /// Completes the update after the DMA transfer, decoding the input DMA
+    /// buffer with the bit order, the chip order and the inversion applied.
+    ///
+    /// Returns `Error::ChainFault` if the loopback check failed. On success,
+    /// the changes made by the update are returned.
+    pub fn finish(self) -> Result<ChangeSet<IN_LEN>, Error> {
+        let chain = self.chain;
+        let (_, input) = chain.dma_buffers.split();
+        let mut data = [0; IN_LEN];
+        data.copy_from_slice(&input[..IN_LEN]);
+        chain.decode_input(&mut data);
+        chain.data_in_buffer = data;
+        chain.complete_update();
+        chain.check_loopback()?;
+
+        Ok(chain.change_set)
+    }
+}
+
+#[cfg(feature = "embedded-dma")]
+// SAFETY: The returned pointer and length refer to the output DMA buffer of
+// the chain, which is mutably borrowed for the lifetime of the transfer.
+unsafe impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    embedded_dma::ReadTarget for DmaTransfer<'_, Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    type Word = u8;
+
+    /// Returns the output DMA buffer encoded by `prepare_dma()`.
+    ///
+    /// The bytes are in the order they have to be shifted out, MSB first,
+    /// preceded by zeros if the PISO chain is longer, so both DMA buffers
+    /// have the length of the longer chain. Toggling the latch signal after
+    /// the transfer is up to the caller.
+    fn as_read_buffer(&self) -> (*const u8, usize) {
+        let (output, _) = self.chain.dma_buffers.split();
+        (output.as_ptr(), output.len())
+    }
+}
+
+#[cfg(feature = "embedded-dma")]
+// SAFETY: The returned pointer and length refer to the input DMA buffer of
+// the chain, which is mutably borrowed for the lifetime of the transfer and
+// valid for every byte pattern.
+unsafe impl<Transport, const IN_LEN: usize, const OUT_LEN: usize, OePin, ClearPin>
+    embedded_dma::WriteTarget for DmaTransfer<'_, Transport, IN_LEN, OUT_LEN, OePin, ClearPin>
+{
+    type Word = u8;
+
+    /// Returns the input DMA buffer decoded by `finish()`.
+    ///
+    /// The bytes are expected in the order they are shifted in, MSB first,
+    /// followed by spare bytes if the SIPO chain is longer, so both DMA
+    /// buffers have the length of the longer chain. Toggling the latch
+    /// signal before the transfer is up to the caller.
+    fn as_write_buffer(&mut self) -> (*mut u8, usize) {
+        let (_, input) = self.chain.dma_buffers.split_mut();
+        (input.as_mut_ptr(), input.len())
+    }
+}
+
+/// Output and input DMA buffers of a dual chain, each of the longer chain
+/// length.
+///
+/// Both buffers are halves of the array sized by the longer chain, the
+/// array sized by the shorter chain is unused.
+#[cfg(feature = "embedded-dma")]
+#[derive(Clone, Copy)]
+struct DmaBuffers<const IN_LEN: usize, const OUT_LEN: usize> {
+    /// Buffers used if the PISO chain is not shorter.
+    in_sized: [[u8; IN_LEN]; 2],
+
+    /// Buffers used if the SIPO chain is longer.
+    out_sized: [[u8; OUT_LEN]; 2],
+}
+
+#[cfg(feature = "embedded-dma")]
+impl<const IN_LEN: usize, const OUT_LEN: usize> DmaBuffers<IN_LEN, OUT_LEN> {
+    /// Creates new zeroed buffers.
+    const fn new() -> Self {
+        Self {
+            in_sized: [[0; IN_LEN]; 2],
+            out_sized: [[0; OUT_LEN]; 2],
+        }
+    }
+
+    /// Returns the output and the input buffer.
+    fn split(&self) -> (&[u8], &[u8]) {
+        let data = if IN_LEN >= OUT_LEN {
+            self.in_sized.as_flattened()
+        } else {
+            self.out_sized.as_flattened()
+        };
+
+        data.split_at(data.len() / 2)
+    }
+
+    /// Returns the mutable output and input buffer.
+    fn split_mut(&mut self) -> (&mut [u8], &mut [u8]) {
+        let data = if IN_LEN >= OUT_LEN {
+            self.in_sized.as_flattened_mut()
+        } else {
+            self.out_sized.as_flattened_mut()
+        };
+
+        data.split_at_mut(data.len() / 2)
+    }
+}
+
+/// Returns the output data to be shifted, preceded by zeros in `buffer` if
+/// the PISO chain is longer, so the data ends up in the SIPO chips.
+fn pad_output<'a>(data: &'a [u8], buffer: &'a mut [u8]) -> &'a [u8] {
+    match buffer.len().checked_sub(data.len()) {
+        Some(padding) if padding > 0 => {
+            buffer[padding..].copy_from_slice(data);
+            buffer
+        }
+        _ => data,
+    }
+}
 
 ////////////////////////////////////////////////////////////////////////////////
 
-/// Dual chain of SIPO/PISO shift registers.
-pub struct DualChain<ClockPin, LatchPin, DataInPin, DataOutPin, const CHAIN_LENGTH: usize> {
+/// Transport shifting the data in and out by bit-banging pins.
+///
+/// An optional `Delay` inserts a settle time around each signal change,
+/// e.g. for long cables, optocouplers or level shifters. The SIPO chips can
+/// be clocked by a separate pin, whose type defaults to the type of the
+/// clock pin.
+pub struct BitBangTransport<
+    ClockPin,
+    LatchPin,
+    DataInPin,
+    DataOutPin,
+    Delay = NoDelay,
+    ClockOutPin = ClockPin,
+> {
+    /// Pin for the clock output signal.
+    clock_pin: ClockPin,
+
+    /// Pin for a separate clock output signal of the SIPO chips.
+    clock_out_pin: Option<ClockOutPin>,
+
+    /// Pin for the latch output signal.
+    latch_pin: LatchPin,
+
+    /// Pin for the data input signal.
+    data_in_pin: DataInPin,
+
+    /// Pin for the data output signal.
+    data_out_pin: DataOutPin,
+
+    /// Clock configuration.
+    clock: ClockConfig,
+
+    /// Polarity of the latch signal.
+    latch_polarity: LatchPolarity,
+
+    /// Settle time of the signals.
+    settle: Settle<Delay>,
+}
+
+impl<ClockPin, LatchPin, DataInPin, DataOutPin>
+    BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+{
+    /// Creates a new transport by consuming the pins.
+    pub fn new(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_in_pin: DataInPin,
+        data_out_pin: DataOutPin,
+    ) -> Self {
+        Self::with_delay(clock_pin, latch_pin, data_in_pin, data_out_pin, NoDelay, 0)
+    }
+}
+
+impl<ClockPin, LatchPin, DataInPin, DataOutPin, Delay>
+    BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, Delay>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new transport by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_in_pin: DataInPin,
+        data_out_pin: DataOutPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self {
+            clock_pin,
+            clock_out_pin: None,
+            latch_pin,
+            data_in_pin,
+            data_out_pin,
+            clock: ClockConfig::default(),
+            latch_polarity: LatchPolarity::Normal,
+            settle: Settle::new(delay, settle_ns),
+        }
+    }
+}
+
+impl<ClockPin, LatchPin, DataInPin, DataOutPin, Delay, ClockOutPin>
+    BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, Delay, ClockOutPin>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+    Delay: DelayNs,
+    ClockOutPin: OutputPin,
+{
+    /// Attaches a separate clock pin for the SIPO chips, replacing any
+    /// attached before.
+    ///
+    /// Both clock pins are driven in parallel. The pin must share the error
+    /// type of the other pins.
+    pub fn with_clock_out_pin<NewClockOutPin>(
+        self,
+        clock_out_pin: NewClockOutPin,
+    ) -> BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, Delay, NewClockOutPin>
+    where
+        NewClockOutPin: OutputPin,
+    {
+        BitBangTransport {
+            clock_pin: self.clock_pin,
+            clock_out_pin: Some(clock_out_pin),
+            latch_pin: self.latch_pin,
+            data_in_pin: self.data_in_pin,
+            data_out_pin: self.data_out_pin,
+            clock: self.clock,
+            latch_polarity: self.latch_polarity,
+            settle: self.settle,
+        }
+    }
+
+    /// Detaches the separate clock pin of the SIPO chips and returns it.
+    pub fn take_clock_out_pin(&mut self) -> Option<ClockOutPin> {
+        self.clock_out_pin.take()
+    }
+
+    /// Frees the transport and returns the pins, including a separate clock pin
+    /// of the SIPO chips if attached.
+    pub fn free(
+        self,
+    ) -> (
+        ClockPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        Option<ClockOutPin>,
+    ) {
+        (
+            self.clock_pin,
+            self.latch_pin,
+            self.data_in_pin,
+            self.data_out_pin,
+            self.clock_out_pin,
+        )
+    }
+
+    /// Frees the transport and returns the pins and the delay.
+    pub fn free_with_delay(
+        self,
+    ) -> (
+        ClockPin,
+        LatchPin,
+        DataInPin,
+        DataOutPin,
+        Option<ClockOutPin>,
+        Delay,
+    ) {
+        (
+            self.clock_pin,
+            self.latch_pin,
+            self.data_in_pin,
+            self.data_out_pin,
+            self.clock_out_pin,
+            self.settle.into_delay(),
+        )
+    }
+
+    /// Returns the clock configuration.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.clock
+    }
+
+    /// Sets the clock configuration.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.clock = clock;
+    }
+
+    /// Returns the polarity of the latch signal.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.latch_polarity
+    }
+
+    /// Sets the polarity of the latch signal.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.latch_polarity = polarity;
+    }
+
+    /// Returns the settle time in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.settle.ns()
+    }
+
+    /// Sets the settle time in nanoseconds, 0 disables the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.settle.set_ns(settle_ns);
+    }
+}
+
+impl<ClockPin, LatchPin, DataInPin, DataOutPin, Delay, ClockOutPin> ShiftTransport
+    for BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, Delay, ClockOutPin>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin<Error = ClockPin::Error>,
+    DataInPin: InputPin<Error = ClockPin::Error>,
+    DataOutPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+    ClockOutPin: OutputPin<Error = ClockPin::Error>,
+{
+    type Error = ClockPin::Error;
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.begin()?;
+
+        for chain_index in 0..read.len().max(write.len()) {
+            let mut in_value: u8 = 0;
+            let out_value = write.get(chain_index).copied().unwrap_or(0);
+
+            for bit in 0..=7 {
+                let out_state = (out_value & (1 << (7 - bit))) != 0;
+
+                if self.shift_bit(out_state)? {
+                    in_value |= 1 << (7 - bit);
+                } else {
+                    in_value &= !(1 << (7 - bit));
+                }
+            }
+
+            if let Some(data) = read.get_mut(chain_index) {
+                *data = in_value;
+            }
+        }
+
+        self.end()
+    }
+}
+
+impl<ClockPin, LatchPin, DataInPin, DataOutPin, Delay, ClockOutPin> StepTransport
+    for BitBangTransport<ClockPin, LatchPin, DataInPin, DataOutPin, Delay, ClockOutPin>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin<Error = ClockPin::Error>,
+    DataInPin: InputPin<Error = ClockPin::Error>,
+    DataOutPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+    ClockOutPin: OutputPin<Error = ClockPin::Error>,
+{
+    type Error = ClockPin::Error;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.clock.idle(&mut self.clock_pin)?;
+
+        if let Some(clock_out_pin) = &mut self.clock_out_pin {
+            self.clock.idle(clock_out_pin)?;
+        }
+
+        self.latch_polarity.set(&mut self.latch_pin, true)?;
+        self.settle.wait();
+
+        Ok(())
+    }
+
+    fn shift_bit(&mut self, state: bool) -> Result<bool, Self::Error> {
+        self.clock.before_edge(&mut self.clock_pin)?;
+
+        if let Some(clock_out_pin) = &mut self.clock_out_pin {
+            self.clock.before_edge(clock_out_pin)?;
+        }
+
+        // Get input
+        let in_state = self.data_in_pin.is_high()?;
+
+        // Set output
+        if state {
+            self.data_out_pin.set_high()?;
+        } else {
+            self.data_out_pin.set_low()?;
+        }
+
+        self.settle.wait();
+        self.clock.edge(&mut self.clock_pin)?;
+
+        if let Some(clock_out_pin) = &mut self.clock_out_pin {
+            self.clock.edge(clock_out_pin)?;
+        }
+
+        self.settle.wait();
+        self.clock.after_edge(&mut self.clock_pin)?;
+
+        if let Some(clock_out_pin) = &mut self.clock_out_pin {
+            self.clock.after_edge(clock_out_pin)?;
+        }
+
+        Ok(in_state)
+    }
+
+    fn end(&mut self) -> Result<(), Self::Error> {
+        self.latch_polarity.set(&mut self.latch_pin, false)?;
+        self.settle.wait();
+
+        // Additional latch cycle for output shift register to update
+        // Otherwise, outputs would stay at previous states until next update() call
+        self.latch_polarity.set(&mut self.latch_pin, true)?;
+        self.settle.wait();
+        self.latch_polarity.set(&mut self.latch_pin, false)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Transport shifting the data in and out by bit-banging pins, with separate
+/// pins for the load signal of the PISO chips (/PL of the 74HC165) and the
+/// latch signal of the SIPO chips (ST_CP of the 74HC595).
+///
+/// The inputs are loaded by a pulse before shifting and the outputs are
+/// latched by a single edge afterwards, without the additional latch cycle
+/// needed with a shared pin.
+pub struct BitBangSplitTransport<
+    ClockPin,
+    LoadPin,
+    LatchPin,
+    DataInPin,
+    DataOutPin,
+    Delay = NoDelay,
+> {
     /// Pin for the clock output signal.
     clock_pin: ClockPin,
 
+    /// Pin for the load output signal.
+    load_pin: LoadPin,
+
     /// Pin for the latch output signal.
     latch_pin: LatchPin,
 
@@ -20,158 +2188,679 @@ pub struct DualChain<ClockPin, LatchPin, DataInPin, DataOutPin, const CHAIN_LENG
     /// Pin for the data output signal.
     data_out_pin: DataOutPin,
 
-    /// Buffer storing the data read from pins.
-    data_in_buffer: [u8; CHAIN_LENGTH],
+    /// Clock configuration.
+    clock: ClockConfig,
 
-    /// Buffer storing the data to output.
-    data_out_buffer: [u8; CHAIN_LENGTH],
+    /// Polarity of the load signal.
+    load_polarity: LatchPolarity,
+
+    /// Polarity of the latch signal.
+    latch_polarity: LatchPolarity,
+
+    /// Settle time of the signals.
+    settle: Settle<Delay>,
 }
 
-impl<ClockPin, LatchPin, DataInPin, DataOutPin, const CHAIN_LENGTH: usize>
-    DualChain<ClockPin, LatchPin, DataInPin, DataOutPin, CHAIN_LENGTH>
+impl<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin>
+    BitBangSplitTransport<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin>
 where
     ClockPin: OutputPin,
+    LoadPin: OutputPin,
     LatchPin: OutputPin,
     DataInPin: InputPin,
     DataOutPin: OutputPin,
 {
-    /// Creates a new chain by consuming the pins.
+    /// Creates a new transport by consuming the pins.
     pub fn new(
         clock_pin: ClockPin,
+        load_pin: LoadPin,
+        latch_pin: LatchPin,
+        data_in_pin: DataInPin,
+        data_out_pin: DataOutPin,
+    ) -> Self {
+        Self::with_delay(
+            clock_pin,
+            load_pin,
+            latch_pin,
+            data_in_pin,
+            data_out_pin,
+            NoDelay,
+            0,
+        )
+    }
+}
+
+impl<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin, Delay>
+    BitBangSplitTransport<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin, Delay>
+where
+    ClockPin: OutputPin,
+    LoadPin: OutputPin,
+    LatchPin: OutputPin,
+    DataInPin: InputPin,
+    DataOutPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new transport by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        load_pin: LoadPin,
         latch_pin: LatchPin,
         data_in_pin: DataInPin,
         data_out_pin: DataOutPin,
+        delay: Delay,
+        settle_ns: u32,
     ) -> Self {
         Self {
             clock_pin,
+            load_pin,
             latch_pin,
             data_in_pin,
             data_out_pin,
-            data_in_buffer: [0; CHAIN_LENGTH],
-            data_out_buffer: [0; CHAIN_LENGTH],
+            clock: ClockConfig::default(),
+            load_polarity: LatchPolarity::Normal,
+            latch_polarity: LatchPolarity::Normal,
+            settle: Settle::new(delay, settle_ns),
         }
     }
 
-    /// Frees the chain and returns the pins.
-    pub fn free(self) -> (ClockPin, LatchPin, DataInPin, DataOutPin) {
+    /// Frees the transport and returns the pins.
+    pub fn free(self) -> (ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin) {
         (
             self.clock_pin,
+            self.load_pin,
             self.latch_pin,
             self.data_in_pin,
             self.data_out_pin,
         )
     }
 
-    /// Updates the chain inputs and outputs simultaneously by shifting
-    /// the data from and to the buffers.
-    pub fn update(&mut self) {
-        self.latch_pin.set_high().ok();
+    /// Frees the transport and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin, Delay) {
+        (
+            self.clock_pin,
+            self.load_pin,
+            self.latch_pin,
+            self.data_in_pin,
+            self.data_out_pin,
+            self.settle.into_delay(),
+        )
+    }
+
+    /// Returns the clock configuration.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.clock
+    }
+
+    /// Sets the clock configuration.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.clock = clock;
+    }
+
+    /// Returns the polarity of the load signal.
+    pub fn load_polarity(&self) -> LatchPolarity {
+        self.load_polarity
+    }
+
+    /// Sets the polarity of the load signal.
+    pub fn set_load_polarity(&mut self, polarity: LatchPolarity) {
+        self.load_polarity = polarity;
+    }
+
+    /// Returns the polarity of the latch signal.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.latch_polarity
+    }
+
+    /// Sets the polarity of the latch signal.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.latch_polarity = polarity;
+    }
+
+    /// Returns the settle time in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.settle.ns()
+    }
 
-        for chain_index in 0..CHAIN_LENGTH {
+    /// Sets the settle time in nanoseconds, 0 disables the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.settle.set_ns(settle_ns);
+    }
+}
+
+impl<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin, Delay> ShiftTransport
+    for BitBangSplitTransport<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin, Delay>
+where
+    ClockPin: OutputPin,
+    LoadPin: OutputPin<Error = ClockPin::Error>,
+    LatchPin: OutputPin<Error = ClockPin::Error>,
+    DataInPin: InputPin<Error = ClockPin::Error>,
+    DataOutPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+{
+    type Error = ClockPin::Error;
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.begin()?;
+
+        for chain_index in 0..read.len().max(write.len()) {
             let mut in_value: u8 = 0;
-            let out_value = self.data_out_buffer[chain_index];
+            let out_value = write.get(chain_index).copied().unwrap_or(0);
 
             for bit in 0..=7 {
-                self.clock_pin.set_low().ok();
-
-                // Get input
-                if self.data_in_pin.is_high().ok().unwrap() {
+                if self.shift_bit((out_value & (1 << (7 - bit))) != 0)? {
                     in_value |= 1 << (7 - bit);
-                } else {
-                    in_value &= !(1 << (7 - bit));
                 }
+            }
 
-                // Set output
-                let out_state = (out_value & (1 << (7 - bit))) != 0;
+            if let Some(data) = read.get_mut(chain_index) {
+                *data = in_value;
+            }
+        }
 
-                if out_state {
-                    self.data_out_pin.set_high().ok();
-                } else {
-                    self.data_out_pin.set_low().ok();
-                }
+        self.end()
+    }
+}
 
-                self.clock_pin.set_high().ok();
-            }
+impl<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin, Delay> StepTransport
+    for BitBangSplitTransport<ClockPin, LoadPin, LatchPin, DataInPin, DataOutPin, Delay>
+where
+    ClockPin: OutputPin,
+    LoadPin: OutputPin<Error = ClockPin::Error>,
+    LatchPin: OutputPin<Error = ClockPin::Error>,
+    DataInPin: InputPin<Error = ClockPin::Error>,
+    DataOutPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+{
+    type Error = ClockPin::Error;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.clock.idle(&mut self.clock_pin)?;
+        self.latch_polarity.set(&mut self.latch_pin, false)?;
 
-            self.data_in_buffer[chain_index] = in_value;
+        // Load the inputs, then switch to shifting
+        self.load_polarity.set(&mut self.load_pin, false)?;
+        self.settle.wait();
+        self.load_polarity.set(&mut self.load_pin, true)?;
+        self.settle.wait();
+
+        Ok(())
+    }
+
+    fn shift_bit(&mut self, state: bool) -> Result<bool, Self::Error> {
+        self.clock.before_edge(&mut self.clock_pin)?;
+
+        let in_state = self.data_in_pin.is_high()?;
+
+        if state {
+            self.data_out_pin.set_high()?;
+        } else {
+            self.data_out_pin.set_low()?;
         }
 
-        self.latch_pin.set_low().ok();
+        self.settle.wait();
+        self.clock.edge(&mut self.clock_pin)?;
+        self.settle.wait();
+        self.clock.after_edge(&mut self.clock_pin)?;
 
-        // Additional latch cycle for output shift register to update
-        // Otherwise, outputs would stay at previous states until next update() call
-        self.latch_pin.set_high().ok();
-        self.latch_pin.set_low().ok();
+        Ok(in_state)
+    }
+
+    fn end(&mut self) -> Result<(), Self::Error> {
+        self.latch_polarity.set(&mut self.latch_pin, true)?;
+        self.settle.wait();
+        self.load_polarity.set(&mut self.load_pin, false)
     }
 }
 
-impl<ClockPin, LatchPin, DataInPin, DataOutPin, const CHAIN_LENGTH: usize> GetInput
-    for DualChain<ClockPin, LatchPin, DataInPin, DataOutPin, CHAIN_LENGTH>
-{
-    /// Returns the input state for a pin.
-    ///
-    /// The state is buffered and not read immediately because the bits
-    /// have to be shifted in by calling `update()` first.
-    fn get_input(&self, pin: usize) -> Result<bool, Error> {
-        if pin >= CHAIN_LENGTH * 8 {
-            return Err(Error::PinOutOfRange);
+////////////////////////////////////////////////////////////////////////////////
+
+/// Transport shifting the data in and out by a hardware SPI bus.
+///
+/// The bus must be configured for SPI mode 0 with MSB first. SCK is the common
+/// clock, MOSI feeds the SIPO chain and MISO reads the PISO chain. The common
+/// latch signal is driven by a separate pin.
+pub struct SpiTransport<Spi, LatchPin> {
+    /// SPI bus for the clock and data signals.
+    spi: Spi,
+
+    /// Pin for the latch output signal.
+    latch_pin: LatchPin,
+
+    /// Polarity of the latch signal.
+    latch_polarity: LatchPolarity,
+}
+
+impl<Spi, LatchPin> SpiTransport<Spi, LatchPin> {
+    /// Creates a new transport by consuming the SPI bus and the latch pin.
+    pub fn new(spi: Spi, latch_pin: LatchPin) -> Self {
+        Self {
+            spi,
+            latch_pin,
+            latch_polarity: LatchPolarity::Normal,
         }
+    }
 
-        Ok(self.get_input_unchecked(pin))
+    /// Frees the transport and returns the SPI bus and the latch pin.
+    pub fn free(self) -> (Spi, LatchPin) {
+        (self.spi, self.latch_pin)
     }
 
-    /// Return the input state for a pin without pin boundary checks.
+    /// Returns the polarity of the latch signal.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.latch_polarity
+    }
+
+    /// Sets the polarity of the latch signal.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.latch_polarity = polarity;
+    }
+}
+
+impl<Spi, LatchPin> ShiftTransport for SpiTransport<Spi, LatchPin>
+where
+    Spi: SpiBus,
+    LatchPin: OutputPin,
+{
+    type Error = SpiError<Spi::Error, LatchPin::Error>;
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.latch_polarity
+            .set(&mut self.latch_pin, true)
+            .map_err(SpiError::Pin)?;
+
+        self.spi.transfer(read, write).map_err(SpiError::Spi)?;
+        self.spi.flush().map_err(SpiError::Spi)?;
+
+        self.latch_polarity
+            .set(&mut self.latch_pin, false)
+            .map_err(SpiError::Pin)?;
+
+        // Additional latch cycle for output shift register to update
+        self.latch_polarity
+            .set(&mut self.latch_pin, true)
+            .map_err(SpiError::Pin)?;
+        self.latch_polarity
+            .set(&mut self.latch_pin, false)
+            .map_err(SpiError::Pin)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Spi, LatchPin> AsyncShiftTransport for SpiTransport<Spi, LatchPin>
+where
+    Spi: embedded_hal_async::spi::SpiBus,
+    LatchPin: OutputPin,
+{
+    type Error = SpiError<Spi::Error, LatchPin::Error>;
+
+    /// Shifts the data in a single asynchronous full-duplex transfer.
     ///
-    /// The state is buffered and not read immediately because the bits
-    /// have to be shifted in by calling `update()` first.
-    fn get_input_unchecked(&self, pin: usize) -> bool {
-        // Calculate index and bit position within buffer array
-        let index = pin / 8;
-        let bit = pin % 8;
+    /// The latch signal is toggled after the transfer has completed.
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.latch_polarity
+            .set(&mut self.latch_pin, true)
+            .map_err(SpiError::Pin)?;
 
-        (self.data_in_buffer[index] & (1 << bit)) != 0
+        self.spi
+            .transfer(read, write)
+            .await
+            .map_err(SpiError::Spi)?;
+        self.spi.flush().await.map_err(SpiError::Spi)?;
+
+        self.latch_polarity
+            .set(&mut self.latch_pin, false)
+            .map_err(SpiError::Pin)?;
+
+        // Additional latch cycle for output shift register to update
+        self.latch_polarity
+            .set(&mut self.latch_pin, true)
+            .map_err(SpiError::Pin)?;
+        self.latch_polarity
+            .set(&mut self.latch_pin, false)
+            .map_err(SpiError::Pin)
     }
 }
 
-impl<ClockPin, LatchPin, DataInPin, DataOutPin, const CHAIN_LENGTH: usize> SetOutput
-    for DualChain<ClockPin, LatchPin, DataInPin, DataOutPin, CHAIN_LENGTH>
+////////////////////////////////////////////////////////////////////////////////
+
+/// Combined input and output pin of the chips in the chain.
+///
+/// The pin reads the input and drives the output with the same pin number,
+/// e.g. to sense the state of a load driven by the output.
+#[cfg_attr(not(any(feature = "eh1", feature = "eh02")), allow(dead_code))]
+pub struct Pin<'a, Chain> {
+    /// Reference to the chain.
+    chain: &'a RefCell<Chain>,
+
+    /// Pin number of the input and output in the chain.
+    pin: usize,
+}
+
+impl<'a, Chain> Pin<'a, Chain>
+where
+    Chain: GetInput + SetOutput + Length,
 {
-    /// Sets the output state for a pin.
+    /// Creates a new combined pin.
     ///
-    /// The output state is buffered and not set immediately because the bits
-    /// have to be shifted out by calling `update()` first.
-    fn set_output(&mut self, pin: usize, state: bool) -> Result<(), Error> {
-        if pin >= CHAIN_LENGTH * 8 {
+    /// Returns an error unless the pin exists as both input and output.
+    pub fn new(chain: &'a RefCell<Chain>, pin: usize) -> Result<Self, Error> {
+        let pins = {
+            let chain = chain.borrow();
+            chain.input_len().min(chain.output_len()) * 8
+        };
+
+        if pin >= pins {
             return Err(Error::PinOutOfRange);
         }
 
-        self.set_output_unchecked(pin, state);
+        Ok(Self { chain, pin })
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<Chain> embedded_hal::digital::ErrorType for Pin<'_, Chain> {
+    type Error = Infallible;
+}
+
+#[cfg(feature = "eh1")]
+impl<Chain> InputPin for Pin<'_, Chain>
+where
+    Chain: GetInput,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.chain.borrow().get_input_unchecked(self.pin))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.chain.borrow().get_input_unchecked(self.pin))
+    }
+}
 
+#[cfg(feature = "eh1")]
+impl<Chain> OutputPin for Pin<'_, Chain>
+where
+    Chain: SetOutput,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.chain
+            .borrow_mut()
+            .set_output_unchecked(self.pin, false);
         Ok(())
     }
 
-    /// Sets the output state for a pin without pin boundary checks.
-    ///
-    /// The output state is buffered and not set immediately because the bits
-    /// have to be shifted out by calling `update()` first.
-    fn set_output_unchecked(&mut self, pin: usize, state: bool) {
-        // Calculate index and bit position within buffer array
-        let index = CHAIN_LENGTH - (pin / 8) - 1;
-        let bit = pin % 8;
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.chain.borrow_mut().set_output_unchecked(self.pin, true);
+        Ok(())
+    }
+}
 
-        if state {
-            self.data_out_buffer[index] |= 1 << bit;
-        } else {
-            self.data_out_buffer[index] &= !(1 << bit);
-        }
+#[cfg(feature = "eh1")]
+impl<Chain> embedded_hal::digital::StatefulOutputPin for Pin<'_, Chain>
+where
+    Chain: GetOutput + SetOutput,
+{
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.chain.borrow().get_output_unchecked(self.pin))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.chain.borrow().get_output_unchecked(self.pin))
+    }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.chain.borrow_mut().toggle_output_unchecked(self.pin);
+        Ok(())
     }
 }
 
-impl<ClockPin, LatchPin, DataInPin, DataOutPin, const CHAIN_LENGTH: usize> Length
-    for DualChain<ClockPin, LatchPin, DataInPin, DataOutPin, CHAIN_LENGTH>
+#[cfg(feature = "eh02")]
+impl<Chain> embedded_hal_02::digital::v2::InputPin for Pin<'_, Chain>
+where
+    Chain: GetInput,
 {
-    /// Returns the chain length.
-    fn len(&self) -> usize {
-        CHAIN_LENGTH
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.chain.borrow().get_input_unchecked(self.pin))
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.chain.borrow().get_input_unchecked(self.pin))
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<Chain> embedded_hal_02::digital::v2::OutputPin for Pin<'_, Chain>
+where
+    Chain: SetOutput,
+{
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.chain
+            .borrow_mut()
+            .set_output_unchecked(self.pin, false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.chain.borrow_mut().set_output_unchecked(self.pin, true);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<Chain> embedded_hal_02::digital::v2::StatefulOutputPin for Pin<'_, Chain>
+where
+    Chain: GetOutput + SetOutput,
+{
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.chain.borrow().get_output_unchecked(self.pin))
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.chain.borrow().get_output_unchecked(self.pin))
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<Chain> embedded_hal_02::digital::v2::ToggleableOutputPin for Pin<'_, Chain>
+where
+    Chain: GetOutput + SetOutput,
+{
+    type Error = Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.chain.borrow_mut().toggle_output_unchecked(self.pin);
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input,
+        mock::{Fault, Recorder},
+        output,
+    };
+
+    #[test]
+    fn longer_input_chain() {
+        let mut chain = DualChain::<_, 2, 1>::with_transport(Recorder::default());
+        chain.transport.read[..2].copy_from_slice(&[0x12, 0x34]);
+
+        assert_eq!((chain.input_len(), chain.output_len()), (2, 1));
+        assert_eq!(chain.set_output_word(0, 0xA5u8), Ok(()));
+        assert_eq!(chain.set_output(8, true), Err(Error::PinOutOfRange));
+        chain.fill_pattern(0xFF);
+
+        assert!(chain.update().is_ok());
+        assert_eq!(chain.transport.written(), [0x00, 0xFF]);
+        assert_eq!(chain.get_input_word::<u16>(0), Ok(0x3412));
+        assert_eq!(chain.read_group::<u8>(12, 4), Ok(0x03));
+        assert_eq!(chain.inputs().count(), 16);
+        assert_eq!(chain.outputs().count(), 8);
+
+        let chain = RefCell::new(chain);
+        assert!(input::Pin::new(&chain, 15).is_ok());
+        assert!(output::Pin::new(&chain, 8).is_err());
+        assert!(Pin::new(&chain, 8).is_err());
+    }
+
+    #[test]
+    fn longer_output_chain() {
+        let mut chain = DualChain::<_, 1, 2>::with_transport(Recorder::default());
+        chain.transport.read[0] = 0x5A;
+
+        assert_eq!((chain.input_len(), chain.output_len()), (1, 2));
+        assert_eq!(chain.set_output_word(0, 0x1234u16), Ok(()));
+        assert_eq!(chain.write_bcd_digits(8, 2, 42, false), Ok(()));
+
+        assert!(chain.update().is_ok());
+        assert_eq!(chain.transport.written(), [0x42, 0x34]);
+        assert_eq!(chain.get_input_word::<u8>(0), Ok(0x5A));
+        assert_eq!(chain.get_input(8), Err(Error::PinOutOfRange));
+        assert_eq!(chain.read_bcd(8), Err(Error::PinOutOfRange));
+    }
+
+    #[test]
+    fn failed_update_keeps_inputs() {
+        let mut chain = DualChain::<_, 2, 1>::with_transport(Recorder::default());
+        chain.transport.read[..2].copy_from_slice(&[0x12, 0x34]);
+        assert!(chain.update().is_ok());
+
+        chain.transport.read[..2].copy_from_slice(&[0xFF, 0xFF]);
+        chain.transport.fail = true;
+        assert_eq!(chain.update(), Err(Error::Transport(Fault)));
+        assert_eq!(chain.get_input_word::<u16>(0), Ok(0x3412));
+    }
+
+    /// Checks that an update polled in steps of 3 bits matches `update()`.
+    fn check_poll<const IN_LEN: usize, const OUT_LEN: usize>() {
+        let encoded_chain = || {
+            let mut chain = DualChain::<_, IN_LEN, OUT_LEN>::with_transport(Recorder::default());
+            chain.set_bit_order(BitOrder::LsbFirst);
+            chain.set_chip_order(ChipOrder::FarthestFirst);
+            assert_eq!(chain.set_inverted_output(1, true), Ok(()));
+            assert_eq!(chain.set_inverted_input(2, true), Ok(()));
+            assert_eq!(chain.set_output_word(0, 0xC3u8), Ok(()));
+            chain.transport.read[..3].copy_from_slice(&[0x12, 0x34, 0xA5]);
+            chain
+        };
+
+        let mut expected = encoded_chain();
+        assert!(expected.update().is_ok());
+
+        let mut chain = encoded_chain();
+        let mut calls = 1;
+
+        while let Err(error) = chain.poll_update(3) {
+            assert!(matches!(error, nb::Error::WouldBlock));
+            calls += 1;
+        }
+
+        assert_eq!(calls, (IN_LEN.max(OUT_LEN) * 8).div_ceil(3));
+        assert_eq!(chain.transport.written(), expected.transport.written());
+        assert_eq!(chain.transport.transfers, 1);
+        assert!(chain.inputs().eq(expected.inputs()));
+    }
+
+    #[test]
+    fn poll_update_resumes() {
+        check_poll::<2, 2>();
+        check_poll::<3, 1>();
+        check_poll::<1, 3>();
+    }
+
+    #[test]
+    fn poll_update_keeps_inputs_until_completed() {
+        let mut chain = DualChain::<_, 2, 1>::with_transport(Recorder::default());
+        chain.transport.read[..2].copy_from_slice(&[0x12, 0x34]);
+        assert!(chain.update().is_ok());
+
+        chain.transport.read[..2].copy_from_slice(&[0xFF, 0xFF]);
+        assert_eq!(chain.poll_update(12), Err(nb::Error::WouldBlock));
+        assert_eq!(chain.get_input_word::<u16>(0), Ok(0x3412));
+
+        chain.transport.fail = true;
+        assert_eq!(
+            chain.poll_update(4),
+            Err(nb::Error::Other(Error::Transport(Fault)))
+        );
+        assert_eq!(chain.get_input_word::<u16>(0), Ok(0x3412));
+
+        chain.transport.fail = false;
+        assert!(chain.poll_update(16).is_ok());
+        assert_eq!(chain.get_input_word::<u16>(0), Ok(0xFFFF));
+    }
+
+    #[cfg(feature = "eh1")]
+    #[test]
+    fn brightness_sets_inverted_duty_cycle() -> Result<(), Error> {
+        let lamps = output::Chain::<Recorder, 1>::with_transport(Recorder::default());
+        let pwm = RefCell::new(crate::pwm::SoftPwm::new(&lamps, [0], 100)?);
+        let mut chain = DualChain::<_, 1>::with_transport(Recorder::default())
+            .with_oe_pin(crate::pwm::Channel::new(&pwm, 0)?);
+        assert_eq!(chain.max_brightness(), 100);
+
+        assert_eq!(chain.set_brightness(30), Ok(()));
+        assert_eq!(pwm.borrow().duty_cycle(0), Ok(70));
+
+        assert_eq!(chain.set_brightness(200), Ok(()));
+        assert_eq!(pwm.borrow().duty_cycle(0), Ok(0));
+
+        Ok(())
+    }
+
+    /// Checks that the DMA buffers match the bytes transferred by an update.
+    #[cfg(feature = "embedded-dma")]
+    fn check_dma<const IN_LEN: usize, const OUT_LEN: usize>() {
+        use embedded_dma::{ReadTarget, WriteTarget};
+
+        let encoded_chain = || {
+            let mut chain = DualChain::<_, IN_LEN, OUT_LEN>::with_transport(Recorder::default());
+            chain.set_bit_order(BitOrder::LsbFirst);
+            chain.set_chip_order(ChipOrder::FarthestFirst);
+            assert_eq!(chain.set_inverted_output(1, true), Ok(()));
+            assert_eq!(chain.set_inverted_input(2, true), Ok(()));
+            assert_eq!(chain.set_output_word(0, 0xC3u8), Ok(()));
+            chain.transport.read[..3].copy_from_slice(&[0x81, 0x3C, 0xA5]);
+            chain
+        };
+
+        let mut expected = encoded_chain();
+        assert!(expected.update().is_ok());
+
+        let mut chain = encoded_chain();
+        let read = chain.transport.read;
+        let mut transfer = chain.prepare_dma();
+
+        let (ptr, len) = transfer.as_read_buffer();
+        // SAFETY: The buffer is borrowed by the transfer, which is alive
+        // while the slice is.
+        let data = unsafe { core::slice::from_raw_parts(ptr, len) };
+        assert_eq!(data, expected.transport.written());
+
+        let (ptr, len) = transfer.as_write_buffer();
+        assert_eq!(len, IN_LEN.max(OUT_LEN));
+        // SAFETY: The buffer is borrowed by the transfer and not accessed
+        // otherwise while the slice is alive.
+        let data = unsafe { core::slice::from_raw_parts_mut(ptr, len) };
+        data.copy_from_slice(&read[..len]);
+
+        assert!(transfer.finish().is_ok());
+        assert!(chain.inputs().eq(expected.inputs()));
+    }
+
+    #[cfg(feature = "embedded-dma")]
+    #[test]
+    fn dma_buffers_are_wire_encoded() {
+        check_dma::<2, 2>();
+        check_dma::<3, 1>();
+        check_dma::<1, 3>();
     }
 }
@@ -1,10 +1,29 @@
 //! Single chain of 8-bit PISO shift registers (e.g. 74HC165) for digital input
 
-use core::cell::RefCell;
+#[cfg(any(feature = "eh1", feature = "eh02"))]
+use core::convert::Infallible;
+#[cfg(feature = "async")]
+use core::task::{Poll, Waker};
+use core::{cell::RefCell, ops::Index};
 
-use embedded_hal::digital::{ErrorType, InputPin, OutputPin};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+    spi::SpiBus,
+};
 
-use crate::{Error, Length};
+use crate::{
+    age::UpdateAge,
+    event::Events,
+    output::{get_bits, Word},
+    register_bytes,
+    transport::{
+        ClockConfig, LatchPolarity, NoDelay, Settle, ShiftTransport, SpiError, StepTransport,
+    },
+    BitOrder, ChipOrder, Error, Length, Update,
+};
+#[cfg(feature = "async")]
+use crate::{transport::AsyncShiftTransport, waker::WakerRegistration, AsyncUpdate};
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -15,113 +34,1921 @@ pub trait GetInput {
 
     /// Returns the input state for a pin without pin boundary checks.
     fn get_input_unchecked(&self, pin: usize) -> bool;
+
+    /// Copies the input buffer into `data`, starting with the first chip.
+    ///
+    /// Bit `n` of byte `i` is the state of pin `i * 8 + n`. Returns an error
+    /// if `data` is longer than the chain.
+    fn read_inputs(&self, data: &mut [u8]) -> Result<(), Error>;
+
+    /// Checks whether the buffered input states are valid, e.g. not stale.
+    fn check_inputs(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Returns the input states of consecutive pins as a word, starting
+    /// with the LSB at `start_pin`.
+    ///
+    /// The pins may span several chips. Returns an error if they exceed the
+    /// chain or the input states are not valid.
+    fn get_input_word<W: Word>(&self, start_pin: usize) -> Result<W, Error>
+    where
+        Self: Length,
+    {
+        let value = get_bits(
+            self,
+            self.input_len() * 8,
+            start_pin,
+            W::BYTES * 8,
+            Self::get_input_unchecked,
+        )?;
+        self.check_inputs()?;
+
+        Ok(W::from_u64(value))
+    }
+
+    /// Returns the input states of `len` consecutive pins, starting with the
+    /// LSB at `start_pin`.
+    ///
+    /// Returns an error if `len` exceeds 32, the pins exceed the chain or
+    /// the input states are not valid.
+    fn get_input_range(&self, start_pin: usize, len: usize) -> Result<u32, Error>
+    where
+        Self: Length,
+    {
+        if len > 32 {
+            return Err(Error::InvalidRange);
+        }
+
+        let value = get_bits(
+            self,
+            self.input_len() * 8,
+            start_pin,
+            len,
+            Self::get_input_unchecked,
+        )?;
+        self.check_inputs()?;
+
+        Ok(value as u32)
+    }
+
+    /// Reads a group of `width` consecutive pins as a number, starting with
+    /// the LSB at `start_pin`, e.g. a bank of DIP switches or address jumpers.
+    ///
+    /// The unused upper bits of the returned word are 0. Returns an error if
+    /// `width` exceeds the word size, the pins exceed the chain or the input
+    /// states are not valid.
+    fn read_group<W: Word>(&self, start_pin: usize, width: usize) -> Result<W, Error>
+    where
+        Self: Length,
+    {
+        if width > W::BYTES * 8 {
+            return Err(Error::InvalidRange);
+        }
+
+        let value = get_bits(
+            self,
+            self.input_len() * 8,
+            start_pin,
+            width,
+            Self::get_input_unchecked,
+        )?;
+        self.check_inputs()?;
+
+        Ok(W::from_u64(value))
+    }
+
+    /// Reads a coded rotary switch on the 4 pins starting at `start_pin`,
+    /// e.g. a BCD switch with 10 `positions` or a hex switch with 16.
+    ///
+    /// Returns `None` for codes not below `positions`, e.g. while a BCD
+    /// switch is between two positions. Returns an error if the pins exceed
+    /// the chain or the input states are not valid.
+    fn read_rotary_switch(&self, start_pin: usize, positions: u8) -> Result<Option<u8>, Error>
+    where
+        Self: Length,
+    {
+        let code: u8 = self.read_group(start_pin, 4)?;
+
+        Ok((code < positions).then_some(code))
+    }
+
+    /// Reads a BCD rotary switch on the 4 pins starting at `start_pin`.
+    ///
+    /// Returns `None` for the illegal codes above 9. Returns an error if the
+    /// pins exceed the chain or the input states are not valid.
+    fn read_bcd(&self, start_pin: usize) -> Result<Option<u8>, Error>
+    where
+        Self: Length,
+    {
+        self.read_rotary_switch(start_pin, 10)
+    }
+
+    /// Returns an iterator over the pin numbers and input states of the
+    /// whole chain.
+    ///
+    /// The states are taken from the buffer without checking their validity.
+    fn inputs(&self) -> impl Iterator<Item = (usize, bool)>
+    where
+        Self: Length,
+    {
+        (0..self.input_len() * 8).map(move |pin| (pin, self.get_input_unchecked(pin)))
+    }
+}
+
+/// Function called by a chain after an update changed inputs, with the
+/// changed inputs and the input states.
+pub type ChangeObserver<const CHAIN_LENGTH: usize> =
+    fn(changes: &Snapshot<CHAIN_LENGTH>, inputs: &Snapshot<CHAIN_LENGTH>);
+
+/// Trait to be implemented by chains that can wake tasks after an update.
+#[cfg(feature = "async")]
+pub trait WakeOnUpdate {
+    /// Registers a waker to be woken after the next update.
+    ///
+    /// The wakers of up to 4 tasks are kept at the same time. Further tasks
+    /// are woken right away to poll again.
+    fn register_waker(&mut self, waker: &Waker);
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Sampling mode of the inputs used by `update()` and `update_async()`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Sampling {
+    /// Inputs are read once.
+    #[default]
+    Single,
+
+    /// Inputs are read twice and only accepted if both reads match,
+    /// otherwise `Error::Mismatch` is returned.
+    Verify,
+
+    /// Inputs are read three times and each bit is set to the state of at
+    /// least two of the reads.
+    Majority,
+}
+
+impl Sampling {
+    /// Returns the number of reads per update.
+    fn reads(self) -> usize {
+        match self {
+            Self::Single => 1,
+            Self::Verify => 2,
+            Self::Majority => 3,
+        }
+    }
+}
+
+/// Profile of the PISO chips in the chain, selecting the signal polarities
+/// and timing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChipProfile {
+    /// 74HC165 and alike, loading while SH/LD is low.
+    #[default]
+    Hc165,
+
+    /// CD4021 and alike, loading while P/S is high. The CMOS chips need
+    /// slower signals, so the bit-banged transports should be created with a
+    /// delay and SPI buses clocked at 1 MHz or less.
+    Cd4021,
+}
+
+impl ChipProfile {
+    /// Returns the polarity of the load signal for a pin wired directly to
+    /// the chips.
+    pub fn latch_polarity(self) -> LatchPolarity {
+        match self {
+            Self::Hc165 => LatchPolarity::Normal,
+            Self::Cd4021 => LatchPolarity::Inverted,
+        }
+    }
+
+    /// Returns the minimum settle time in nanoseconds at a supply of 5 V.
+    ///
+    /// This covers the minimum pulse widths of the clock and load signals,
+    /// lower supply voltages need longer times.
+    pub fn settle_ns(self) -> u32 {
+        match self {
+            Self::Hc165 => 0,
+            Self::Cd4021 => 200,
+        }
+    }
+}
+
+/// Maximum number of reads per update of all sampling modes.
+const MAX_READS: usize = 3;
+
+/// Inverts the bits of `data` set in `mask`.
+pub(crate) fn invert(data: &mut [u8], mask: &[u8]) {
+    for (data, mask) in data.iter_mut().zip(mask) {
+        *data ^= mask;
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Input states of a chain captured after an update.
+///
+/// Indexing with a pin number returns its state, snapshots of two scans can
+/// be compared to detect changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Snapshot<const CHAIN_LENGTH: usize> {
+    /// Input states, 8 pins per byte starting with pin 0 in byte 0.
+    data: [u8; CHAIN_LENGTH],
+}
+
+impl<const CHAIN_LENGTH: usize> Snapshot<CHAIN_LENGTH> {
+    /// Creates a snapshot from bytes in pin order.
+    ///
+    /// Bit `n` of byte `i` is the state of pin `i * 8 + n`.
+    pub const fn from_bytes(data: [u8; CHAIN_LENGTH]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the bytes in pin order.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the state of a pin or `None` if out of range.
+    pub fn get(&self, pin: usize) -> Option<bool> {
+        (pin < CHAIN_LENGTH * 8).then(|| self.get_input_unchecked(pin))
+    }
+
+    /// Returns a snapshot with the states set for all pins that differ from
+    /// `previous`.
+    pub fn changes(&self, previous: &Self) -> Self {
+        self.combine(previous, |data, previous| data ^ previous)
+    }
+
+    /// Returns a snapshot with the states set for all pins that are high
+    /// but were low in `previous`.
+    pub fn rising_edges(&self, previous: &Self) -> Self {
+        self.combine(previous, |data, previous| data & !previous)
+    }
+
+    /// Returns a snapshot with the states set for all pins that are low
+    /// but were high in `previous`.
+    pub fn falling_edges(&self, previous: &Self) -> Self {
+        self.combine(previous, |data, previous| !data & previous)
+    }
+
+    /// Returns an iterator over the transitions from `previous` to this
+    /// snapshot.
+    pub fn events(&self, previous: &Self) -> Events<CHAIN_LENGTH> {
+        Events::new(self, previous)
+    }
+
+    /// Combines the bytes with those of `other` using `f`.
+    fn combine(&self, other: &Self, f: impl Fn(u8, u8) -> u8) -> Self {
+        let mut data = self.data;
+
+        for (data, other) in data.iter_mut().zip(other.data) {
+            *data = f(*data, other);
+        }
+
+        Self { data }
+    }
+}
+
+impl<const CHAIN_LENGTH: usize> Default for Snapshot<CHAIN_LENGTH> {
+    fn default() -> Self {
+        Self::from_bytes([0; CHAIN_LENGTH])
+    }
+}
+
+impl<const CHAIN_LENGTH: usize> Index<usize> for Snapshot<CHAIN_LENGTH> {
+    type Output = bool;
+
+    /// Returns the state of a pin.
+    ///
+    /// Panics if the pin is out of range.
+    fn index(&self, pin: usize) -> &bool {
+        if self.data[pin / 8] & (1 << (pin % 8)) != 0 {
+            &true
+        } else {
+            &false
+        }
+    }
+}
+
+impl<const CHAIN_LENGTH: usize> GetInput for Snapshot<CHAIN_LENGTH> {
+    fn get_input(&self, pin: usize) -> Result<bool, Error> {
+        self.get(pin).ok_or(Error::PinOutOfRange)
+    }
+
+    fn get_input_unchecked(&self, pin: usize) -> bool {
+        self[pin]
+    }
+
+    fn read_inputs(&self, data: &mut [u8]) -> Result<(), Error> {
+        if data.len() > CHAIN_LENGTH {
+            return Err(Error::InvalidRange);
+        }
+
+        data.copy_from_slice(&self.data[..data.len()]);
+
+        Ok(())
+    }
+}
+
+impl<const CHAIN_LENGTH: usize> Length for Snapshot<CHAIN_LENGTH> {
+    /// Returns the chain length.
+    fn len(&self) -> usize {
+        CHAIN_LENGTH
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Chain of PISO shift registers.
+pub struct Chain<Transport, const CHAIN_LENGTH: usize> {
+    /// Transport shifting the data from the chips.
+    transport: Transport,
+
+    /// Buffer storing the data read from pins.
+    data_buffer: [u8; CHAIN_LENGTH],
+
+    /// Number of bits already shifted by an incremental update.
+    shift_position: usize,
+
+    /// Data shifted in by an incremental update, in wire order.
+    shift_buffer: [u8; CHAIN_LENGTH],
+
+    /// Age of the input data.
+    age: UpdateAge,
+
+    /// Input states after the last completed update.
+    previous_inputs: Snapshot<CHAIN_LENGTH>,
+
+    /// Inputs changed by the last completed update.
+    changes: Snapshot<CHAIN_LENGTH>,
+
+    /// Inputs high after any update since they were cleared.
+    latched_inputs: [u8; CHAIN_LENGTH],
+
+    /// Function called after an update changed inputs.
+    change_observer: Option<ChangeObserver<CHAIN_LENGTH>>,
+
+    /// Sampling mode of the inputs.
+    sampling: Sampling,
+
+    /// Consecutive updates a changed input must read identically.
+    glitch_samples: u8,
+
+    /// Consecutive updates each input read differently from the buffer.
+    glitch_counters: [[u8; 8]; CHAIN_LENGTH],
+
+    /// Order in which the bits of each chip are shifted.
+    bit_order: BitOrder,
+
+    /// Order in which the chips are numbered.
+    chip_order: ChipOrder,
+
+    /// Number of bytes per chip.
+    register_bytes: usize,
+
+    /// Inputs inverted when shifted in, in pin order.
+    input_inversion: [u8; CHAIN_LENGTH],
+
+    /// Task waiting for the next update.
+    #[cfg(feature = "async")]
+    waker: WakerRegistration,
+
+    /// Input data as shifted in, written by DMA transfers.
+    #[cfg(feature = "embedded-dma")]
+    dma_buffer: [u8; CHAIN_LENGTH],
+}
+
+/// Chain of PISO shift registers using bit-banged pins.
+pub type BitBangChain<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize> =
+    Chain<BitBangTransport<ClockPin, LatchPin, DataPin>, CHAIN_LENGTH>;
+
+/// Chain of PISO shift registers with a storage register (e.g. 74HC597)
+/// using bit-banged pins.
+pub type BitBangStorageChain<ClockPin, LatchPin, LoadPin, DataPin, const CHAIN_LENGTH: usize> =
+    Chain<BitBangStorageTransport<ClockPin, LatchPin, LoadPin, DataPin>, CHAIN_LENGTH>;
+
+/// Chain of PISO shift registers read by a hardware SPI bus.
+pub type SpiChain<Spi, LoadPin, const CHAIN_LENGTH: usize> =
+    Chain<SpiTransport<Spi, LoadPin>, CHAIN_LENGTH>;
+
+impl<Transport, const CHAIN_LENGTH: usize> Chain<Transport, CHAIN_LENGTH> {
+    /// Creates a new chain by consuming a transport.
+    pub fn with_transport(transport: Transport) -> Self {
+        Self {
+            transport,
+            data_buffer: [0; CHAIN_LENGTH],
+            shift_position: 0,
+            shift_buffer: [0; CHAIN_LENGTH],
+            age: UpdateAge::new(),
+            previous_inputs: Snapshot::from_bytes([0; CHAIN_LENGTH]),
+            changes: Snapshot::from_bytes([0; CHAIN_LENGTH]),
+            latched_inputs: [0; CHAIN_LENGTH],
+            change_observer: None,
+            sampling: Sampling::Single,
+            glitch_samples: 1,
+            glitch_counters: [[0; 8]; CHAIN_LENGTH],
+            bit_order: BitOrder::MsbFirst,
+            chip_order: ChipOrder::NearestFirst,
+            register_bytes: 1,
+            input_inversion: [0; CHAIN_LENGTH],
+            #[cfg(feature = "async")]
+            waker: WakerRegistration::new(),
+            #[cfg(feature = "embedded-dma")]
+            dma_buffer: [0; CHAIN_LENGTH],
+        }
+    }
+
+    /// Frees the chain and returns the transport.
+    pub fn into_transport(self) -> Transport {
+        self.transport
+    }
+
+    /// Moves the chain into a chain with the transport converted by `f`.
+    fn map_transport<NewTransport>(
+        self,
+        f: impl FnOnce(Transport) -> NewTransport,
+    ) -> Chain<NewTransport, CHAIN_LENGTH> {
+        Chain {
+            transport: f(self.transport),
+            data_buffer: self.data_buffer,
+            shift_position: self.shift_position,
+            shift_buffer: self.shift_buffer,
+            age: self.age,
+            previous_inputs: self.previous_inputs,
+            changes: self.changes,
+            latched_inputs: self.latched_inputs,
+            change_observer: self.change_observer,
+            sampling: self.sampling,
+            glitch_samples: self.glitch_samples,
+            glitch_counters: self.glitch_counters,
+            bit_order: self.bit_order,
+            chip_order: self.chip_order,
+            register_bytes: self.register_bytes,
+            input_inversion: self.input_inversion,
+            #[cfg(feature = "async")]
+            waker: self.waker,
+            #[cfg(feature = "embedded-dma")]
+            dma_buffer: self.dma_buffer,
+        }
+    }
+
+    /// Returns the input buffer.
+    ///
+    /// Byte `i` holds the inputs of chip `i`, counted as set by the chip
+    /// order, with bit `n` being the state of pin `i * 8 + n`.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data_buffer
+    }
+
+    /// Returns a snapshot of the input states.
+    pub fn snapshot(&self) -> Snapshot<CHAIN_LENGTH> {
+        Snapshot::from_bytes(self.data_buffer)
+    }
+
+    /// Returns the inputs changed by the last completed update.
+    ///
+    /// The inputs are compared to the states after the previous update, or
+    /// all low before the first one.
+    pub fn changes(&self) -> Snapshot<CHAIN_LENGTH> {
+        self.changes
+    }
+
+    /// Returns the inputs that changed from low to high by the last
+    /// completed update.
+    pub fn rising_edges(&self) -> Snapshot<CHAIN_LENGTH> {
+        let previous = self.previous_inputs.changes(&self.changes);
+        self.previous_inputs.rising_edges(&previous)
+    }
+
+    /// Returns the inputs that changed from high to low by the last
+    /// completed update.
+    pub fn falling_edges(&self) -> Snapshot<CHAIN_LENGTH> {
+        let previous = self.previous_inputs.changes(&self.changes);
+        self.previous_inputs.falling_edges(&previous)
+    }
+
+    /// Returns an iterator over the transitions of the last completed update.
+    pub fn events(&self) -> Events<CHAIN_LENGTH> {
+        let previous = self.previous_inputs.changes(&self.changes);
+        self.previous_inputs.events(&previous)
+    }
+
+    /// Sets a function called after each completed update that changed
+    /// inputs, e.g. to notify subsystems directly, `None` by default.
+    pub fn set_change_observer(&mut self, observer: Option<ChangeObserver<CHAIN_LENGTH>>) {
+        self.change_observer = observer;
+    }
+
+    /// Returns the inputs that were high after any update since they were
+    /// cleared, so short pulses between two reads are not missed.
+    pub fn latched_inputs(&self) -> Snapshot<CHAIN_LENGTH> {
+        Snapshot::from_bytes(self.latched_inputs)
+    }
+
+    /// Returns the latched inputs and clears them.
+    pub fn take_latched_inputs(&mut self) -> Snapshot<CHAIN_LENGTH> {
+        Snapshot::from_bytes(core::mem::replace(
+            &mut self.latched_inputs,
+            [0; CHAIN_LENGTH],
+        ))
+    }
+
+    /// Clears the latched state of an input.
+    pub fn clear_latched_input(&mut self, pin: usize) -> Result<(), Error> {
+        let chip = self
+            .latched_inputs
+            .get_mut(pin / 8)
+            .ok_or(Error::PinOutOfRange)?;
+        *chip &= !(1 << (pin % 8));
+
+        Ok(())
+    }
+
+    /// Returns the input buffer mutably, e.g. to be filled by external means.
+    ///
+    /// Byte `i` holds the inputs of chip `i`, counted as set by the chip
+    /// order, with bit `n` being the state of pin `i * 8 + n`.
+    pub fn as_mut_bytes(&mut self) -> &mut [u8] {
+        &mut self.data_buffer
+    }
+
+    /// Returns the sampling mode of the inputs.
+    pub fn sampling(&self) -> Sampling {
+        self.sampling
+    }
+
+    /// Sets the sampling mode of the inputs.
+    ///
+    /// The mode applies to `update()` and `update_async()`. Incremental
+    /// updates read the inputs once, so `poll_update()` returns
+    /// `Error::Unsupported` for modes with several reads.
+    pub fn set_sampling(&mut self, sampling: Sampling) {
+        self.sampling = sampling;
+    }
+
+    /// Returns the number of consecutive updates a changed input must read
+    /// identically before the buffer changes.
+    pub fn glitch_filter(&self) -> u8 {
+        self.glitch_samples
+    }
+
+    /// Sets the number of consecutive updates a changed input must read
+    /// identically before the buffer changes, e.g. to reject single-update
+    /// noise on long cables. 0 or 1 accepts changes immediately, which is
+    /// the default.
+    pub fn set_glitch_filter(&mut self, samples: u8) {
+        self.glitch_samples = samples;
+        self.glitch_counters = [[0; 8]; CHAIN_LENGTH];
+    }
+
+    /// Returns the order in which the bits of each chip are shifted.
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Sets the order in which the bits of each chip are shifted.
+    ///
+    /// Bit `n` of each buffer byte holds pin `n` of the chip regardless of
+    /// the bit order.
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) {
+        self.bit_order = bit_order;
+    }
+
+    /// Returns the order in which the chips are numbered.
+    pub fn chip_order(&self) -> ChipOrder {
+        self.chip_order
+    }
+
+    /// Sets the order in which the chips are numbered.
+    ///
+    /// By default, pin 0 is on the chip nearest to the controller, the same
+    /// as for the output chains. `ChipOrder::FarthestFirst` numbers the pins
+    /// from the far end of the chain instead.
+    pub fn set_chip_order(&mut self, chip_order: ChipOrder) {
+        self.chip_order = chip_order;
+    }
+
+    /// Returns the number of bits per chip.
+    pub fn register_bits(&self) -> usize {
+        self.register_bytes * 8
+    }
+
+    /// Sets the number of bits per chip at runtime, 8 by default.
+    ///
+    /// The chain length remains the number of bytes and the pins are numbered
+    /// consecutively across the chips. The width only changes how the bit
+    /// order applies, so chains of chips with different widths can be
+    /// used with `BitOrder::MsbFirst` and the default width. Returns an error
+    /// if the width is not a multiple of 8 or the chain length is not a
+    /// multiple of it.
+    pub fn set_register_bits(&mut self, bits: usize) -> Result<(), Error> {
+        self.register_bytes = register_bytes(bits, CHAIN_LENGTH).ok_or(Error::InvalidRange)?;
+
+        Ok(())
+    }
+
+    /// Sets the inputs inverted when shifted in, e.g. for switches pulling
+    /// low when pressed, so the buffer holds the logical states.
+    ///
+    /// Bit `n` of byte `i` inverts pin `i * 8 + n`, pins beyond `mask` are
+    /// not inverted. Returns an error if `mask` is longer than the chain.
+    /// The buffer is not changed until the next update.
+    pub fn set_input_inversion(&mut self, mask: &[u8]) -> Result<(), Error> {
+        if mask.len() > CHAIN_LENGTH {
+            return Err(Error::InvalidRange);
+        }
+
+        self.input_inversion = [0; CHAIN_LENGTH];
+        self.input_inversion[..mask.len()].copy_from_slice(mask);
+
+        Ok(())
+    }
+
+    /// Sets whether an input is inverted when shifted in.
+    ///
+    /// The buffer is not changed until the next update.
+    pub fn set_inverted_input(&mut self, pin: usize, inverted: bool) -> Result<(), Error> {
+        if pin >= CHAIN_LENGTH * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        if inverted {
+            self.input_inversion[pin / 8] |= 1 << (pin % 8);
+        } else {
+            self.input_inversion[pin / 8] &= !(1 << (pin % 8));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the reads of an update according to the sampling mode and
+    /// copies the accepted data into the buffer.
+    fn accept_reads<E>(&mut self, reads: &[[u8; CHAIN_LENGTH]; MAX_READS]) -> Result<(), Error<E>> {
+        match self.sampling {
+            Sampling::Single => self.data_buffer = reads[0],
+            Sampling::Verify => {
+                if reads[0] != reads[1] {
+                    return Err(Error::Mismatch);
+                }
+
+                self.data_buffer = reads[0];
+            }
+            Sampling::Majority => {
+                for (index, data) in self.data_buffer.iter_mut().enumerate() {
+                    let (a, b, c) = (reads[0][index], reads[1][index], reads[2][index]);
+                    *data = (a & b) | (a & c) | (b & c);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Advances the age of the input data by one tick.
+    ///
+    /// Intended to be called periodically, e.g. from a timer, to detect
+    /// stale data in case the updates stopped.
+    pub fn tick(&mut self) {
+        self.age.tick();
+    }
+
+    /// Returns the ticks since the last successful update, `None` if the
+    /// chain has not been updated yet.
+    pub fn age(&self) -> Option<u32> {
+        self.age.age()
+    }
+
+    /// Sets the maximum age of the input data in ticks, `None` disables
+    /// the check.
+    ///
+    /// With a maximum age set, `get_input()` returns `Error::NotUpdated`
+    /// before the first update and `Error::Stale` if the data is older.
+    pub fn set_max_age(&mut self, max_age: Option<u32>) {
+        self.age.set_max_age(max_age);
+    }
+
+    /// Converts data shifted in into pin order, with the bit order, the chip
+    /// order and the inversion applied.
+    fn decode(&self, data: &mut [u8; CHAIN_LENGTH]) {
+        self.bit_order.reorder_registers(data, self.register_bytes);
+        self.chip_order.reorder(data, self.register_bytes);
+        invert(data, &self.input_inversion);
+    }
+
+    /// Keeps changed inputs at their previous state until they were read
+    /// identically by the number of updates set by the glitch filter.
+    fn filter_glitches(&mut self) {
+        if self.glitch_samples <= 1 {
+            return;
+        }
+
+        let previous = self.previous_inputs.as_bytes();
+
+        for (index, (data, counters)) in self
+            .data_buffer
+            .iter_mut()
+            .zip(self.glitch_counters.iter_mut())
+            .enumerate()
+        {
+            for (bit, counter) in counters.iter_mut().enumerate() {
+                let mask = 1 << bit;
+
+                if (*data ^ previous[index]) & mask == 0 {
+                    *counter = 0;
+                    continue;
+                }
+
+                *counter = counter.saturating_add(1);
+
+                if *counter < self.glitch_samples {
+                    *data ^= mask;
+                } else {
+                    *counter = 0;
+                }
+            }
+        }
+    }
+
+    /// Finishes an update after all data has been shifted.
+    fn complete_update(&mut self) {
+        self.age.reset();
+        self.filter_glitches();
+
+        let inputs = self.snapshot();
+        self.changes = inputs.changes(&self.previous_inputs);
+        self.previous_inputs = inputs;
+
+        for (latched, data) in self.latched_inputs.iter_mut().zip(self.data_buffer) {
+            *latched |= data;
+        }
+
+        if let Some(observer) = self.change_observer {
+            if self.changes != Snapshot::default() {
+                observer(&self.changes, &inputs);
+            }
+        }
+
+        #[cfg(feature = "async")]
+        self.waker.wake();
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize>
+    Chain<BitBangTransport<ClockPin, LatchPin, DataPin>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: InputPin,
+{
+    /// Creates a new chain by consuming the pins.
+    pub fn new(clock_pin: ClockPin, latch_pin: LatchPin, data_pin: DataPin) -> Self {
+        Self::with_transport(BitBangTransport::new(clock_pin, latch_pin, data_pin))
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay, const CHAIN_LENGTH: usize>
+    Chain<BitBangTransport<ClockPin, LatchPin, DataPin, Delay>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: InputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new chain by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_pin: DataPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self::with_transport(BitBangTransport::with_delay(
+            clock_pin, latch_pin, data_pin, delay, settle_ns,
+        ))
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay, InhibitPin, const CHAIN_LENGTH: usize>
+    Chain<BitBangTransport<ClockPin, LatchPin, DataPin, Delay, InhibitPin>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: InputPin,
+    Delay: DelayNs,
+    InhibitPin: OutputPin,
+{
+    /// Attaches a pin driving the clock inhibit input (CLK INH) of the
+    /// chips, see `BitBangTransport::with_inhibit_pin()`.
+    pub fn with_inhibit_pin<NewInhibitPin>(
+        self,
+        inhibit_pin: NewInhibitPin,
+    ) -> Chain<BitBangTransport<ClockPin, LatchPin, DataPin, Delay, NewInhibitPin>, CHAIN_LENGTH>
+    where
+        NewInhibitPin: OutputPin,
+    {
+        self.map_transport(|transport| transport.with_inhibit_pin(inhibit_pin))
+    }
+
+    /// Detaches the clock inhibit pin of the transport and returns it.
+    pub fn take_inhibit_pin(&mut self) -> Option<InhibitPin> {
+        self.transport.take_inhibit_pin()
+    }
+
+    /// Frees the chain and returns the pins.
+    ///
+    /// The clock inhibit pin is dropped, use `take_inhibit_pin()` before
+    /// to get it back.
+    pub fn free(self) -> (ClockPin, LatchPin, DataPin) {
+        self.transport.free()
+    }
+
+    /// Frees the chain and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, LatchPin, DataPin, Delay) {
+        self.transport.free_with_delay()
+    }
+
+    /// Returns the clock configuration of the transport.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.transport.clock_config()
+    }
+
+    /// Sets the clock configuration of the transport.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.transport.set_clock_config(clock);
+    }
+
+    /// Returns the polarity of the load signal of the transport.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.transport.latch_polarity()
+    }
+
+    /// Sets the polarity of the load signal of the transport.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_latch_polarity(polarity);
+    }
+
+    /// Returns the settle time of the transport in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.transport.settle_ns()
+    }
+
+    /// Sets the settle time of the transport in nanoseconds, 0 disables
+    /// the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.transport.set_settle_ns(settle_ns);
+    }
+
+    /// Configures the transport for the chips of a profile.
+    ///
+    /// Sets the load polarity and the default clock configuration, and raises
+    /// the settle time to the minimum of the profile. The settle time only
+    /// takes effect if the chain was created with a delay.
+    pub fn set_chip_profile(&mut self, profile: ChipProfile) {
+        self.set_clock_config(ClockConfig::default());
+        self.set_latch_polarity(profile.latch_polarity());
+        self.set_settle_ns(self.settle_ns().max(profile.settle_ns()));
+    }
+}
+
+impl<ClockPin, LatchPin, LoadPin, DataPin, const CHAIN_LENGTH: usize>
+    Chain<BitBangStorageTransport<ClockPin, LatchPin, LoadPin, DataPin>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    LoadPin: OutputPin,
+    DataPin: InputPin,
+{
+    /// Creates a new chain by consuming the pins.
+    pub fn new(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        load_pin: LoadPin,
+        data_pin: DataPin,
+    ) -> Self {
+        Self::with_transport(BitBangStorageTransport::new(
+            clock_pin, latch_pin, load_pin, data_pin,
+        ))
+    }
+}
+
+impl<ClockPin, LatchPin, LoadPin, DataPin, Delay, const CHAIN_LENGTH: usize>
+    Chain<BitBangStorageTransport<ClockPin, LatchPin, LoadPin, DataPin, Delay>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    LoadPin: OutputPin,
+    DataPin: InputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new chain by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        load_pin: LoadPin,
+        data_pin: DataPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self::with_transport(BitBangStorageTransport::with_delay(
+            clock_pin, latch_pin, load_pin, data_pin, delay, settle_ns,
+        ))
+    }
+
+    /// Frees the chain and returns the pins.
+    pub fn free(self) -> (ClockPin, LatchPin, LoadPin, DataPin) {
+        self.transport.free()
+    }
+
+    /// Frees the chain and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, LatchPin, LoadPin, DataPin, Delay) {
+        self.transport.free_with_delay()
+    }
+
+    /// Returns the clock configuration of the transport.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.transport.clock_config()
+    }
+
+    /// Sets the clock configuration of the transport.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.transport.set_clock_config(clock);
+    }
+
+    /// Returns the polarity of the latch signal of the transport.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.transport.latch_polarity()
+    }
+
+    /// Sets the polarity of the latch signal of the transport.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_latch_polarity(polarity);
+    }
+
+    /// Returns the polarity of the load signal of the transport.
+    pub fn load_polarity(&self) -> LatchPolarity {
+        self.transport.load_polarity()
+    }
+
+    /// Sets the polarity of the load signal of the transport.
+    pub fn set_load_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_load_polarity(polarity);
+    }
+
+    /// Returns the settle time of the transport in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.transport.settle_ns()
+    }
+
+    /// Sets the settle time of the transport in nanoseconds, 0 disables
+    /// the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.transport.set_settle_ns(settle_ns);
+    }
+}
+
+impl<Spi, LoadPin, const CHAIN_LENGTH: usize> Chain<SpiTransport<Spi, LoadPin>, CHAIN_LENGTH> {
+    /// Creates a new chain by consuming the SPI bus and the load pin.
+    pub fn new(spi: Spi, load_pin: LoadPin) -> Self {
+        Self::with_transport(SpiTransport::new(spi, load_pin))
+    }
+}
+
+impl<Spi, LoadPin, InhibitPin, const CHAIN_LENGTH: usize>
+    Chain<SpiTransport<Spi, LoadPin, InhibitPin>, CHAIN_LENGTH>
+{
+    /// Attaches a pin driving the clock inhibit input (CLK INH) of the
+    /// chips, see `SpiTransport::with_inhibit_pin()`.
+    pub fn with_inhibit_pin<NewInhibitPin>(
+        self,
+        inhibit_pin: NewInhibitPin,
+    ) -> Chain<SpiTransport<Spi, LoadPin, NewInhibitPin>, CHAIN_LENGTH> {
+        self.map_transport(|transport| transport.with_inhibit_pin(inhibit_pin))
+    }
+
+    /// Detaches the clock inhibit pin of the transport and returns it.
+    pub fn take_inhibit_pin(&mut self) -> Option<InhibitPin> {
+        self.transport.take_inhibit_pin()
+    }
+
+    /// Frees the chain and returns the SPI bus and the load pin.
+    ///
+    /// The clock inhibit pin is dropped, use `take_inhibit_pin()` before
+    /// to get it back.
+    pub fn free(self) -> (Spi, LoadPin) {
+        self.transport.free()
+    }
+
+    /// Returns the polarity of the load signal of the transport.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.transport.latch_polarity()
+    }
+
+    /// Sets the polarity of the load signal of the transport.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_latch_polarity(polarity);
+    }
+
+    /// Configures the load polarity for the chips of a profile.
+    ///
+    /// The clock rate of the bus has to match the chips, see
+    /// `ChipProfile`.
+    pub fn set_chip_profile(&mut self, profile: ChipProfile) {
+        self.set_latch_polarity(profile.latch_polarity());
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize> Chain<Transport, CHAIN_LENGTH>
+where
+    Transport: ShiftTransport,
+{
+    /// Updates the chain by shifting the data from the chips into the buffer.
+    ///
+    /// The inputs are read according to the sampling mode. Returns an error
+    /// if the transport failed or the reads were rejected, the buffer is left
+    /// unchanged in this case.
+    ///
+    /// On success, the inputs changed since the previous update are returned
+    /// as described by `changes()`.
+    pub fn update(&mut self) -> Result<Snapshot<CHAIN_LENGTH>, Error<Transport::Error>> {
+        self.shift_position = 0;
+
+        let mut reads = [[0; CHAIN_LENGTH]; MAX_READS];
+
+        for read in reads.iter_mut().take(self.sampling.reads()) {
+            self.transport
+                .transfer(read, &[])
+                .map_err(Error::Transport)?;
+            self.decode(read);
+        }
+
+        self.accept_reads(&reads)?;
+        self.complete_update();
+
+        Ok(self.changes)
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize> Chain<Transport, CHAIN_LENGTH>
+where
+    Transport: StepTransport,
+{
+    /// Updates the chain incrementally by shifting up to `bits` bits per call.
+    ///
+    /// Returns `WouldBlock` until the whole chain has been shifted. The buffer
+    /// keeps the previous inputs until the update has completed and is left
+    /// unchanged if the transport fails, in which case an error is returned
+    /// and the next call starts a new update.
+    ///
+    /// The inputs are read once, so `Error::Unsupported` is returned if the
+    /// sampling mode needs several reads. On success, the inputs changed
+    /// since the previous update are returned as described by `changes()`.
+    pub fn poll_update(
+        &mut self,
+        bits: usize,
+    ) -> nb::Result<Snapshot<CHAIN_LENGTH>, Error<Transport::Error>> {
+        self.step_update(bits).inspect_err(|error| {
+            if let nb::Error::Other(_) = error {
+                self.shift_position = 0;
+            }
+        })
+    }
+
+    /// Shifts up to `bits` bits for `poll_update()`.
+    fn step_update(
+        &mut self,
+        bits: usize,
+    ) -> nb::Result<Snapshot<CHAIN_LENGTH>, Error<Transport::Error>> {
+        if self.sampling.reads() > 1 {
+            return Err(nb::Error::Other(Error::Unsupported));
+        }
+
+        if self.shift_position == 0 {
+            self.transport.begin().map_err(Error::Transport)?;
+        }
+
+        let end = (self.shift_position + bits).min(CHAIN_LENGTH * 8);
+
+        for position in self.shift_position..end {
+            // Bits are shifted in MSB first, like the bytes of `transfer()`
+            let (index, bit) = (position / 8, 7 - position % 8);
+
+            if self.transport.shift_bit(false).map_err(Error::Transport)? {
+                self.shift_buffer[index] |= 1 << bit;
+            } else {
+                self.shift_buffer[index] &= !(1 << bit);
+            }
+        }
+
+        self.shift_position = end;
+
+        if end < CHAIN_LENGTH * 8 {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.transport.end().map_err(Error::Transport)?;
+        self.shift_position = 0;
+
+        let mut reads = [[0; CHAIN_LENGTH]; MAX_READS];
+        reads[0] = self.shift_buffer;
+        self.decode(&mut reads[0]);
+        self.accept_reads(&reads)?;
+        self.complete_update();
+
+        Ok(self.changes)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Transport, const CHAIN_LENGTH: usize> Chain<Transport, CHAIN_LENGTH>
+where
+    Transport: AsyncShiftTransport,
+{
+    /// Updates the chain by shifting the data from the chips into the buffer
+    /// asynchronously.
+    ///
+    /// The inputs are read according to the sampling mode. Returns an error
+    /// if the transport failed or the reads were rejected, the buffer is left
+    /// unchanged in this case.
+    ///
+    /// On success, the inputs changed since the previous update are returned
+    /// as described by `changes()`.
+    pub async fn update_async(
+        &mut self,
+    ) -> Result<Snapshot<CHAIN_LENGTH>, Error<Transport::Error>> {
+        self.shift_position = 0;
+
+        let mut reads = [[0; CHAIN_LENGTH]; MAX_READS];
+
+        for read in reads.iter_mut().take(self.sampling.reads()) {
+            self.transport
+                .transfer(read, &[])
+                .await
+                .map_err(Error::Transport)?;
+            self.decode(read);
+        }
+
+        self.accept_reads(&reads)?;
+        self.complete_update();
+
+        Ok(self.changes)
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize> GetInput for Chain<Transport, CHAIN_LENGTH> {
+    /// Returns the input state for a pin.
+    ///
+    /// The state is buffered and not read immediately because the bits
+    /// have to be shifted in by calling `update()` first.
+    fn get_input(&self, pin: usize) -> Result<bool, Error> {
+        if pin >= CHAIN_LENGTH * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        self.check_inputs()?;
+
+        Ok(self.get_input_unchecked(pin))
+    }
+
+    /// Returns the input state for a pin without pin boundary checks.
+    ///
+    /// The state is buffered and not read immediately because the bits
+    /// have to be shifted in by calling `update()` first.
+    fn get_input_unchecked(&self, pin: usize) -> bool {
+        // Calculate index and bit position within buffer array
+        let index = pin / 8;
+        let bit = pin % 8;
+
+        (self.data_buffer[index] & (1 << bit)) != 0
+    }
+
+    /// Copies the input buffer into `data`, starting with the first chip.
+    ///
+    /// The states are buffered and not read immediately because the bits
+    /// have to be shifted in by calling `update()` first.
+    fn read_inputs(&self, data: &mut [u8]) -> Result<(), Error> {
+        if data.len() > CHAIN_LENGTH {
+            return Err(Error::InvalidRange);
+        }
+
+        self.check_inputs()?;
+
+        data.copy_from_slice(&self.data_buffer[..data.len()]);
+
+        Ok(())
+    }
+
+    /// Checks whether the buffered input states are not stale.
+    fn check_inputs(&self) -> Result<(), Error> {
+        self.age.check()
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize> Update for Chain<Transport, CHAIN_LENGTH>
+where
+    Transport: ShiftTransport,
+{
+    type Error = Error<Transport::Error>;
+
+    fn update(&mut self) -> Result<(), Self::Error> {
+        Chain::update(self).map(|_| ())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Transport, const CHAIN_LENGTH: usize> AsyncUpdate for Chain<Transport, CHAIN_LENGTH>
+where
+    Transport: AsyncShiftTransport,
+{
+    type Error = Error<Transport::Error>;
+
+    async fn update_async(&mut self) -> Result<(), Self::Error> {
+        Chain::update_async(self).await.map(|_| ())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Transport, const CHAIN_LENGTH: usize> WakeOnUpdate for Chain<Transport, CHAIN_LENGTH> {
+    fn register_waker(&mut self, waker: &Waker) {
+        self.waker.register(waker);
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize> Length for Chain<Transport, CHAIN_LENGTH> {
+    /// Returns the chain length.
+    fn len(&self) -> usize {
+        CHAIN_LENGTH
+    }
+}
+
+#[cfg(feature = "embedded-dma")]
+impl<Transport, const CHAIN_LENGTH: usize> Chain<Transport, CHAIN_LENGTH> {
+    /// Prepares a DMA transfer into the chain.
+    ///
+    /// The returned transfer borrows the chain until `finish()` is called
+    /// after the DMA transfer.
+    pub fn prepare_dma(&mut self) -> DmaTransfer<'_, Transport, CHAIN_LENGTH> {
+        self.shift_position = 0;
+        DmaTransfer { chain: self }
+    }
+}
+
+/// DMA transfer into an input chain, returned by `prepare_dma()`.
+#[cfg(feature = "embedded-dma")]
+pub struct DmaTransfer<'a, Transport, const CHAIN_LENGTH: usize> {
+    /// Chain the DMA buffer belongs to.
+    chain: &'a mut Chain<Transport, CHAIN_LENGTH>,
+}
+
+#[cfg(feature = "embedded-dma")]
+impl<Transport, const CHAIN_LENGTH: usize> DmaTransfer<'_, Transport, CHAIN_LENGTH> {
+    /// Completes the update after the DMA transfer, decoding the DMA buffer
+    /// with the bit order, the chip order and the inversion applied.
+    ///
+    /// Each transfer is a single read, so the sampling mode is not applied.
+    /// The inputs changed since the previous update are returned as
+    /// described by `changes()`.
+    pub fn finish(self) -> Snapshot<CHAIN_LENGTH> {
+        let chain = self.chain;
+        let mut data = chain.dma_buffer;
+        chain.decode(&mut data);
+        chain.data_buffer = data;
+        chain.complete_update();
+
+        chain.changes
+    }
+}
+
+#[cfg(feature = "embedded-dma")]
+// SAFETY: The returned pointer and length refer to the DMA buffer of the
+// chain, which is mutably borrowed for the lifetime of the transfer and
+// valid for every byte pattern.
+unsafe impl<Transport, const CHAIN_LENGTH: usize> embedded_dma::WriteTarget
+    for DmaTransfer<'_, Transport, CHAIN_LENGTH>
+{
+    type Word = u8;
+
+    /// Returns the DMA buffer decoded by `finish()`.
+    ///
+    /// The bytes are expected in the order they are shifted in, MSB first.
+    /// Toggling the load signal before the transfer is up to the caller.
+    fn as_write_buffer(&mut self) -> (*mut u8, usize) {
+        (self.chain.dma_buffer.as_mut_ptr(), CHAIN_LENGTH)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Transport shifting the data in by bit-banging pins.
+///
+/// Each bit is sampled before the active clock edge, so the first bit is
+/// read from the output presented by the chips right after loading (QH of
+/// the 74HC165) before any clock edge.
+///
+/// An optional `Delay` inserts a settle time around each signal change,
+/// e.g. for long cables, optocouplers or level shifters. The type of the
+/// optional clock inhibit pin defaults to the type of the clock pin.
+pub struct BitBangTransport<ClockPin, LatchPin, DataPin, Delay = NoDelay, InhibitPin = ClockPin> {
+    /// Pin for the clock output signal.
+    clock_pin: ClockPin,
+
+    /// Pin for the latch output signal.
+    latch_pin: LatchPin,
+
+    /// Pin for the data input signal.
+    data_pin: DataPin,
+
+    /// Clock configuration.
+    clock: ClockConfig,
+
+    /// Polarity of the load signal.
+    latch_polarity: LatchPolarity,
+
+    /// Settle time of the signals.
+    settle: Settle<Delay>,
+
+    /// Pin for the clock inhibit output signal.
+    inhibit_pin: Option<InhibitPin>,
+}
+
+impl<ClockPin, LatchPin, DataPin> BitBangTransport<ClockPin, LatchPin, DataPin>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: InputPin,
+{
+    /// Creates a new transport by consuming the pins.
+    pub fn new(clock_pin: ClockPin, latch_pin: LatchPin, data_pin: DataPin) -> Self {
+        Self::with_delay(clock_pin, latch_pin, data_pin, NoDelay, 0)
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay> BitBangTransport<ClockPin, LatchPin, DataPin, Delay>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: InputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new transport by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_pin: DataPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self {
+            clock_pin,
+            latch_pin,
+            data_pin,
+            clock: ClockConfig::default(),
+            latch_polarity: LatchPolarity::Normal,
+            settle: Settle::new(delay, settle_ns),
+            inhibit_pin: None,
+        }
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay, InhibitPin>
+    BitBangTransport<ClockPin, LatchPin, DataPin, Delay, InhibitPin>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: InputPin,
+    Delay: DelayNs,
+    InhibitPin: OutputPin,
+{
+    /// Attaches a pin driving the clock inhibit input (CLK INH) of the chips,
+    /// replacing any attached before.
+    ///
+    /// The pin is driven low while shifting and high otherwise, so the clock
+    /// line can be shared with other devices. It must share the error type
+    /// of the other pins.
+    pub fn with_inhibit_pin<NewInhibitPin>(
+        self,
+        inhibit_pin: NewInhibitPin,
+    ) -> BitBangTransport<ClockPin, LatchPin, DataPin, Delay, NewInhibitPin>
+    where
+        NewInhibitPin: OutputPin,
+    {
+        BitBangTransport {
+            clock_pin: self.clock_pin,
+            latch_pin: self.latch_pin,
+            data_pin: self.data_pin,
+            clock: self.clock,
+            latch_polarity: self.latch_polarity,
+            settle: self.settle,
+            inhibit_pin: Some(inhibit_pin),
+        }
+    }
+
+    /// Detaches the clock inhibit pin and returns it.
+    pub fn take_inhibit_pin(&mut self) -> Option<InhibitPin> {
+        self.inhibit_pin.take()
+    }
+
+    /// Frees the transport and returns the pins.
+    ///
+    /// The clock inhibit pin is dropped, use `take_inhibit_pin()` before
+    /// to get it back.
+    pub fn free(self) -> (ClockPin, LatchPin, DataPin) {
+        (self.clock_pin, self.latch_pin, self.data_pin)
+    }
+
+    /// Frees the transport and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, LatchPin, DataPin, Delay) {
+        (
+            self.clock_pin,
+            self.latch_pin,
+            self.data_pin,
+            self.settle.into_delay(),
+        )
+    }
+
+    /// Returns the clock configuration.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.clock
+    }
+
+    /// Sets the clock configuration.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.clock = clock;
+    }
+
+    /// Returns the polarity of the load signal.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.latch_polarity
+    }
+
+    /// Sets the polarity of the load signal.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.latch_polarity = polarity;
+    }
+
+    /// Returns the settle time in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.settle.ns()
+    }
+
+    /// Sets the settle time in nanoseconds, 0 disables the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.settle.set_ns(settle_ns);
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay, InhibitPin> ShiftTransport
+    for BitBangTransport<ClockPin, LatchPin, DataPin, Delay, InhibitPin>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin<Error = ClockPin::Error>,
+    DataPin: InputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+    InhibitPin: OutputPin<Error = ClockPin::Error>,
+{
+    type Error = ClockPin::Error;
+
+    fn transfer(&mut self, read: &mut [u8], _write: &[u8]) -> Result<(), Self::Error> {
+        self.begin()?;
+
+        for data in read.iter_mut() {
+            let mut value: u8 = 0;
+
+            for bit in 0..=7 {
+                if self.shift_bit(false)? {
+                    value |= 1 << (7 - bit);
+                } else {
+                    value &= !(1 << (7 - bit));
+                }
+            }
+
+            *data = value;
+        }
+
+        self.end()
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay, InhibitPin> StepTransport
+    for BitBangTransport<ClockPin, LatchPin, DataPin, Delay, InhibitPin>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin<Error = ClockPin::Error>,
+    DataPin: InputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+    InhibitPin: OutputPin<Error = ClockPin::Error>,
+{
+    type Error = ClockPin::Error;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.clock.idle(&mut self.clock_pin)?;
+        self.latch_polarity.set(&mut self.latch_pin, true)?;
+
+        if let Some(inhibit_pin) = &mut self.inhibit_pin {
+            inhibit_pin.set_low()?;
+        }
+
+        self.settle.wait();
+
+        Ok(())
+    }
+
+    fn shift_bit(&mut self, _state: bool) -> Result<bool, Self::Error> {
+        self.clock.before_edge(&mut self.clock_pin)?;
+
+        let state = self.data_pin.is_high()?;
+
+        self.settle.wait();
+        self.clock.edge(&mut self.clock_pin)?;
+        self.settle.wait();
+        self.clock.after_edge(&mut self.clock_pin)?;
+
+        Ok(state)
+    }
+
+    fn end(&mut self) -> Result<(), Self::Error> {
+        if let Some(inhibit_pin) = &mut self.inhibit_pin {
+            inhibit_pin.set_high()?;
+        }
+
+        self.latch_polarity.set(&mut self.latch_pin, false)
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
-/// Chain of PISO shift registers.
-pub struct Chain<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize> {
+/// Transport shifting the data in by bit-banging pins from chips with a
+/// storage register, e.g. the 74HC597.
+///
+/// Each transfer captures the inputs into the storage registers with a
+/// rising edge on the latch pin (RCK), copies them into the shift registers
+/// with a pulse on the load pin (/SLOAD) and then shifts them in.
+pub struct BitBangStorageTransport<ClockPin, LatchPin, LoadPin, DataPin, Delay = NoDelay> {
     /// Pin for the clock output signal.
     clock_pin: ClockPin,
 
     /// Pin for the latch output signal.
     latch_pin: LatchPin,
 
+    /// Pin for the load output signal.
+    load_pin: LoadPin,
+
     /// Pin for the data input signal.
     data_pin: DataPin,
 
-    /// Buffer storing the data read from pins.
-    data_buffer: [u8; CHAIN_LENGTH],
+    /// Clock configuration.
+    clock: ClockConfig,
+
+    /// Polarity of the latch signal.
+    latch_polarity: LatchPolarity,
+
+    /// Polarity of the load signal.
+    load_polarity: LatchPolarity,
+
+    /// Settle time of the signals.
+    settle: Settle<Delay>,
 }
 
-impl<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize>
-    Chain<ClockPin, LatchPin, DataPin, CHAIN_LENGTH>
+impl<ClockPin, LatchPin, LoadPin, DataPin>
+    BitBangStorageTransport<ClockPin, LatchPin, LoadPin, DataPin>
 where
     ClockPin: OutputPin,
     LatchPin: OutputPin,
+    LoadPin: OutputPin,
     DataPin: InputPin,
 {
-    /// Creates a new chain by consuming the pins.
-    pub fn new(clock_pin: ClockPin, latch_pin: LatchPin, data_pin: DataPin) -> Self {
+    /// Creates a new transport by consuming the pins.
+    pub fn new(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        load_pin: LoadPin,
+        data_pin: DataPin,
+    ) -> Self {
+        Self::with_delay(clock_pin, latch_pin, load_pin, data_pin, NoDelay, 0)
+    }
+}
+
+impl<ClockPin, LatchPin, LoadPin, DataPin, Delay>
+    BitBangStorageTransport<ClockPin, LatchPin, LoadPin, DataPin, Delay>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    LoadPin: OutputPin,
+    DataPin: InputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new transport by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        load_pin: LoadPin,
+        data_pin: DataPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
         Self {
             clock_pin,
             latch_pin,
+            load_pin,
             data_pin,
-            data_buffer: [0; CHAIN_LENGTH],
+            clock: ClockConfig::default(),
+            latch_polarity: LatchPolarity::Normal,
+            load_polarity: LatchPolarity::Normal,
+            settle: Settle::new(delay, settle_ns),
         }
     }
 
-    /// Frees the chain and returns the pins.
-    pub fn free(self) -> (ClockPin, LatchPin, DataPin) {
-        (self.clock_pin, self.latch_pin, self.data_pin)
+    /// Frees the transport and returns the pins.
+    pub fn free(self) -> (ClockPin, LatchPin, LoadPin, DataPin) {
+        (self.clock_pin, self.latch_pin, self.load_pin, self.data_pin)
     }
 
-    /// Updates the chain by shifting the data from the chips into the buffer.
-    pub fn update(&mut self) {
-        self.latch_pin.set_high().ok();
+    /// Frees the transport and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, LatchPin, LoadPin, DataPin, Delay) {
+        (
+            self.clock_pin,
+            self.latch_pin,
+            self.load_pin,
+            self.data_pin,
+            self.settle.into_delay(),
+        )
+    }
+
+    /// Returns the clock configuration.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.clock
+    }
+
+    /// Sets the clock configuration.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.clock = clock;
+    }
+
+    /// Returns the polarity of the latch signal.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.latch_polarity
+    }
+
+    /// Sets the polarity of the latch signal.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.latch_polarity = polarity;
+    }
+
+    /// Returns the polarity of the load signal.
+    pub fn load_polarity(&self) -> LatchPolarity {
+        self.load_polarity
+    }
+
+    /// Sets the polarity of the load signal.
+    pub fn set_load_polarity(&mut self, polarity: LatchPolarity) {
+        self.load_polarity = polarity;
+    }
+
+    /// Returns the settle time in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.settle.ns()
+    }
+
+    /// Sets the settle time in nanoseconds, 0 disables the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.settle.set_ns(settle_ns);
+    }
+}
+
+impl<ClockPin, LatchPin, LoadPin, DataPin, Delay> ShiftTransport
+    for BitBangStorageTransport<ClockPin, LatchPin, LoadPin, DataPin, Delay>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin<Error = ClockPin::Error>,
+    LoadPin: OutputPin<Error = ClockPin::Error>,
+    DataPin: InputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+{
+    type Error = ClockPin::Error;
 
-        for data in self.data_buffer.iter_mut() {
+    fn transfer(&mut self, read: &mut [u8], _write: &[u8]) -> Result<(), Self::Error> {
+        self.begin()?;
+
+        for data in read.iter_mut() {
             let mut value: u8 = 0;
 
             for bit in 0..=7 {
-                self.clock_pin.set_low().ok();
-
-                if self.data_pin.is_high().ok().unwrap() {
+                if self.shift_bit(false)? {
                     value |= 1 << (7 - bit);
-                } else {
-                    value &= !(1 << (7 - bit));
                 }
-
-                self.clock_pin.set_high().ok();
             }
 
             *data = value;
         }
 
-        self.latch_pin.set_low().ok();
+        self.end()
     }
 }
 
-impl<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize> GetInput
-    for Chain<ClockPin, LatchPin, DataPin, CHAIN_LENGTH>
+impl<ClockPin, LatchPin, LoadPin, DataPin, Delay> StepTransport
+    for BitBangStorageTransport<ClockPin, LatchPin, LoadPin, DataPin, Delay>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin<Error = ClockPin::Error>,
+    LoadPin: OutputPin<Error = ClockPin::Error>,
+    DataPin: InputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
 {
-    /// Returns the input state for a pin.
+    type Error = ClockPin::Error;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.clock.idle(&mut self.clock_pin)?;
+
+        // Capture the inputs into the storage registers
+        self.latch_polarity.set(&mut self.latch_pin, false)?;
+        self.settle.wait();
+        self.latch_polarity.set(&mut self.latch_pin, true)?;
+        self.settle.wait();
+
+        // Copy the storage registers into the shift registers
+        self.load_polarity.set(&mut self.load_pin, false)?;
+        self.settle.wait();
+        self.load_polarity.set(&mut self.load_pin, true)?;
+        self.settle.wait();
+
+        Ok(())
+    }
+
+    fn shift_bit(&mut self, _state: bool) -> Result<bool, Self::Error> {
+        self.clock.before_edge(&mut self.clock_pin)?;
+
+        let state = self.data_pin.is_high()?;
+
+        self.settle.wait();
+        self.clock.edge(&mut self.clock_pin)?;
+        self.settle.wait();
+        self.clock.after_edge(&mut self.clock_pin)?;
+
+        Ok(state)
+    }
+
+    fn end(&mut self) -> Result<(), Self::Error> {
+        self.latch_polarity.set(&mut self.latch_pin, false)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Transport reading the data by a hardware SPI bus.
+///
+/// The bus must be configured for SPI mode 0 with MSB first. Clock and data
+/// are connected to SCK and MISO, the load signal is driven by a separate pin.
+/// The type of the optional clock inhibit pin defaults to the type of the
+/// load pin.
+pub struct SpiTransport<Spi, LoadPin, InhibitPin = LoadPin> {
+    /// SPI bus for the clock output and data input signals.
+    spi: Spi,
+
+    /// Pin for the load output signal.
+    load_pin: LoadPin,
+
+    /// Polarity of the load signal.
+    latch_polarity: LatchPolarity,
+
+    /// Pin for the clock inhibit output signal.
+    inhibit_pin: Option<InhibitPin>,
+}
+
+impl<Spi, LoadPin> SpiTransport<Spi, LoadPin> {
+    /// Creates a new transport by consuming the SPI bus and the load pin.
+    pub fn new(spi: Spi, load_pin: LoadPin) -> Self {
+        Self {
+            spi,
+            load_pin,
+            latch_polarity: LatchPolarity::Normal,
+            inhibit_pin: None,
+        }
+    }
+}
+
+impl<Spi, LoadPin, InhibitPin> SpiTransport<Spi, LoadPin, InhibitPin> {
+    /// Attaches a pin driving the clock inhibit input (CLK INH) of the chips,
+    /// replacing any attached before.
     ///
-    /// The state is buffered and not read immediately because the bits
-    /// have to be shifted in by calling `update()` first.
-    fn get_input(&self, pin: usize) -> Result<bool, Error> {
-        if pin >= CHAIN_LENGTH * 8 {
-            return Err(Error::PinOutOfRange);
+    /// The pin is driven low while reading and high otherwise, so the bus
+    /// can be shared with other devices. It must share the error type of
+    /// the load pin.
+    pub fn with_inhibit_pin<NewInhibitPin>(
+        self,
+        inhibit_pin: NewInhibitPin,
+    ) -> SpiTransport<Spi, LoadPin, NewInhibitPin> {
+        SpiTransport {
+            spi: self.spi,
+            load_pin: self.load_pin,
+            latch_polarity: self.latch_polarity,
+            inhibit_pin: Some(inhibit_pin),
         }
+    }
 
-        Ok(self.get_input_unchecked(pin))
+    /// Detaches the clock inhibit pin and returns it.
+    pub fn take_inhibit_pin(&mut self) -> Option<InhibitPin> {
+        self.inhibit_pin.take()
     }
 
-    /// Returns the input state for a pin without pin boundary checks.
+    /// Frees the transport and returns the SPI bus and the load pin.
     ///
-    /// The state is buffered and not read immediately because the bits
-    /// have to be shifted in by calling `update()` first.
-    fn get_input_unchecked(&self, pin: usize) -> bool {
-        // Calculate index and bit position within buffer array
-        let index = pin / 8;
-        let bit = pin % 8;
+    /// The clock inhibit pin is dropped, use `take_inhibit_pin()` before
+    /// to get it back.
+    pub fn free(self) -> (Spi, LoadPin) {
+        (self.spi, self.load_pin)
+    }
 
-        (self.data_buffer[index] & (1 << bit)) != 0
+    /// Returns the polarity of the load signal.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.latch_polarity
+    }
+
+    /// Sets the polarity of the load signal.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.latch_polarity = polarity;
     }
 }
 
-impl<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize> Length
-    for Chain<ClockPin, LatchPin, DataPin, CHAIN_LENGTH>
+impl<Spi, LoadPin, InhibitPin> SpiTransport<Spi, LoadPin, InhibitPin>
+where
+    LoadPin: OutputPin,
+    InhibitPin: OutputPin<Error = LoadPin::Error>,
 {
-    /// Returns the chain length.
-    fn len(&self) -> usize {
-        CHAIN_LENGTH
+    /// Sets the clock inhibit pin, if attached.
+    fn set_inhibit(&mut self, inhibit: bool) -> Result<(), LoadPin::Error> {
+        match &mut self.inhibit_pin {
+            Some(inhibit_pin) if inhibit => inhibit_pin.set_high(),
+            Some(inhibit_pin) => inhibit_pin.set_low(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<Spi, LoadPin, InhibitPin> ShiftTransport for SpiTransport<Spi, LoadPin, InhibitPin>
+where
+    Spi: SpiBus,
+    LoadPin: OutputPin,
+    InhibitPin: OutputPin<Error = LoadPin::Error>,
+{
+    type Error = SpiError<Spi::Error, LoadPin::Error>;
+
+    fn transfer(&mut self, read: &mut [u8], _write: &[u8]) -> Result<(), Self::Error> {
+        self.latch_polarity
+            .set(&mut self.load_pin, true)
+            .map_err(SpiError::Pin)?;
+        self.set_inhibit(false).map_err(SpiError::Pin)?;
+
+        self.spi.read(read).map_err(SpiError::Spi)?;
+        self.spi.flush().map_err(SpiError::Spi)?;
+
+        self.set_inhibit(true).map_err(SpiError::Pin)?;
+        self.latch_polarity
+            .set(&mut self.load_pin, false)
+            .map_err(SpiError::Pin)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Spi, LoadPin, InhibitPin> AsyncShiftTransport for SpiTransport<Spi, LoadPin, InhibitPin>
+where
+    Spi: embedded_hal_async::spi::SpiBus,
+    LoadPin: OutputPin,
+    InhibitPin: OutputPin<Error = LoadPin::Error>,
+{
+    type Error = SpiError<Spi::Error, LoadPin::Error>;
+
+    /// Reads the data in a single asynchronous transfer.
+    ///
+    /// The load signal is toggled after the transfer has completed.
+    async fn transfer(&mut self, read: &mut [u8], _write: &[u8]) -> Result<(), Self::Error> {
+        self.latch_polarity
+            .set(&mut self.load_pin, true)
+            .map_err(SpiError::Pin)?;
+        self.set_inhibit(false).map_err(SpiError::Pin)?;
+
+        self.spi.read(read).await.map_err(SpiError::Spi)?;
+        self.spi.flush().await.map_err(SpiError::Spi)?;
+
+        self.set_inhibit(true).map_err(SpiError::Pin)?;
+        self.latch_polarity
+            .set(&mut self.load_pin, false)
+            .map_err(SpiError::Pin)
     }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Input pin of a chip in the chain.
+#[cfg_attr(not(any(feature = "eh1", feature = "eh02")), allow(dead_code))]
 pub struct Pin<'a, Chain> {
     /// Reference to the chain.
     chain: &'a RefCell<Chain>,
@@ -136,7 +1963,7 @@ where
 {
     /// Creates a new input pin.
     pub fn new(chain: &'a RefCell<Chain>, pin: usize) -> Result<Self, Error> {
-        if pin >= chain.borrow().len() * 8 {
+        if pin >= chain.borrow().input_len() * 8 {
             return Err(Error::PinOutOfRange);
         }
 
@@ -144,10 +1971,12 @@ where
     }
 }
 
-impl<Chain> ErrorType for Pin<'_, Chain> {
-    type Error = core::convert::Infallible;
+#[cfg(feature = "eh1")]
+impl<Chain> embedded_hal::digital::ErrorType for Pin<'_, Chain> {
+    type Error = Infallible;
 }
 
+#[cfg(feature = "eh1")]
 impl<Chain> InputPin for Pin<'_, Chain>
 where
     Chain: GetInput,
@@ -160,3 +1989,201 @@ where
         Ok(!self.chain.borrow().get_input_unchecked(self.pin))
     }
 }
+
+#[cfg(feature = "eh02")]
+impl<Chain> embedded_hal_02::digital::v2::InputPin for Pin<'_, Chain>
+where
+    Chain: GetInput,
+{
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.chain.borrow().get_input_unchecked(self.pin))
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.chain.borrow().get_input_unchecked(self.pin))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Chain> Pin<'_, Chain>
+where
+    Chain: GetInput + WakeOnUpdate,
+{
+    /// Waits until `condition` returns `true` for the previous and the
+    /// current state of the pin, checked after each update of the chain.
+    async fn wait_for(&mut self, condition: impl Fn(bool, bool) -> bool) {
+        let mut previous = self.chain.borrow().get_input_unchecked(self.pin);
+        let mut registered = false;
+
+        core::future::poll_fn(|cx| {
+            let mut chain = self.chain.borrow_mut();
+
+            if registered {
+                let state = chain.get_input_unchecked(self.pin);
+
+                if condition(previous, state) {
+                    return Poll::Ready(());
+                }
+
+                previous = state;
+            }
+
+            chain.register_waker(cx.waker());
+            registered = true;
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Chain> embedded_hal_async::digital::Wait for Pin<'_, Chain>
+where
+    Chain: GetInput + WakeOnUpdate,
+{
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        if !self.chain.borrow().get_input_unchecked(self.pin) {
+            self.wait_for(|_, state| state).await;
+        }
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        if self.chain.borrow().get_input_unchecked(self.pin) {
+            self.wait_for(|_, state| !state).await;
+        }
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(|previous, state| !previous && state).await;
+        Ok(())
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(|previous, state| previous && !state).await;
+        Ok(())
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(|previous, state| previous != state).await;
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+impl<Transport, const CHAIN_LENGTH: usize> Chain<Transport, CHAIN_LENGTH> {
+    /// Returns a mutable reference to the transport.
+    pub(crate) fn transport_mut(&mut self) -> &mut Transport {
+        &mut self.transport
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Fault, Recorder};
+
+    /// Returns a chain with all wire encoding options changed.
+    fn encoded_chain() -> Chain<Recorder, 4> {
+        let mut chain = Chain::with_transport(Recorder::default());
+        chain.set_bit_order(BitOrder::LsbFirst);
+        chain.set_chip_order(ChipOrder::FarthestFirst);
+        assert_eq!(chain.set_register_bits(16), Ok(()));
+        assert_eq!(chain.set_inverted_input(3, true), Ok(()));
+        chain.transport.read[..4].copy_from_slice(&[0x12, 0x34, 0x00, 0xA5]);
+        chain
+    }
+
+    #[test]
+    fn update_reads_nearest_chip_first() {
+        let mut chain = Chain::<_, 2>::with_transport(Recorder::default());
+        chain.transport.read[..2].copy_from_slice(&[0x12, 0x34]);
+
+        assert!(chain.update().is_ok());
+        assert_eq!(chain.get_input_word::<u16>(0), Ok(0x3412));
+        assert_eq!(chain.transport.transfers, 1);
+    }
+
+    #[test]
+    fn update_is_wire_decoded() {
+        let mut chain = encoded_chain();
+
+        assert!(chain.update().is_ok());
+        assert_eq!(chain.get_input_word::<u32>(0), Ok(0x482C_00AD));
+    }
+
+    #[test]
+    fn poll_update_resumes() {
+        let mut chain = encoded_chain();
+        let mut calls = 1;
+
+        while chain.poll_update(5) == Err(nb::Error::WouldBlock) {
+            calls += 1;
+        }
+
+        assert_eq!(calls, 7);
+        assert_eq!(chain.get_input_word::<u32>(0), Ok(0x482C_00AD));
+        assert_eq!(chain.transport.transfers, 1);
+    }
+
+    #[test]
+    fn poll_update_keeps_inputs_until_completed() {
+        let mut chain = encoded_chain();
+        assert!(chain.update().is_ok());
+
+        chain.transport.read[..4].copy_from_slice(&[0xFF; 4]);
+        assert_eq!(chain.poll_update(20), Err(nb::Error::WouldBlock));
+        assert_eq!(chain.get_input_word::<u32>(0), Ok(0x482C_00AD));
+
+        chain.transport.fail = true;
+        assert_eq!(
+            chain.poll_update(8),
+            Err(nb::Error::Other(Error::Transport(Fault)))
+        );
+        assert_eq!(chain.get_input_word::<u32>(0), Ok(0x482C_00AD));
+
+        chain.transport.fail = false;
+        assert_eq!(chain.poll_update(32).map(|_| ()), Ok(()));
+        assert_eq!(chain.get_input_word::<u32>(0), Ok(0xFFFF_FFF7));
+    }
+
+    #[test]
+    fn poll_update_rejects_several_reads() {
+        let mut chain = encoded_chain();
+        chain.set_sampling(Sampling::Verify);
+
+        assert_eq!(
+            chain.poll_update(32),
+            Err(nb::Error::Other(Error::Unsupported))
+        );
+        assert_eq!(chain.transport.transfers, 0);
+    }
+
+    #[cfg(feature = "embedded-dma")]
+    #[test]
+    fn dma_buffer_is_decoded() {
+        use embedded_dma::WriteTarget;
+
+        let mut expected = encoded_chain();
+        let changes = expected.update();
+
+        let mut chain = encoded_chain();
+        let read = chain.transport.read;
+        let mut transfer = chain.prepare_dma();
+        let (ptr, len) = transfer.as_write_buffer();
+        // SAFETY: The buffer is borrowed by the transfer and not accessed
+        // otherwise while the slice is alive.
+        let data = unsafe { core::slice::from_raw_parts_mut(ptr, len) };
+        data.copy_from_slice(&read[..len]);
+
+        assert_eq!(Ok(transfer.finish()), changes);
+        assert_eq!(chain.get_input_word::<u32>(0), expected.get_input_word(0));
+        assert_eq!(chain.age(), Some(0));
+    }
+}
@@ -1,16 +1,108 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
 #![warn(missing_docs)]
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
 
+pub mod bargraph;
+pub mod builder;
+pub mod clock;
+pub mod compose;
+pub mod counter;
+pub mod debounce;
+pub mod display;
+pub mod encoder;
+pub mod event;
+pub mod expander;
+pub mod gate;
 pub mod inout;
 pub mod input;
+pub mod matrix;
+#[cfg(feature = "portable-atomic")]
+pub mod mirror;
 pub mod output;
+pub mod pwm;
+pub mod remap;
+pub mod runner;
+pub mod sequence;
+pub mod subchain;
+#[cfg(feature = "switch-hal")]
+pub mod switch;
+pub mod transport;
+
+mod age;
+#[cfg(test)]
+mod mock;
+mod pulse;
+#[cfg(feature = "async")]
+mod waker;
+
+use core::{convert::Infallible, fmt};
 
 /// Errors
-#[derive(Debug)]
-pub enum Error {
+///
+/// `E` is the error type of the transport, which is `Infallible` for errors
+/// not related to a transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Error<E = Infallible> {
     /// Pin number not within the allowed range.
     PinOutOfRange,
+
+    /// Range of pins or values not valid for the operation.
+    InvalidRange,
+
+    /// Buffer too small to hold the data.
+    BufferTooSmall,
+
+    /// Data not updated since the chain was created.
+    NotUpdated,
+
+    /// Repeated reads of the inputs did not match.
+    Mismatch,
+
+    /// Loopback check detected a broken chain.
+    ChainFault,
+
+    /// Data older than the configured maximum age.
+    Stale,
+
+    /// Operation not supported with the current configuration.
+    Unsupported,
+
+    /// Transport failed to shift the data, e.g. due to a pin or bus error.
+    Transport(E),
+}
+
+impl<E> fmt::Display for Error<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PinOutOfRange => f.write_str("pin number out of range"),
+            Self::InvalidRange => f.write_str("invalid range"),
+            Self::BufferTooSmall => f.write_str("buffer too small"),
+            Self::NotUpdated => f.write_str("chain not updated"),
+            Self::Mismatch => f.write_str("input reads mismatch"),
+            Self::ChainFault => f.write_str("chain fault"),
+            Self::Stale => f.write_str("stale data"),
+            Self::Unsupported => f.write_str("unsupported operation"),
+            Self::Transport(error) => write!(f, "transport error: {error}"),
+        }
+    }
+}
+
+impl<E> core::error::Error for Error<E>
+where
+    E: core::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Transport(error) => Some(error),
+            _ => None,
+        }
+    }
 }
 
 /// Trait to be implemented by any chain to return its length.
@@ -22,4 +114,139 @@ pub trait Length {
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Returns the number of chips providing input pins, `len()` by default.
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+
+    /// Returns the number of chips providing output pins, `len()` by default.
+    fn output_len(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Trait to be implemented by any chain to shift the data from and into the chips.
+pub trait Update {
+    /// Error type of the update.
+    type Error;
+
+    /// Updates the chain.
+    fn update(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Asynchronous variant of [`Update`].
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncUpdate {
+    /// Error type of the update.
+    type Error;
+
+    /// Updates the chain asynchronously.
+    async fn update_async(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Order in which the bits of each chip are shifted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BitOrder {
+    /// Pin 7 of each chip is shifted first, as wired for the 74HC165 (D7)
+    /// and the 74HC595 (QH).
+    #[default]
+    MsbFirst,
+
+    /// Pin 0 of each chip is shifted first, e.g. for boards wired in the
+    /// opposite order.
+    LsbFirst,
+}
+
+impl BitOrder {
+    /// Returns the byte and the bit within the byte that is shifted at
+    /// `position`, for registers of `register_bytes` bytes each.
+    pub(crate) fn locate(self, position: usize, register_bytes: usize) -> (usize, usize) {
+        match self {
+            Self::MsbFirst => (position / 8, 7 - position % 8),
+            Self::LsbFirst => {
+                let register = position / (register_bytes * 8);
+                let offset = position % (register_bytes * 8);
+
+                (
+                    register * register_bytes + register_bytes - 1 - offset / 8,
+                    offset % 8,
+                )
+            }
+        }
+    }
+
+    /// Converts bytes between pin order and wire order, which is the same
+    /// in both directions.
+    pub(crate) fn reorder(self, data: &mut [u8]) {
+        if self == Self::LsbFirst {
+            for byte in data {
+                *byte = byte.reverse_bits();
+            }
+        }
+    }
+
+    /// Converts bytes between pin order and wire order for registers of
+    /// `register_bytes` bytes each, with the byte of the highest pins first.
+    pub(crate) fn reorder_registers(self, data: &mut [u8], register_bytes: usize) {
+        if self == Self::LsbFirst {
+            for register in data.chunks_mut(register_bytes) {
+                register.reverse();
+            }
+        }
+
+        self.reorder(data);
+    }
+}
+
+/// Order in which the chips are numbered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChipOrder {
+    /// Pin 0 is on the chip nearest to the controller, in both directions.
+    #[default]
+    NearestFirst,
+
+    /// Pin 0 is on the chip at the far end of the chain, e.g. for boards
+    /// stacked in the opposite order.
+    FarthestFirst,
+}
+
+impl ChipOrder {
+    /// Returns the buffer index of the byte at wire index `index`, for
+    /// registers of `register_bytes` bytes each.
+    pub(crate) fn locate(self, index: usize, chain_length: usize, register_bytes: usize) -> usize {
+        match self {
+            Self::NearestFirst => index,
+            Self::FarthestFirst => {
+                let registers = chain_length / register_bytes;
+
+                (registers - 1 - index / register_bytes) * register_bytes + index % register_bytes
+            }
+        }
+    }
+
+    /// Converts bytes between pin order and wire order for registers of
+    /// `register_bytes` bytes each, which is the same in both directions.
+    pub(crate) fn reorder(self, data: &mut [u8], register_bytes: usize) {
+        if self == Self::FarthestFirst {
+            data.reverse();
+
+            for register in data.chunks_mut(register_bytes) {
+                register.reverse();
+            }
+        }
+    }
+}
+
+/// Returns the number of bytes of a register with `bits` bits, `None` if the
+/// width is not a multiple of 8 or the chain length in bytes is not a
+/// multiple of the register width.
+pub(crate) fn register_bytes(bits: usize, chain_length: usize) -> Option<usize> {
+    let bytes = bits / 8;
+
+    (bytes > 0 && bytes * 8 == bits && chain_length / bytes * bytes == chain_length)
+        .then_some(bytes)
 }
@@ -0,0 +1,554 @@
+//! Scanning of key matrices
+//!
+//! A [`Matrix`] drives the row lines of a key matrix via output pins and
+//! reads the column lines via input pins, one row per update of the chains.
+//! The rows are driven and read in a pipeline, so the sequencing works with
+//! separate output and input chains as well as with dual chains.
+//!
+//! For matrices without diodes, ghost keys can be detected and masked, and
+//! the number of pressed keys can be limited to a rollover.
+
+use crate::{
+    event::{Edge, Event},
+    input::GetInput,
+    output::SetOutput,
+    Error,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Key matrix with `ROWS` row lines and `COLUMNS` column lines.
+///
+/// Keys are numbered row by row, so key `row * COLUMNS + column` is at the
+/// crossing of the row and column lines. By default, the driven row is set
+/// high and the others low, and a pressed key reads high on its column.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Matrix<const ROWS: usize, const COLUMNS: usize> {
+    /// Output pins driving the rows.
+    row_pins: [usize; ROWS],
+
+    /// Input pins reading the columns.
+    column_pins: [usize; COLUMNS],
+
+    /// Whether the driven row is set low and pressed keys read low.
+    active_low: bool,
+
+    /// Number of updates between driving a row and reading its columns.
+    latency: usize,
+
+    /// Row to be driven by the next step.
+    row: usize,
+
+    /// Steps done since the start of the pipeline, up to the latency.
+    steps: usize,
+
+    /// States of the keys as read.
+    raw_states: [[bool; COLUMNS]; ROWS],
+
+    /// States of the keys as reported.
+    states: [[bool; COLUMNS]; ROWS],
+
+    /// Whether ghosted keys keep their reported state.
+    mask_ghosts: bool,
+
+    /// Maximum number of pressed keys reported, `None` if unlimited.
+    rollover: Option<usize>,
+}
+
+impl<const ROWS: usize, const COLUMNS: usize> Matrix<ROWS, COLUMNS> {
+    /// Creates a new matrix with the pins of the rows and the columns.
+    pub const fn new(row_pins: [usize; ROWS], column_pins: [usize; COLUMNS]) -> Self {
+        Self {
+            row_pins,
+            column_pins,
+            active_low: false,
+            latency: 1,
+            row: 0,
+            steps: 0,
+            raw_states: [[false; COLUMNS]; ROWS],
+            states: [[false; COLUMNS]; ROWS],
+            mask_ghosts: false,
+            rollover: None,
+        }
+    }
+
+    /// Returns `true` if the driven row is set low and pressed keys read low.
+    pub fn active_low(&self) -> bool {
+        self.active_low
+    }
+
+    /// Sets whether the driven row is set low and pressed keys read low,
+    /// e.g. for columns with pull-up resistors.
+    pub fn set_active_low(&mut self, active_low: bool) {
+        self.active_low = active_low;
+    }
+
+    /// Returns the number of updates between driving a row and reading its
+    /// columns.
+    pub fn latency(&self) -> usize {
+        self.latency
+    }
+
+    /// Sets the number of updates between driving a row and reading its
+    /// columns, 1 by default.
+    ///
+    /// Use 1 if the output chain is updated before the input chain and 2 for
+    /// dual chains, which latch the inputs before shifting the outputs.
+    /// Restarts the pipeline. Returns an error if `latency` is 0.
+    pub fn set_latency(&mut self, latency: usize) -> Result<(), Error> {
+        if latency == 0 {
+            return Err(Error::InvalidRange);
+        }
+
+        self.latency = latency;
+        self.steps = 0;
+
+        Ok(())
+    }
+
+    /// Returns `true` if ghosted keys keep their reported state.
+    pub fn mask_ghosts(&self) -> bool {
+        self.mask_ghosts
+    }
+
+    /// Sets whether ghosted keys keep their reported state until the
+    /// ghosting is resolved, disabled by default.
+    pub fn set_mask_ghosts(&mut self, mask_ghosts: bool) {
+        self.mask_ghosts = mask_ghosts;
+    }
+
+    /// Returns the maximum number of pressed keys reported, `None` if
+    /// unlimited.
+    pub fn rollover(&self) -> Option<usize> {
+        self.rollover
+    }
+
+    /// Sets the maximum number of pressed keys reported, e.g. 2 for a
+    /// matrix without diodes, `None` by default.
+    ///
+    /// Presses beyond the limit are ignored until they are read again after
+    /// other keys were released.
+    pub fn set_rollover(&mut self, rollover: Option<usize>) {
+        self.rollover = rollover;
+    }
+
+    /// Returns `true` if the read states of a key may be caused by ghosting,
+    /// `None` if out of range.
+    ///
+    /// Without diodes, pressing three corners of a rectangle of keys makes
+    /// the fourth read as pressed, so the read states of all four are
+    /// ambiguous.
+    pub fn is_ghosted(&self, row: usize, column: usize) -> Option<bool> {
+        let state = *self.raw_states.get(row)?.get(column)?;
+
+        Some(
+            state
+                && self
+                    .raw_states
+                    .iter()
+                    .enumerate()
+                    .any(|(other_row, states)| {
+                        other_row != row
+                            && states[column]
+                            && (0..COLUMNS).any(|other_column| {
+                                other_column != column
+                                    && states[other_column]
+                                    && self.raw_states[row][other_column]
+                            })
+                    }),
+        )
+    }
+
+    /// Returns `true` if any key may be ghosted.
+    pub fn has_ghosting(&self) -> bool {
+        (0..ROWS).any(|row| (0..COLUMNS).any(|column| self.is_ghosted(row, column) == Some(true)))
+    }
+
+    /// Returns the number of reported pressed keys.
+    pub fn pressed_count(&self) -> usize {
+        self.states.iter().flatten().filter(|&&state| state).count()
+    }
+
+    /// Returns the number of keys.
+    pub fn key_count(&self) -> usize {
+        ROWS * COLUMNS
+    }
+
+    /// Returns the state of a key or `None` if out of range.
+    pub fn is_pressed(&self, row: usize, column: usize) -> Option<bool> {
+        self.states.get(row)?.get(column).copied()
+    }
+
+    /// Returns the state of a key by its number or `None` if out of range.
+    pub fn is_key_pressed(&self, key: usize) -> Option<bool> {
+        match COLUMNS {
+            0 => None,
+            _ => self.is_pressed(key / COLUMNS, key % COLUMNS),
+        }
+    }
+
+    /// Reads the columns of the row driven `latency` updates ago and drives
+    /// the next row.
+    ///
+    /// Intended to be called after each update of the chains, before the
+    /// next one shifts the driven row out. Returns an iterator over the
+    /// events of the keys in the read row, which is empty while the pipeline
+    /// starts. Returns an error if a pin is out of range or the inputs are
+    /// not valid.
+    pub fn step<Outputs, Inputs>(
+        &mut self,
+        outputs: &mut Outputs,
+        inputs: &Inputs,
+    ) -> Result<RowEvents<COLUMNS>, Error>
+    where
+        Outputs: SetOutput + ?Sized,
+        Inputs: GetInput + ?Sized,
+    {
+        let mut events = RowEvents::new();
+
+        if ROWS == 0 {
+            return Ok(events);
+        }
+
+        if self.steps >= self.latency {
+            let row = (self.row + ROWS - self.latency % ROWS) % ROWS;
+
+            for (column, &pin) in self.column_pins.iter().enumerate() {
+                self.raw_states[row][column] = inputs.get_input(pin)? != self.active_low;
+            }
+
+            for column in 0..COLUMNS {
+                let previous = self.states[row][column];
+                let mut state = self.raw_states[row][column];
+
+                if self.mask_ghosts && self.is_ghosted(row, column) == Some(true) {
+                    state = previous;
+                }
+
+                if let Some(rollover) = self.rollover {
+                    if state && !previous && self.pressed_count() >= rollover {
+                        state = false;
+                    }
+                }
+
+                if state != previous {
+                    self.states[row][column] = state;
+                    events.changes[column] = true;
+                }
+
+                events.states[column] = state;
+            }
+
+            events.row = Some(row);
+        }
+
+        for (row, &pin) in self.row_pins.iter().enumerate() {
+            outputs.set_output(pin, (row == self.row) != self.active_low)?;
+        }
+
+        self.row = (self.row + 1) % ROWS;
+        self.steps = (self.steps + 1).min(self.latency);
+
+        Ok(events)
+    }
+
+    /// Releases all keys and restarts the pipeline.
+    pub fn reset(&mut self) {
+        self.row = 0;
+        self.steps = 0;
+        self.raw_states = [[false; COLUMNS]; ROWS];
+        self.states = [[false; COLUMNS]; ROWS];
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Iterator over the events of the keys in a row read by a step.
+///
+/// The pin of the events is the key number.
+#[derive(Debug, Clone)]
+pub struct RowEvents<const COLUMNS: usize> {
+    /// Row read by the step, `None` while the pipeline starts.
+    row: Option<usize>,
+
+    /// Keys of the row that changed and are not reported yet.
+    changes: [bool; COLUMNS],
+
+    /// States of the keys of the row.
+    states: [bool; COLUMNS],
+
+    /// Next column to be checked.
+    column: usize,
+}
+
+impl<const COLUMNS: usize> RowEvents<COLUMNS> {
+    /// Creates an empty iterator.
+    fn new() -> Self {
+        Self {
+            row: None,
+            changes: [false; COLUMNS],
+            states: [false; COLUMNS],
+            column: 0,
+        }
+    }
+
+    /// Returns the row read by the step, `None` while the pipeline starts.
+    pub fn row(&self) -> Option<usize> {
+        self.row
+    }
+}
+
+impl<const COLUMNS: usize> Iterator for RowEvents<COLUMNS> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        let row = self.row?;
+
+        while self.column < COLUMNS {
+            let column = self.column;
+            self.column += 1;
+
+            if self.changes[column] {
+                let edge = if self.states[column] {
+                    Edge::Pressed
+                } else {
+                    Edge::Released
+                };
+
+                return Some(Event {
+                    pin: row * COLUMNS + column,
+                    edge,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// Number of rows and columns of the simulated matrix.
+    const SIZE: usize = 3;
+
+    /// Row lines driven via output pins 0 to 2.
+    struct Rows([bool; SIZE]);
+
+    impl SetOutput for Rows {
+        fn set_output(&mut self, pin: usize, state: bool) -> Result<(), Error> {
+            *self.0.get_mut(pin).ok_or(Error::PinOutOfRange)? = state;
+
+            Ok(())
+        }
+
+        fn set_output_unchecked(&mut self, pin: usize, state: bool) {
+            self.0[pin] = state;
+        }
+    }
+
+    /// Column lines read via input pins 0 to 2.
+    struct Columns([bool; SIZE]);
+
+    impl GetInput for Columns {
+        fn get_input(&self, pin: usize) -> Result<bool, Error> {
+            self.0.get(pin).copied().ok_or(Error::PinOutOfRange)
+        }
+
+        fn get_input_unchecked(&self, pin: usize) -> bool {
+            self.0[pin]
+        }
+
+        fn read_inputs(&self, _data: &mut [u8]) -> Result<(), Error> {
+            Err(Error::InvalidRange)
+        }
+    }
+
+    /// Key matrix with diodes wired to chains.
+    struct Board {
+        /// Buffered row states.
+        rows: Rows,
+
+        /// Row states shifted into the chips.
+        driven: [bool; SIZE],
+
+        /// Column states read.
+        columns: Columns,
+
+        /// Pressed keys.
+        keys: [[bool; SIZE]; SIZE],
+
+        /// Whether rows are driven low and pressed keys read low.
+        active_low: bool,
+
+        /// Whether an update latches the inputs before shifting the outputs.
+        inputs_first: bool,
+    }
+
+    impl Board {
+        fn new(active_low: bool, inputs_first: bool) -> Self {
+            Self {
+                rows: Rows([active_low; SIZE]),
+                driven: [active_low; SIZE],
+                columns: Columns([active_low; SIZE]),
+                keys: [[false; SIZE]; SIZE],
+                active_low,
+                inputs_first,
+            }
+        }
+
+        /// Updates the chains and steps the matrix, returning the read row
+        /// and its events.
+        fn step(&mut self, matrix: &mut Matrix<SIZE, SIZE>) -> (Option<usize>, Vec<Event>) {
+            if self.inputs_first {
+                self.read();
+                self.driven = self.rows.0;
+            } else {
+                self.driven = self.rows.0;
+                self.read();
+            }
+
+            let events = matrix.step(&mut self.rows, &self.columns);
+            assert!(events.is_ok());
+
+            events
+                .map(|events| (events.row(), events.collect()))
+                .unwrap_or_default()
+        }
+
+        /// Reads the columns of the driven rows.
+        fn read(&mut self) {
+            for column in 0..SIZE {
+                let pressed = (0..SIZE)
+                    .any(|row| self.driven[row] != self.active_low && self.keys[row][column]);
+                self.columns.0[column] = pressed != self.active_low;
+            }
+        }
+
+        /// Steps the matrix for `steps` updates and returns all events.
+        fn scan(&mut self, matrix: &mut Matrix<SIZE, SIZE>, steps: usize) -> Vec<Event> {
+            (0..steps).flat_map(|_| self.step(matrix).1).collect()
+        }
+    }
+
+    /// Returns an event.
+    fn event(pin: usize, edge: Edge) -> Event {
+        Event { pin, edge }
+    }
+
+    #[test]
+    fn latency_one() {
+        let mut matrix = Matrix::new([0, 1, 2], [0, 1, 2]);
+        let mut board = Board::new(false, false);
+        board.keys[1][2] = true;
+
+        let rows = [(); 5].map(|_| board.step(&mut matrix));
+
+        assert_eq!(
+            rows.each_ref().map(|(row, _)| *row),
+            [None, Some(0), Some(1), Some(2), Some(0)]
+        );
+        assert_eq!(rows[2].1, [event(5, Edge::Pressed)]);
+        assert!(rows
+            .iter()
+            .enumerate()
+            .all(|(step, (_, events))| step == 2 || events.is_empty()));
+        assert_eq!(matrix.is_key_pressed(5), Some(true));
+        assert_eq!(board.rows.0, [false, true, false]);
+    }
+
+    #[test]
+    fn latency_two() {
+        let mut matrix = Matrix::new([0, 1, 2], [0, 1, 2]);
+        assert_eq!(matrix.set_latency(0), Err(Error::InvalidRange));
+        assert_eq!(matrix.set_latency(2), Ok(()));
+        let mut board = Board::new(false, true);
+        board.keys[2][0] = true;
+
+        let rows = [(); 6].map(|_| board.step(&mut matrix));
+
+        assert_eq!(
+            rows.each_ref().map(|(row, _)| *row),
+            [None, None, Some(0), Some(1), Some(2), Some(0)]
+        );
+        assert_eq!(rows[4].1, [event(6, Edge::Pressed)]);
+        assert_eq!(matrix.pressed_count(), 1);
+
+        board.keys[2][0] = false;
+        assert_eq!(board.scan(&mut matrix, 3), [event(6, Edge::Released)]);
+    }
+
+    #[test]
+    fn active_low() {
+        let mut matrix = Matrix::new([0, 1, 2], [0, 1, 2]);
+        matrix.set_active_low(true);
+        let mut board = Board::new(true, false);
+        board.keys[0][1] = true;
+        board.keys[2][2] = true;
+
+        let events = board.scan(&mut matrix, 4);
+
+        assert_eq!(events, [event(1, Edge::Pressed), event(8, Edge::Pressed)]);
+        assert_eq!(board.rows.0, [false, true, true]);
+        assert_eq!(matrix.is_pressed(0, 1), Some(true));
+        assert_eq!(matrix.is_pressed(0, 0), Some(false));
+    }
+
+    #[test]
+    fn ghosts_are_masked() {
+        let mut matrix = Matrix::new([0, 1, 2], [0, 1, 2]);
+        matrix.set_mask_ghosts(true);
+        let mut board = Board::new(false, false);
+        board.keys[0][0] = true;
+        board.keys[0][1] = true;
+        board.keys[1][0] = true;
+
+        assert_eq!(board.scan(&mut matrix, 4).len(), 3);
+        assert!(!matrix.has_ghosting());
+
+        // Without diodes, the fourth corner of the rectangle reads pressed.
+        board.keys[1][1] = true;
+        assert_eq!(board.scan(&mut matrix, 3), []);
+        assert_eq!(matrix.is_ghosted(1, 1), Some(true));
+        assert_eq!(matrix.is_ghosted(2, 2), Some(false));
+        assert_eq!(matrix.is_pressed(1, 1), Some(false));
+
+        board.keys[0][0] = false;
+        assert_eq!(
+            board.scan(&mut matrix, 3),
+            [event(0, Edge::Released), event(4, Edge::Pressed)]
+        );
+        assert!(!matrix.has_ghosting());
+    }
+
+    #[test]
+    fn rollover_limits_presses() {
+        let mut matrix = Matrix::new([0, 1, 2], [0, 1, 2]);
+        matrix.set_rollover(Some(2));
+        let mut board = Board::new(false, false);
+        board.keys[0][2] = true;
+        board.keys[1][1] = true;
+        board.keys[2][0] = true;
+
+        assert_eq!(
+            board.scan(&mut matrix, 4),
+            [event(2, Edge::Pressed), event(4, Edge::Pressed)]
+        );
+        assert_eq!(matrix.is_key_pressed(6), Some(false));
+
+        board.keys[0][2] = false;
+        assert_eq!(
+            board.scan(&mut matrix, 3),
+            [event(2, Edge::Released), event(6, Edge::Pressed)]
+        );
+        assert_eq!(matrix.pressed_count(), 2);
+    }
+}
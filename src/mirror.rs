@@ -0,0 +1,190 @@
+//! Lock-free mirror of the pin states
+//!
+//! A [`Mirror`] holds a copy of the pin states in atomics, so it can be
+//! shared between an interrupt updating the chain and the application
+//! without wrapping the chain into a `RefCell` or a mutex. The atomics are
+//! provided by the `portable-atomic` crate, so targets without native atomic
+//! operations like `thumbv6m` are supported as well.
+
+#[cfg(feature = "eh1")]
+use core::convert::Infallible;
+
+use portable_atomic::{AtomicU8, Ordering};
+
+use crate::{input::GetInput, output::SetOutput, Error, Length};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Atomic copy of the pin states of a chain with `CHAIN_LENGTH` chips.
+pub struct Mirror<const CHAIN_LENGTH: usize> {
+    /// Pin states, 8 pins per byte starting with pin 0 in byte 0.
+    data: [AtomicU8; CHAIN_LENGTH],
+}
+
+impl<const CHAIN_LENGTH: usize> Mirror<CHAIN_LENGTH> {
+    /// Creates a new mirror with all states low.
+    pub const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const LOW: AtomicU8 = AtomicU8::new(0);
+
+        Self {
+            data: [LOW; CHAIN_LENGTH],
+        }
+    }
+
+    /// Returns the number of pins.
+    pub const fn pin_count(&self) -> usize {
+        CHAIN_LENGTH * 8
+    }
+
+    /// Returns the state of a pin.
+    pub fn get(&self, pin: usize) -> Result<bool, Error> {
+        if pin >= self.pin_count() {
+            return Err(Error::PinOutOfRange);
+        }
+
+        Ok(self.get_unchecked(pin))
+    }
+
+    /// Returns the state of a pin without pin boundary checks.
+    pub fn get_unchecked(&self, pin: usize) -> bool {
+        self.data[pin / 8].load(Ordering::Acquire) & (1 << (pin % 8)) != 0
+    }
+
+    /// Sets the state of a pin.
+    pub fn set(&self, pin: usize, state: bool) -> Result<(), Error> {
+        if pin >= self.pin_count() {
+            return Err(Error::PinOutOfRange);
+        }
+
+        self.set_unchecked(pin, state);
+
+        Ok(())
+    }
+
+    /// Sets the state of a pin without pin boundary checks.
+    pub fn set_unchecked(&self, pin: usize, state: bool) {
+        let mask = 1 << (pin % 8);
+
+        if state {
+            self.data[pin / 8].fetch_or(mask, Ordering::AcqRel);
+        } else {
+            self.data[pin / 8].fetch_and(!mask, Ordering::AcqRel);
+        }
+    }
+
+    /// Toggles the state of a pin without pin boundary checks.
+    pub fn toggle_unchecked(&self, pin: usize) {
+        self.data[pin / 8].fetch_xor(1 << (pin % 8), Ordering::AcqRel);
+    }
+
+    /// Copies the input states of a chain into the mirror.
+    ///
+    /// Intended to be called after each update of the chain. Only the pins
+    /// present in both the chain and the mirror are copied.
+    pub fn load_inputs(&self, chain: &(impl GetInput + Length)) {
+        for (index, byte) in self.data.iter().enumerate().take(chain.input_len()) {
+            let value = (0..8).fold(0, |value, bit| {
+                if chain.get_input_unchecked(index * 8 + bit) {
+                    value | (1 << bit)
+                } else {
+                    value
+                }
+            });
+            byte.store(value, Ordering::Release);
+        }
+    }
+
+    /// Copies the states from the mirror into the output buffer of a chain.
+    ///
+    /// Intended to be called before each update of the chain. Only the pins
+    /// present in both the chain and the mirror are copied.
+    pub fn store_outputs(&self, chain: &mut (impl SetOutput + Length)) {
+        let len = chain.output_len();
+
+        for (index, byte) in self.data.iter().enumerate().take(len) {
+            let value = byte.load(Ordering::Acquire);
+
+            for bit in 0..8 {
+                chain.set_output_unchecked(index * 8 + bit, value & (1 << bit) != 0);
+            }
+        }
+    }
+}
+
+impl<const CHAIN_LENGTH: usize> Default for Mirror<CHAIN_LENGTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Pin accessing the state in a mirror.
+///
+/// Implements the input and output pin traits, depending on whether the
+/// mirror is filled from the inputs or copied into the outputs of a chain.
+#[cfg_attr(not(feature = "eh1"), allow(dead_code))]
+pub struct Pin<'a, const CHAIN_LENGTH: usize> {
+    /// Reference to the mirror.
+    mirror: &'a Mirror<CHAIN_LENGTH>,
+
+    /// Pin number in the mirror.
+    pin: usize,
+}
+
+impl<'a, const CHAIN_LENGTH: usize> Pin<'a, CHAIN_LENGTH> {
+    /// Creates a new pin.
+    pub fn new(mirror: &'a Mirror<CHAIN_LENGTH>, pin: usize) -> Result<Self, Error> {
+        if pin >= mirror.pin_count() {
+            return Err(Error::PinOutOfRange);
+        }
+
+        Ok(Self { mirror, pin })
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<const CHAIN_LENGTH: usize> embedded_hal::digital::ErrorType for Pin<'_, CHAIN_LENGTH> {
+    type Error = Infallible;
+}
+
+#[cfg(feature = "eh1")]
+impl<const CHAIN_LENGTH: usize> embedded_hal::digital::InputPin for Pin<'_, CHAIN_LENGTH> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.mirror.get_unchecked(self.pin))
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.mirror.get_unchecked(self.pin))
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<const CHAIN_LENGTH: usize> embedded_hal::digital::OutputPin for Pin<'_, CHAIN_LENGTH> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.mirror.set_unchecked(self.pin, false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.mirror.set_unchecked(self.pin, true);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<const CHAIN_LENGTH: usize> embedded_hal::digital::StatefulOutputPin for Pin<'_, CHAIN_LENGTH> {
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.mirror.get_unchecked(self.pin))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.mirror.get_unchecked(self.pin))
+    }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.mirror.toggle_unchecked(self.pin);
+        Ok(())
+    }
+}
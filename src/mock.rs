@@ -0,0 +1,98 @@
+//! Recording transport for the unit tests
+
+use crate::transport::{ShiftTransport, StepTransport};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Maximum number of bytes of a transfer.
+const CAPACITY: usize = 8;
+
+/// Error of a failing transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Fault;
+
+/// Transport recording the bytes shifted out and shifting in preset bytes,
+/// both in wire order.
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    /// Bytes shifted in, the first byte coming from the nearest chip.
+    pub(crate) read: [u8; CAPACITY],
+
+    /// Bytes shifted out by the last completed transfer, the first byte
+    /// ending up in the farthest chip.
+    pub(crate) written: [u8; CAPACITY],
+
+    /// Number of bytes shifted out by the last completed transfer.
+    pub(crate) written_len: usize,
+
+    /// Number of completed transfers.
+    pub(crate) transfers: usize,
+
+    /// Whether the transfers fail.
+    pub(crate) fail: bool,
+
+    /// Bits shifted out by the current stepped transfer.
+    shifted: [u8; CAPACITY],
+
+    /// Position of the next bit of the current stepped transfer.
+    position: usize,
+}
+
+impl Recorder {
+    /// Returns the bytes shifted out by the last completed transfer.
+    pub(crate) fn written(&self) -> &[u8] {
+        &self.written[..self.written_len]
+    }
+}
+
+impl ShiftTransport for Recorder {
+    type Error = Fault;
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        if self.fail {
+            return Err(Fault);
+        }
+
+        read.copy_from_slice(&self.read[..read.len()]);
+        self.written[..write.len()].copy_from_slice(write);
+        self.written_len = write.len();
+        self.transfers += 1;
+
+        Ok(())
+    }
+}
+
+/// Bits are shifted MSB first, matching `transfer()`.
+impl StepTransport for Recorder {
+    type Error = Fault;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.shifted = [0; CAPACITY];
+        self.position = 0;
+
+        Ok(())
+    }
+
+    fn shift_bit(&mut self, state: bool) -> Result<bool, Self::Error> {
+        if self.fail {
+            return Err(Fault);
+        }
+
+        let (index, bit) = (self.position / 8, 7 - self.position % 8);
+        self.position += 1;
+
+        if state {
+            self.shifted[index] |= 1 << bit;
+        }
+
+        Ok(self.read[index] & (1 << bit) != 0)
+    }
+
+    fn end(&mut self) -> Result<(), Self::Error> {
+        self.written = self.shifted;
+        self.written_len = self.position.div_ceil(8);
+        self.transfers += 1;
+
+        Ok(())
+    }
+}
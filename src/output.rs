@@ -1,10 +1,22 @@
 //! Single chain of 8-bit SIPO shift registers (e.g. 74HC595) for digital output
 
-use core::cell::RefCell;
+#[cfg(any(feature = "eh1", feature = "eh02"))]
+use core::convert::Infallible;
+use core::{cell::RefCell, fmt};
 
-use embedded_hal::digital::{ErrorType, OutputPin};
+use embedded_hal::{delay::DelayNs, digital::OutputPin, pwm::SetDutyCycle, spi::SpiBus};
 
-use crate::{Error, Length};
+use crate::{
+    pulse::Pulses,
+    register_bytes,
+    transport::{
+        set_level, ClockConfig, LatchPolarity, NoDelay, NoPin, Settle, ShiftTransport, SpiError,
+        StepTransport,
+    },
+    BitOrder, ChipOrder, Error, Length, Update,
+};
+#[cfg(feature = "async")]
+use crate::{transport::AsyncShiftTransport, AsyncUpdate};
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -15,117 +27,2532 @@ pub trait SetOutput {
 
     /// Sets the output state for a pin without pin boundary checks.
     fn set_output_unchecked(&mut self, pin: usize, state: bool);
+
+    /// Toggles the output state for a pin.
+    fn toggle_output(&mut self, pin: usize) -> Result<(), Error>
+    where
+        Self: GetOutput,
+    {
+        let state = self.get_output(pin)?;
+        self.set_output(pin, !state)
+    }
+
+    /// Toggles the output state for a pin without pin boundary checks.
+    fn toggle_output_unchecked(&mut self, pin: usize)
+    where
+        Self: GetOutput,
+    {
+        let state = self.get_output_unchecked(pin);
+        self.set_output_unchecked(pin, !state);
+    }
+
+    /// Sets the output states for all pins to high.
+    fn set_all_outputs(&mut self)
+    where
+        Self: Length,
+    {
+        self.fill_with(|_, _| true);
+    }
+
+    /// Sets the output states for all pins to low.
+    fn clear_all_outputs(&mut self)
+    where
+        Self: Length,
+    {
+        self.fill_with(|_, _| false);
+    }
+
+    /// Inverts the output states for all pins.
+    fn invert_all_outputs(&mut self)
+    where
+        Self: GetOutput + Length,
+    {
+        for pin in 0..self.output_len() * 8 {
+            self.toggle_output_unchecked(pin);
+        }
+    }
+
+    /// Copies `data` into the output buffer, starting with the first chip.
+    ///
+    /// Bit `n` of byte `i` is the state of pin `i * 8 + n`. Returns an error
+    /// if `data` is longer than the chain.
+    fn write_outputs(&mut self, data: &[u8]) -> Result<(), Error>
+    where
+        Self: Length,
+    {
+        if data.len() > self.output_len() {
+            return Err(Error::InvalidRange);
+        }
+
+        for (index, byte) in data.iter().enumerate() {
+            for bit in 0..8 {
+                self.set_output_unchecked(index * 8 + bit, byte & (1 << bit) != 0);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the output states for a group of pins given as `(pin, state)`.
+    ///
+    /// All pins are checked before any state is set, so the group is never
+    /// applied partially. Returns an error if a pin is out of range.
+    fn set_outputs(&mut self, states: &[(usize, bool)]) -> Result<(), Error>
+    where
+        Self: Length,
+    {
+        if states.iter().any(|&(pin, _)| pin >= self.output_len() * 8) {
+            return Err(Error::PinOutOfRange);
+        }
+
+        for &(pin, state) in states {
+            self.set_output_unchecked(pin, state);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the output states of consecutive pins from the bits of `value`,
+    /// starting with the LSB at `start_pin`.
+    ///
+    /// The pins may span several chips. Returns an error if they exceed the
+    /// chain.
+    fn set_output_word<W: Word>(&mut self, start_pin: usize, value: W) -> Result<(), Error>
+    where
+        Self: Length,
+    {
+        set_bits(self, start_pin, W::BYTES * 8, value.to_u64())
+    }
+
+    /// Sets the output states of `len` consecutive pins from the lower bits
+    /// of `value`, starting with the LSB at `start_pin`.
+    ///
+    /// Returns an error if `len` exceeds 32 or the pins exceed the chain.
+    fn set_output_range(&mut self, start_pin: usize, len: usize, value: u32) -> Result<(), Error>
+    where
+        Self: Length,
+    {
+        if len > 32 {
+            return Err(Error::InvalidRange);
+        }
+
+        set_bits(self, start_pin, len, value.into())
+    }
+
+    /// Sets all outputs to low except the one at `position`, wrapped around
+    /// the chain length.
+    ///
+    /// Incrementing `position` with each update walks a single high output
+    /// through the chain, e.g. for hardware bring-up.
+    fn fill_walking_one(&mut self, position: usize)
+    where
+        Self: Length,
+    {
+        self.fill_with(|pin, pins| pin == position % pins);
+    }
+
+    /// Sets all outputs to high except the one at `position`, wrapped around
+    /// the chain length.
+    fn fill_walking_zero(&mut self, position: usize)
+    where
+        Self: Length,
+    {
+        self.fill_with(|pin, pins| pin != position % pins);
+    }
+
+    /// Sets the even outputs to high and the odd outputs to low, or vice
+    /// versa if `inverted` is `true`.
+    fn fill_alternating(&mut self, inverted: bool)
+    where
+        Self: Length,
+    {
+        self.fill_with(|pin, _| (pin % 2 == 0) != inverted);
+    }
+
+    /// Sets the outputs of each chip to the bits of `pattern`, with bit `n`
+    /// being the state of output `n` of the chip.
+    fn fill_pattern(&mut self, pattern: u8)
+    where
+        Self: Length,
+    {
+        self.fill_with(|pin, _| pattern & (1 << (pin % 8)) != 0);
+    }
+
+    /// Sets each output to the state returned by `f` for the pin number and
+    /// the total number of pins.
+    fn fill_with(&mut self, f: impl Fn(usize, usize) -> bool)
+    where
+        Self: Length,
+    {
+        let pins = self.output_len() * 8;
+
+        for pin in 0..pins {
+            self.set_output_unchecked(pin, f(pin, pins));
+        }
+    }
+
+    /// Writes a digit to the 4 pins starting at `start_pin`, e.g. feeding a
+    /// BCD to 7-segment decoder like the 7447.
+    ///
+    /// `None` writes the blanking code [`BCD_BLANK`]. Returns an error if the
+    /// digit exceeds 9 or the pins exceed the chain.
+    fn write_bcd(&mut self, start_pin: usize, digit: Option<u8>) -> Result<(), Error>
+    where
+        Self: Length,
+    {
+        let code = match digit {
+            Some(digit) if digit > 9 => return Err(Error::InvalidRange),
+            Some(digit) => digit,
+            None => BCD_BLANK,
+        };
+
+        set_bits(self, start_pin, 4, code.into())
+    }
+
+    /// Writes `value` as `digits` BCD digits to consecutive groups of 4 pins,
+    /// starting with the least significant digit at `start_pin`.
+    ///
+    /// If `blank_leading_zeros` is `true`, leading zeros except the least
+    /// significant digit are written as [`BCD_BLANK`]. Returns an error
+    /// without changing the buffer if `value` does not fit into the digits
+    /// or the pins exceed the chain.
+    fn write_bcd_digits(
+        &mut self,
+        start_pin: usize,
+        digits: usize,
+        value: u32,
+        blank_leading_zeros: bool,
+    ) -> Result<(), Error>
+    where
+        Self: Length,
+    {
+        let fits = match u32::try_from(digits)
+            .ok()
+            .and_then(|digits| 10u32.checked_pow(digits))
+        {
+            Some(limit) => value < limit,
+            None => true,
+        };
+
+        if !fits {
+            return Err(Error::InvalidRange);
+        }
+
+        check_bits(
+            self.output_len() * 8,
+            start_pin,
+            digits.checked_mul(4).ok_or(Error::PinOutOfRange)?,
+        )?;
+
+        let mut rest = value;
+
+        for digit in 0..digits {
+            let code = if rest == 0 && digit > 0 && blank_leading_zeros {
+                BCD_BLANK
+            } else {
+                (rest % 10) as u8
+            };
+            set_bits(self, start_pin + digit * 4, 4, code.into())?;
+            rest /= 10;
+        }
+
+        Ok(())
+    }
+}
+
+/// Trait to be implemented by output chains that buffer the output states
+/// and can read them back.
+pub trait GetOutput {
+    /// Returns the buffered output state for a pin.
+    fn get_output(&self, pin: usize) -> Result<bool, Error>;
+
+    /// Returns the buffered output state for a pin without pin boundary checks.
+    fn get_output_unchecked(&self, pin: usize) -> bool;
+
+    /// Returns the buffered output states of consecutive pins as a word,
+    /// starting with the LSB at `start_pin`.
+    ///
+    /// The pins may span several chips. Returns an error if they exceed the
+    /// chain.
+    fn get_output_word<W: Word>(&self, start_pin: usize) -> Result<W, Error>
+    where
+        Self: Length,
+    {
+        get_bits(
+            self,
+            self.output_len() * 8,
+            start_pin,
+            W::BYTES * 8,
+            Self::get_output_unchecked,
+        )
+        .map(W::from_u64)
+    }
+
+    /// Returns the buffered output states of `len` consecutive pins,
+    /// starting with the LSB at `start_pin`.
+    ///
+    /// Returns an error if `len` exceeds 32 or the pins exceed the chain.
+    fn get_output_range(&self, start_pin: usize, len: usize) -> Result<u32, Error>
+    where
+        Self: Length,
+    {
+        if len > 32 {
+            return Err(Error::InvalidRange);
+        }
+
+        get_bits(
+            self,
+            self.output_len() * 8,
+            start_pin,
+            len,
+            Self::get_output_unchecked,
+        )
+        .map(|value| value as u32)
+    }
+
+    /// Returns an iterator over the pin numbers and buffered output states
+    /// of the whole chain.
+    fn outputs(&self) -> impl Iterator<Item = (usize, bool)>
+    where
+        Self: Length,
+    {
+        (0..self.output_len() * 8).map(move |pin| (pin, self.get_output_unchecked(pin)))
+    }
+}
+
+/// Code blanking the display of a BCD to 7-segment decoder like the 7447.
+pub const BCD_BLANK: u8 = 0x0F;
+
+/// Trait to be implemented by chains that can encode their output buffer
+/// for a transfer by external means, e.g. a DMA-driven SPI peripheral.
+pub trait EncodeOutput {
+    /// Encodes the output buffer into `words` in the exact order the bits
+    /// have to be shifted out, MSB first, and returns the number of words used.
+    ///
+    /// If the chain length is not a multiple of the word size, the first word
+    /// is padded with leading zero bits that are shifted out of the chain.
+    fn encode_output<W: Word>(&self, words: &mut [W]) -> Result<usize, Error>;
+}
+
+/// Trait to be implemented by word types the pin states can be converted to.
+pub trait Word: Copy {
+    /// Number of bytes in a word.
+    const BYTES: usize;
+
+    /// Creates a word from a slice of `BYTES` bytes in big-endian order.
+    ///
+    /// Returns `None` if the slice length differs from `BYTES`.
+    fn from_be_slice(bytes: &[u8]) -> Option<Self>;
+
+    /// Creates a word from the lower bits of a `u64`.
+    fn from_u64(value: u64) -> Self;
+
+    /// Converts the word into a `u64`.
+    fn to_u64(self) -> u64;
+}
+
+macro_rules! impl_word {
+    ($($ty:ty),*) => {
+        $(
+            impl Word for $ty {
+                const BYTES: usize = core::mem::size_of::<$ty>();
+
+                fn from_be_slice(bytes: &[u8]) -> Option<Self> {
+                    bytes.try_into().ok().map(Self::from_be_bytes)
+                }
+
+                fn from_u64(value: u64) -> Self {
+                    value as Self
+                }
+
+                fn to_u64(self) -> u64 {
+                    self as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(u8, u16, u32, u64);
+
+/// Checks whether `len` pins starting at `start_pin` are within `pins`.
+fn check_bits(pins: usize, start_pin: usize, len: usize) -> Result<(), Error> {
+    match start_pin.checked_add(len) {
+        Some(end) if end <= pins => Ok(()),
+        _ => Err(Error::PinOutOfRange),
+    }
+}
+
+/// Sets the output states of `len` pins from the bits of `value`, starting
+/// with the LSB at `start_pin`.
+fn set_bits<C>(chain: &mut C, start_pin: usize, len: usize, value: u64) -> Result<(), Error>
+where
+    C: SetOutput + Length + ?Sized,
+{
+    check_bits(chain.output_len() * 8, start_pin, len)?;
+
+    for bit in 0..len {
+        chain.set_output_unchecked(start_pin + bit, value & (1 << bit) != 0);
+    }
+
+    Ok(())
+}
+
+/// Returns the states of `len` pins reported by `state`, starting with the
+/// LSB at `start_pin`, out of `pins` pins.
+pub(crate) fn get_bits<C>(
+    chain: &C,
+    pins: usize,
+    start_pin: usize,
+    len: usize,
+    state: impl Fn(&C, usize) -> bool,
+) -> Result<u64, Error>
+where
+    C: ?Sized,
+{
+    check_bits(pins, start_pin, len)?;
+
+    Ok((0..len).fold(0, |value, bit| {
+        if state(chain, start_pin + bit) {
+            value | (1 << bit)
+        } else {
+            value
+        }
+    }))
+}
+
+/// Encodes a buffer into words as described by [`EncodeOutput::encode_output`].
+pub(crate) fn encode<W: Word>(buffer: &[u8], words: &mut [W]) -> Result<usize, Error> {
+    let word_count = buffer.len().div_ceil(W::BYTES);
+
+    if words.len() < word_count {
+        return Err(Error::BufferTooSmall);
+    }
+
+    // Number of padding bytes in the first word
+    let padding = word_count * W::BYTES - buffer.len();
+
+    for (index, word) in words[..word_count].iter_mut().enumerate() {
+        // Large enough for the widest word type
+        let mut bytes = [0; 8];
+        let start = (index * W::BYTES).saturating_sub(padding);
+        let end = (index + 1) * W::BYTES - padding;
+        let offset = W::BYTES - (end - start);
+        bytes[offset..W::BYTES].copy_from_slice(&buffer[start..end]);
+        *word = W::from_u64(u64::from_be_bytes(bytes) >> (8 * (8 - W::BYTES)));
+    }
+
+    Ok(word_count)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Error of a hardware clear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClearError<PinE, TransportE> {
+    /// Error of the clear pin.
+    Pin(PinE),
+
+    /// Error of the transport latching the cleared outputs.
+    Transport(TransportE),
+}
+
+impl<PinE, TransportE> fmt::Display for ClearError<PinE, TransportE>
+where
+    PinE: fmt::Display,
+    TransportE: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pin(error) => write!(f, "clear pin error: {error}"),
+            Self::Transport(error) => write!(f, "transport error: {error}"),
+        }
+    }
+}
+
+impl<PinE, TransportE> core::error::Error for ClearError<PinE, TransportE>
+where
+    PinE: core::error::Error + 'static,
+    TransportE: core::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Pin(error) => Some(error),
+            Self::Transport(error) => Some(error),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Profile of the SIPO chips in the chain, selecting the register width.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChipProfile {
+    /// 74HC595 and alike with 8 outputs per chip.
+    #[default]
+    Hc595,
+
+    /// 16-bit constant-current LED sink drivers like the STP16CP05 or the
+    /// TLC5926, shifting OUT15 first and latching while LE is high.
+    ///
+    /// Brightness can be controlled by a PWM channel attached as output
+    /// enable pin, see `set_brightness()`.
+    Stp16,
+
+    /// TPIC6B595 power shift registers with open-drain outputs, e.g. for
+    /// relay or solenoid boards.
+    ///
+    /// A set output switches its DMOS transistor on, so a load connected
+    /// between the supply and DRAIN is energized while the DRAIN pin is
+    /// pulled low. The buffer holds the load states, no inversion is needed.
+    /// The /G and /SRCLR pins are attached with `with_oe_pin()` and
+    /// `with_clear_pin()`, `reset_outputs()` switches all loads off safely.
+    Tpic6b595,
+}
+
+impl ChipProfile {
+    /// Returns the number of outputs per chip.
+    pub fn register_bits(self) -> usize {
+        match self {
+            Self::Hc595 | Self::Tpic6b595 => 8,
+            Self::Stp16 => 16,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Chain of SIPO shift registers.
+///
+/// Optional output enable and clear pins can be attached with
+/// `with_oe_pin()` and `with_clear_pin()`.
+pub struct Chain<Transport, const CHAIN_LENGTH: usize, OePin = NoPin, ClearPin = NoPin> {
+    /// Transport shifting the data into the chips.
+    transport: Transport,
+
+    /// Buffer storing the data to output.
+    data_buffer: [u8; CHAIN_LENGTH],
+
+    /// Number of bits already shifted by an incremental update.
+    shift_position: usize,
+
+    /// Order in which the bits of each chip are shifted.
+    bit_order: BitOrder,
+
+    /// Order in which the chips are numbered.
+    chip_order: ChipOrder,
+
+    /// Number of bytes per chip.
+    register_bytes: usize,
+
+    /// Outputs inverted at shift time, in the order of the buffer.
+    output_inversion: [u8; CHAIN_LENGTH],
+
+    /// Buffer shifted by the last completed update, `None` if the outputs
+    /// are unknown.
+    shifted_buffer: Option<[u8; CHAIN_LENGTH]>,
+
+    /// Timed pulses of the outputs.
+    pulses: Pulses<CHAIN_LENGTH>,
+
+    /// Output buffer as shifted out, read by DMA transfers.
+    #[cfg(feature = "embedded-dma")]
+    dma_buffer: [u8; CHAIN_LENGTH],
+
+    /// Pin for the active-low output enable signal.
+    oe_pin: OePin,
+
+    /// Pin for the active-low shift register clear signal.
+    clear_pin: ClearPin,
+}
+
+/// Chain of SIPO shift registers using bit-banged pins.
+pub type BitBangChain<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize> =
+    Chain<BitBangTransport<ClockPin, LatchPin, DataPin>, CHAIN_LENGTH>;
+
+/// Chain of SIPO shift registers without a storage register (e.g. 74HC164)
+/// using bit-banged pins.
+pub type UnlatchedChain<ClockPin, DataPin, const CHAIN_LENGTH: usize> =
+    Chain<UnlatchedTransport<ClockPin, DataPin>, CHAIN_LENGTH>;
+
+/// Chain of universal shift registers (e.g. 74HC194) using bit-banged pins.
+pub type UniversalChain<ClockPin, ModePin, DataPin, const CHAIN_LENGTH: usize> =
+    Chain<UniversalTransport<ClockPin, ModePin, DataPin>, CHAIN_LENGTH>;
+
+/// Chain of SIPO shift registers driven by a hardware SPI bus.
+pub type SpiChain<Spi, LatchPin, const CHAIN_LENGTH: usize> =
+    Chain<SpiTransport<Spi, LatchPin>, CHAIN_LENGTH>;
+
+impl<Transport, const CHAIN_LENGTH: usize> Chain<Transport, CHAIN_LENGTH> {
+    /// Creates a new chain by consuming a transport.
+    pub fn with_transport(transport: Transport) -> Self {
+        Self {
+            transport,
+            data_buffer: [0; CHAIN_LENGTH],
+            shift_position: 0,
+            bit_order: BitOrder::MsbFirst,
+            chip_order: ChipOrder::NearestFirst,
+            register_bytes: 1,
+            output_inversion: [0; CHAIN_LENGTH],
+            shifted_buffer: None,
+            pulses: Pulses::new(),
+            #[cfg(feature = "embedded-dma")]
+            dma_buffer: [0; CHAIN_LENGTH],
+            oe_pin: NoPin,
+            clear_pin: NoPin,
+        }
+    }
+
+    /// Frees the chain and returns the transport.
+    pub fn into_transport(self) -> Transport {
+        self.transport
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, NoPin, ClearPin>
+{
+    /// Attaches a pin driving the active-low output enable input (/OE) of
+    /// the chips.
+    ///
+    /// The pin is not changed, so the outputs can be kept disabled by a
+    /// pull-up until `enable_outputs()` is called after the first update.
+    /// A PWM channel can be attached instead of a pin to dim the outputs
+    /// with `set_brightness()`.
+    pub fn with_oe_pin<OePin>(
+        self,
+        oe_pin: OePin,
+    ) -> Chain<Transport, CHAIN_LENGTH, OePin, ClearPin> {
+        self.map_parts(|transport, _, clear_pin| (transport, oe_pin, clear_pin, ()))
+            .0
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin> Chain<Transport, CHAIN_LENGTH, OePin, NoPin> {
+    /// Attaches a pin driving the active-low shift register clear input
+    /// (/SRCLR or /MR) of the chips, used by `hardware_clear()`.
+    ///
+    /// The pin is not changed, it has to be high for the chips to shift.
+    pub fn with_clear_pin<ClearPin>(
+        self,
+        clear_pin: ClearPin,
+    ) -> Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+    where
+        ClearPin: OutputPin,
+    {
+        self.map_parts(|transport, oe_pin, _| (transport, oe_pin, clear_pin, ()))
+            .0
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+where
+    OePin: OutputPin,
+{
+    /// Enables the outputs of the chips by driving the output enable pin low.
+    pub fn enable_outputs(&mut self) -> Result<(), OePin::Error> {
+        self.oe_pin.set_low()
+    }
+
+    /// Disables the outputs of the chips by driving the output enable pin
+    /// high, putting them in high-impedance state.
+    pub fn disable_outputs(&mut self) -> Result<(), OePin::Error> {
+        self.oe_pin.set_high()
+    }
+
+    /// Detaches the output enable pin and returns it with the chain.
+    pub fn release_oe_pin(self) -> (Chain<Transport, CHAIN_LENGTH, NoPin, ClearPin>, OePin) {
+        self.map_parts(|transport, oe_pin, clear_pin| (transport, NoPin, clear_pin, oe_pin))
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+where
+    OePin: SetDutyCycle,
+{
+    /// Sets the brightness of the outputs by the duty cycle of the PWM
+    /// channel attached as output enable pin.
+    ///
+    /// `brightness` ranges from 0 (off) to `max_brightness()` (fully on),
+    /// larger values are clamped.
+    pub fn set_brightness(&mut self, brightness: u16) -> Result<(), OePin::Error> {
+        let max_duty_cycle = self.oe_pin.max_duty_cycle();
+
+        // The output enable signal is active-low
+        self.oe_pin
+            .set_duty_cycle(max_duty_cycle - brightness.min(max_duty_cycle))
+    }
+
+    /// Returns the brightness of fully enabled outputs.
+    pub fn max_brightness(&self) -> u16 {
+        self.oe_pin.max_duty_cycle()
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+where
+    ClearPin: OutputPin,
+{
+    /// Detaches the clear pin and returns it with the chain.
+    pub fn release_clear_pin(self) -> (Chain<Transport, CHAIN_LENGTH, OePin>, ClearPin) {
+        self.map_parts(|transport, oe_pin, clear_pin| (transport, oe_pin, NoPin, clear_pin))
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+where
+    Transport: ShiftTransport,
+    ClearPin: OutputPin,
+{
+    /// Clears all outputs by pulsing the clear pin and latching the cleared
+    /// shift registers, which is faster than shifting zeros through the
+    /// chain.
+    ///
+    /// The outputs are driven low, so the buffer is cleared except for
+    /// inverted outputs, which read as set afterwards.
+    pub fn hardware_clear(&mut self) -> Result<(), ClearError<ClearPin::Error, Transport::Error>> {
+        self.shift_position = 0;
+        self.shifted_buffer = None;
+
+        self.clear_pin.set_low().map_err(ClearError::Pin)?;
+        self.clear_pin.set_high().map_err(ClearError::Pin)?;
+        self.transport
+            .transfer(&mut [], &[])
+            .map_err(ClearError::Transport)?;
+
+        self.data_buffer = self.output_inversion;
+        self.shifted_buffer = Some(self.data_buffer);
+
+        Ok(())
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+where
+    Transport: ShiftTransport,
+    OePin: OutputPin,
+    ClearPin: OutputPin<Error = OePin::Error>,
+{
+    /// Brings the chips into a defined state, e.g. after power-up of a relay
+    /// board with random register contents.
+    ///
+    /// Disables the outputs, clears them by `hardware_clear()` and enables
+    /// them again, so no output is switched on in between.
+    pub fn reset_outputs(&mut self) -> Result<(), ClearError<OePin::Error, Transport::Error>> {
+        self.oe_pin.set_high().map_err(ClearError::Pin)?;
+        self.hardware_clear()?;
+        self.oe_pin.set_low().map_err(ClearError::Pin)
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+{
+    /// Moves the chain into a chain with another transport or other control
+    /// pins, returning an additional value from `f`.
+    fn map_parts<NewTransport, NewOePin, NewClearPin, R>(
+        self,
+        f: impl FnOnce(Transport, OePin, ClearPin) -> (NewTransport, NewOePin, NewClearPin, R),
+    ) -> (Chain<NewTransport, CHAIN_LENGTH, NewOePin, NewClearPin>, R) {
+        let (transport, oe_pin, clear_pin, value) = f(self.transport, self.oe_pin, self.clear_pin);
+
+        let chain = Chain {
+            transport,
+            data_buffer: self.data_buffer,
+            shift_position: self.shift_position,
+            bit_order: self.bit_order,
+            chip_order: self.chip_order,
+            register_bytes: self.register_bytes,
+            output_inversion: self.output_inversion,
+            shifted_buffer: self.shifted_buffer,
+            pulses: self.pulses,
+            #[cfg(feature = "embedded-dma")]
+            dma_buffer: self.dma_buffer,
+            oe_pin,
+            clear_pin,
+        };
+
+        (chain, value)
+    }
+
+    /// Returns the output buffer.
+    ///
+    /// With `ChipOrder::NearestFirst`, the bytes are in the order they are
+    /// shifted out, so the first byte holds the outputs of the last chip.
+    /// Bit `n` of byte `i` is the state of pin `(CHAIN_LENGTH - i - 1) * 8 + n`
+    /// regardless of the chip order.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data_buffer
+    }
+
+    /// Returns the output buffer mutably.
+    ///
+    /// With `ChipOrder::NearestFirst`, the bytes are in the order they are
+    /// shifted out, so the first byte holds the outputs of the last chip.
+    /// Bit `n` of byte `i` is the state of pin `(CHAIN_LENGTH - i - 1) * 8 + n`
+    /// regardless of the chip order.
+    pub fn as_mut_bytes(&mut self) -> &mut [u8] {
+        &mut self.data_buffer
+    }
+
+    /// Returns `true` if the buffer changed since the last update, or the
+    /// outputs are unknown because the chain was not updated successfully.
+    ///
+    /// Only `update()` and `update_async()` record the shifted buffer,
+    /// the chain is always dirty after `poll_update()`.
+    pub fn is_dirty(&self) -> bool {
+        self.shifted_buffer != Some(self.data_buffer)
+    }
+
+    /// Returns the order in which the bits of each chip are shifted.
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
+    /// Sets the order in which the bits of each chip are shifted.
+    ///
+    /// Bit `n` of each buffer byte holds pin `n` of the chip regardless of
+    /// the bit order.
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) {
+        self.bit_order = bit_order;
+        self.shifted_buffer = None;
+    }
+
+    /// Returns the order in which the chips are numbered.
+    pub fn chip_order(&self) -> ChipOrder {
+        self.chip_order
+    }
+
+    /// Sets the order in which the chips are numbered.
+    ///
+    /// By default, pin 0 is on the chip nearest to the controller, the same
+    /// as for the input chains. `ChipOrder::FarthestFirst` numbers the pins
+    /// from the far end of the chain instead. The buffer keeps its layout,
+    /// the order is applied when shifting.
+    pub fn set_chip_order(&mut self, chip_order: ChipOrder) {
+        self.chip_order = chip_order;
+        self.shifted_buffer = None;
+    }
+
+    /// Returns the number of bits per chip.
+    pub fn register_bits(&self) -> usize {
+        self.register_bytes * 8
+    }
+
+    /// Sets the number of bits per chip at runtime, 8 by default.
+    ///
+    /// The chain length remains the number of bytes and the pins are numbered
+    /// consecutively across the chips. The width only changes how the bit
+    /// order applies, so chains of chips with different widths can be
+    /// used with `BitOrder::MsbFirst` and the default width. Returns an error
+    /// if the width is not a multiple of 8 or the chain length is not a
+    /// multiple of it.
+    pub fn set_register_bits(&mut self, bits: usize) -> Result<(), Error> {
+        self.register_bytes = register_bytes(bits, CHAIN_LENGTH).ok_or(Error::InvalidRange)?;
+        self.shifted_buffer = None;
+
+        Ok(())
+    }
+
+    /// Sets the register width for the chips of a profile, see
+    /// `set_register_bits()`.
+    ///
+    /// The pins are numbered consecutively across the chips, e.g. pin 16 is
+    /// OUT0 of the second 16-bit driver.
+    pub fn set_chip_profile(&mut self, profile: ChipProfile) -> Result<(), Error> {
+        self.set_register_bits(profile.register_bits())
+    }
+
+    /// Sets the outputs inverted at shift time, e.g. for active-low relays
+    /// or LEDs, so the buffer holds the logical states.
+    ///
+    /// Bit `n` of byte `i` inverts pin `i * 8 + n`, pins beyond `mask` are
+    /// not inverted. Returns an error if `mask` is longer than the chain.
+    pub fn set_output_inversion(&mut self, mask: &[u8]) -> Result<(), Error> {
+        if mask.len() > CHAIN_LENGTH {
+            return Err(Error::InvalidRange);
+        }
+
+        self.output_inversion = [0; CHAIN_LENGTH];
+
+        for (index, &mask) in mask.iter().enumerate() {
+            self.output_inversion[CHAIN_LENGTH - index - 1] = mask;
+        }
+
+        self.shifted_buffer = None;
+
+        Ok(())
+    }
+
+    /// Sets whether an output is inverted at shift time.
+    pub fn set_inverted_output(&mut self, pin: usize, inverted: bool) -> Result<(), Error> {
+        if pin >= CHAIN_LENGTH * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        let index = CHAIN_LENGTH - (pin / 8) - 1;
+        let bit = pin % 8;
+
+        if inverted {
+            self.output_inversion[index] |= 1 << bit;
+        } else {
+            self.output_inversion[index] &= !(1 << bit);
+        }
+
+        self.shifted_buffer = None;
+
+        Ok(())
+    }
+
+    /// Returns the output buffer as shifted out, with the inversion, the
+    /// bit order and the chip order applied.
+    fn wire_output(&self) -> [u8; CHAIN_LENGTH] {
+        let mut data = self.data_buffer;
+
+        for (data, inversion) in data.iter_mut().zip(self.output_inversion) {
+            *data ^= inversion;
+        }
+
+        self.bit_order
+            .reorder_registers(&mut data, self.register_bytes);
+        self.chip_order.reorder(&mut data, self.register_bytes);
+
+        data
+    }
+
+    /// Returns the buffer byte and the bit within the byte that is shifted
+    /// at `position`.
+    fn locate(&self, position: usize) -> (usize, usize) {
+        let (index, bit) = self.bit_order.locate(position, self.register_bytes);
+
+        (
+            self.chip_order
+                .locate(index, CHAIN_LENGTH, self.register_bytes),
+            bit,
+        )
+    }
+
+    /// Marks the chain as dirty, so the next conditional update shifts the
+    /// buffer, e.g. after the outputs may have been disturbed.
+    pub fn mark_dirty(&mut self) {
+        self.shifted_buffer = None;
+    }
+
+    /// Sets an output high and clears it after `ticks` calls of `tick()`,
+    /// e.g. to fire a solenoid or a buzzer.
+    ///
+    /// A running pulse of the pin is restarted. Setting the pin otherwise
+    /// does not stop the pulse. Returns an error if the pin is out of range.
+    pub fn pulse(&mut self, pin: usize, ticks: u16) -> Result<(), Error> {
+        self.set_output(pin, true)?;
+        self.pulses.start(pin, ticks);
+
+        Ok(())
+    }
+
+    /// Stops the pulse of an output and clears it.
+    ///
+    /// Returns an error if the pin is out of range.
+    pub fn cancel_pulse(&mut self, pin: usize) -> Result<(), Error> {
+        self.set_output(pin, false)?;
+        self.pulses.cancel(pin);
+
+        Ok(())
+    }
+
+    /// Returns `true` if an output is pulsing or `None` if out of range.
+    pub fn is_pulsing(&self, pin: usize) -> Option<bool> {
+        (pin < CHAIN_LENGTH * 8).then(|| self.pulses.is_running(pin))
+    }
+
+    /// Advances the pulses by one tick and clears the outputs whose pulse
+    /// ended.
+    ///
+    /// Intended to be called periodically, e.g. from a timer or before each
+    /// update.
+    pub fn tick(&mut self) {
+        let buffer = &mut self.data_buffer;
+
+        self.pulses.tick(|pin| {
+            buffer[CHAIN_LENGTH - (pin / 8) - 1] &= !(1 << (pin % 8));
+        });
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize>
+    Chain<BitBangTransport<ClockPin, LatchPin, DataPin>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: OutputPin,
+{
+    /// Creates a new chain by consuming the pins.
+    pub fn new(clock_pin: ClockPin, latch_pin: LatchPin, data_pin: DataPin) -> Self {
+        Self::with_transport(BitBangTransport::new(clock_pin, latch_pin, data_pin))
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay, const CHAIN_LENGTH: usize>
+    Chain<BitBangTransport<ClockPin, LatchPin, DataPin, Delay>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new chain by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_pin: DataPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self::with_transport(BitBangTransport::with_delay(
+            clock_pin, latch_pin, data_pin, delay, settle_ns,
+        ))
+    }
+
+    /// Frees the chain and returns the pins.
+    pub fn free(self) -> (ClockPin, LatchPin, DataPin) {
+        self.transport.free()
+    }
+
+    /// Frees the chain and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, LatchPin, DataPin, Delay) {
+        self.transport.free_with_delay()
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<BitBangTransport<ClockPin, LatchPin, DataPin, Delay>, CHAIN_LENGTH, OePin, ClearPin>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Returns the clock configuration of the transport.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.transport.clock_config()
+    }
+
+    /// Sets the clock configuration of the transport.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.transport.set_clock_config(clock);
+    }
+
+    /// Returns the polarity of the latch signal of the transport.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.transport.latch_polarity()
+    }
+
+    /// Sets the polarity of the latch signal of the transport.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_latch_polarity(polarity);
+    }
+
+    /// Returns the settle time of the transport in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.transport.settle_ns()
+    }
+
+    /// Sets the settle time of the transport in nanoseconds, 0 disables
+    /// the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.transport.set_settle_ns(settle_ns);
+    }
+}
+
+impl<ClockPin, DataPin, const CHAIN_LENGTH: usize>
+    Chain<UnlatchedTransport<ClockPin, DataPin>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    DataPin: OutputPin,
+{
+    /// Creates a new chain by consuming the pins.
+    pub fn new(clock_pin: ClockPin, data_pin: DataPin) -> Self {
+        Self::with_transport(UnlatchedTransport::new(clock_pin, data_pin))
+    }
+}
+
+impl<ClockPin, DataPin, Delay, const CHAIN_LENGTH: usize>
+    Chain<UnlatchedTransport<ClockPin, DataPin, Delay>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new chain by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        data_pin: DataPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self::with_transport(UnlatchedTransport::with_delay(
+            clock_pin, data_pin, delay, settle_ns,
+        ))
+    }
+}
+
+impl<ClockPin, DataPin, Delay, StrobePin, const CHAIN_LENGTH: usize>
+    Chain<UnlatchedTransport<ClockPin, DataPin, Delay, StrobePin>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+    StrobePin: OutputPin,
+{
+    /// Frees the chain and returns the pins.
+    ///
+    /// A strobe pin is dropped, use `take_strobe_pin()` before to get it
+    /// back.
+    pub fn free(self) -> (ClockPin, DataPin) {
+        self.transport.free()
+    }
+
+    /// Frees the chain and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, DataPin, Delay) {
+        self.transport.free_with_delay()
+    }
+}
+
+impl<ClockPin, DataPin, Delay, StrobePin, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<UnlatchedTransport<ClockPin, DataPin, Delay, StrobePin>, CHAIN_LENGTH, OePin, ClearPin>
+where
+    ClockPin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+    StrobePin: OutputPin,
+{
+    /// Attaches a strobe pin, see `UnlatchedTransport::with_strobe_pin()`.
+    pub fn with_strobe_pin<NewStrobePin>(
+        self,
+        strobe_pin: NewStrobePin,
+    ) -> Chain<
+        UnlatchedTransport<ClockPin, DataPin, Delay, NewStrobePin>,
+        CHAIN_LENGTH,
+        OePin,
+        ClearPin,
+    >
+    where
+        NewStrobePin: OutputPin,
+    {
+        self.map_parts(|transport, oe_pin, clear_pin| {
+            (transport.with_strobe_pin(strobe_pin), oe_pin, clear_pin, ())
+        })
+        .0
+    }
+
+    /// Detaches the strobe pin and returns it.
+    pub fn take_strobe_pin(&mut self) -> Option<StrobePin> {
+        self.transport.take_strobe_pin()
+    }
+
+    /// Returns the clock configuration of the transport.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.transport.clock_config()
+    }
+
+    /// Sets the clock configuration of the transport.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.transport.set_clock_config(clock);
+    }
+
+    /// Returns the polarity of the strobe signal of the transport.
+    pub fn strobe_polarity(&self) -> LatchPolarity {
+        self.transport.strobe_polarity()
+    }
+
+    /// Sets the polarity of the strobe signal of the transport.
+    pub fn set_strobe_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_strobe_polarity(polarity);
+    }
+
+    /// Returns the settle time of the transport in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.transport.settle_ns()
+    }
+
+    /// Sets the settle time of the transport in nanoseconds, 0 disables
+    /// the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.transport.set_settle_ns(settle_ns);
+    }
+}
+
+impl<ClockPin, ModePin, DataPin, const CHAIN_LENGTH: usize>
+    Chain<UniversalTransport<ClockPin, ModePin, DataPin>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    ModePin: OutputPin,
+    DataPin: OutputPin,
+{
+    /// Creates a new chain by consuming the pins.
+    pub fn new(clock_pin: ClockPin, s0_pin: ModePin, s1_pin: ModePin, data_pin: DataPin) -> Self {
+        Self::with_transport(UniversalTransport::new(clock_pin, s0_pin, s1_pin, data_pin))
+    }
+}
+
+impl<ClockPin, ModePin, DataPin, Delay, const CHAIN_LENGTH: usize>
+    Chain<UniversalTransport<ClockPin, ModePin, DataPin, Delay>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    ModePin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new chain by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        s0_pin: ModePin,
+        s1_pin: ModePin,
+        data_pin: DataPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self::with_transport(UniversalTransport::with_delay(
+            clock_pin, s0_pin, s1_pin, data_pin, delay, settle_ns,
+        ))
+    }
+}
+
+impl<ClockPin, ModePin, DataPin, Delay, LeftDataPin, const CHAIN_LENGTH: usize>
+    Chain<UniversalTransport<ClockPin, ModePin, DataPin, Delay, LeftDataPin>, CHAIN_LENGTH>
+where
+    ClockPin: OutputPin,
+    ModePin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+    LeftDataPin: OutputPin,
+{
+    /// Frees the chain and returns the pins.
+    ///
+    /// A left data pin is dropped, use `take_left_data_pin()` before to get
+    /// it back.
+    pub fn free(self) -> (ClockPin, ModePin, ModePin, DataPin) {
+        self.transport.free()
+    }
+
+    /// Frees the chain and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, ModePin, ModePin, DataPin, Delay) {
+        self.transport.free_with_delay()
+    }
+}
+
+impl<
+        ClockPin,
+        ModePin,
+        DataPin,
+        Delay,
+        LeftDataPin,
+        const CHAIN_LENGTH: usize,
+        OePin,
+        ClearPin,
+    >
+    Chain<
+        UniversalTransport<ClockPin, ModePin, DataPin, Delay, LeftDataPin>,
+        CHAIN_LENGTH,
+        OePin,
+        ClearPin,
+    >
+where
+    ClockPin: OutputPin,
+    ModePin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+    LeftDataPin: OutputPin,
+{
+    /// Attaches a pin for shifting left, see
+    /// `UniversalTransport::with_left_data_pin()`.
+    pub fn with_left_data_pin<NewLeftDataPin>(
+        self,
+        left_data_pin: NewLeftDataPin,
+    ) -> Chain<
+        UniversalTransport<ClockPin, ModePin, DataPin, Delay, NewLeftDataPin>,
+        CHAIN_LENGTH,
+        OePin,
+        ClearPin,
+    >
+    where
+        NewLeftDataPin: OutputPin,
+    {
+        self.map_parts(|transport, oe_pin, clear_pin| {
+            (
+                transport.with_left_data_pin(left_data_pin),
+                oe_pin,
+                clear_pin,
+                (),
+            )
+        })
+        .0
+    }
+
+    /// Detaches the left data pin and returns it.
+    pub fn take_left_data_pin(&mut self) -> Option<LeftDataPin> {
+        self.transport.take_left_data_pin()
+    }
+
+    /// Returns the clock configuration of the transport.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.transport.clock_config()
+    }
+
+    /// Sets the clock configuration of the transport.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.transport.set_clock_config(clock);
+    }
+
+    /// Returns the settle time of the transport in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.transport.settle_ns()
+    }
+
+    /// Sets the settle time of the transport in nanoseconds, 0 disables
+    /// the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.transport.set_settle_ns(settle_ns);
+    }
+}
+
+impl<
+        ClockPin,
+        ModePin,
+        DataPin,
+        Delay,
+        LeftDataPin,
+        const CHAIN_LENGTH: usize,
+        OePin,
+        ClearPin,
+    >
+    Chain<
+        UniversalTransport<ClockPin, ModePin, DataPin, Delay, LeftDataPin>,
+        CHAIN_LENGTH,
+        OePin,
+        ClearPin,
+    >
+where
+    ClockPin: OutputPin,
+    ModePin: OutputPin<Error = ClockPin::Error>,
+    DataPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+    LeftDataPin: OutputPin<Error = ClockPin::Error>,
+{
+    /// Shifts all outputs by one position in the chips without shifting the
+    /// whole chain, e.g. for running light effects.
+    ///
+    /// `ShiftDirection::Right` moves each output to the next higher pin and
+    /// sets pin 0 to `state`, `ShiftDirection::Left` moves each output to the
+    /// next lower pin and sets the highest pin to `state`, or low without a
+    /// left data pin. The buffer is shifted along with the outputs, an
+    /// incremental update in progress is aborted.
+    pub fn shift_outputs(
+        &mut self,
+        direction: ShiftDirection,
+        state: bool,
+    ) -> Result<(), Error<ClockPin::Error>> {
+        let positions = CHAIN_LENGTH * 8;
+
+        // Positions count along the chain from the far end, so the entering
+        // bit is at the last position when shifting right
+        let entering = match direction {
+            ShiftDirection::Right => positions - 1,
+            ShiftDirection::Left => 0,
+        };
+
+        if self.shift_position != 0 {
+            self.shift_position = 0;
+            self.shifted_buffer = None;
+        }
+
+        let (index, bit) = self.locate(entering);
+        let inverted = self.output_inversion[index] & (1 << bit) != 0;
+
+        let wire_state = self
+            .transport
+            .step(direction, state != inverted)
+            .map_err(Error::Transport)?;
+
+        let wire_output = |position: usize| {
+            let (index, bit) = self.locate(position);
+            (self.data_buffer[index] ^ self.output_inversion[index]) & (1 << bit) != 0
+        };
+
+        let mut data = [0; CHAIN_LENGTH];
+
+        for position in 0..positions {
+            let state = match direction {
+                _ if position == entering => wire_state,
+                ShiftDirection::Right => wire_output(position + 1),
+                ShiftDirection::Left => wire_output(position - 1),
+            };
+
+            let (index, bit) = self.locate(position);
+
+            if state {
+                data[index] |= 1 << bit;
+            }
+        }
+
+        for (data, inversion) in data.iter_mut().zip(self.output_inversion) {
+            *data ^= inversion;
+        }
+
+        if let Some(shifted_buffer) = &mut self.shifted_buffer {
+            *shifted_buffer = data;
+        }
+
+        self.data_buffer = data;
+
+        Ok(())
+    }
+}
+
+impl<Spi, LatchPin, const CHAIN_LENGTH: usize> Chain<SpiTransport<Spi, LatchPin>, CHAIN_LENGTH> {
+    /// Creates a new chain by consuming the SPI bus and the latch pin.
+    pub fn new(spi: Spi, latch_pin: LatchPin) -> Self {
+        Self::with_transport(SpiTransport::new(spi, latch_pin))
+    }
+
+    /// Frees the chain and returns the SPI bus and the latch pin.
+    pub fn free(self) -> (Spi, LatchPin) {
+        self.transport.free()
+    }
+}
+
+impl<Spi, LatchPin, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<SpiTransport<Spi, LatchPin>, CHAIN_LENGTH, OePin, ClearPin>
+{
+    /// Returns the polarity of the latch signal of the transport.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.transport.latch_polarity()
+    }
+
+    /// Sets the polarity of the latch signal of the transport.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.transport.set_latch_polarity(polarity);
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+where
+    Transport: ShiftTransport,
+{
+    /// Updates the chain by shifting the data from the buffer into the chips.
+    ///
+    /// Returns an error if the transport failed, the outputs may be in an
+    /// undefined state in this case.
+    pub fn update(&mut self) -> Result<(), Error<Transport::Error>> {
+        self.shift_position = 0;
+        self.shifted_buffer = None;
+        let data = self.wire_output();
+        self.transport
+            .transfer(&mut [], &data)
+            .map_err(Error::Transport)?;
+        self.shifted_buffer = Some(self.data_buffer);
+
+        Ok(())
+    }
+
+    /// Updates the chain like `update()` if it is dirty, otherwise the
+    /// transfer is skipped.
+    ///
+    /// Returns `true` if the buffer was shifted.
+    pub fn update_if_dirty(&mut self) -> Result<bool, Error<Transport::Error>> {
+        if !self.is_dirty() {
+            return Ok(false);
+        }
+
+        self.update().map(|_| true)
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+where
+    Transport: StepTransport,
+{
+    /// Updates the chain incrementally by shifting up to `bits` bits per call.
+    ///
+    /// Returns `WouldBlock` until the whole chain has been shifted. Changes to
+    /// the buffer while an update is in progress may only be partially applied.
+    /// If the transport fails, an error is returned and the next call starts a
+    /// new update.
+    pub fn poll_update(&mut self, bits: usize) -> nb::Result<(), Error<Transport::Error>> {
+        self.step_update(bits).inspect_err(|error| {
+            if let nb::Error::Other(_) = error {
+                self.shift_position = 0;
+            }
+        })
+    }
+
+    /// Shifts up to `bits` bits for `poll_update()`.
+    fn step_update(&mut self, bits: usize) -> nb::Result<(), Error<Transport::Error>> {
+        if self.shift_position == 0 {
+            self.shifted_buffer = None;
+            self.transport.begin().map_err(Error::Transport)?;
+        }
+
+        let end = (self.shift_position + bits).min(CHAIN_LENGTH * 8);
+
+        for position in self.shift_position..end {
+            // Calculate index and bit position within buffer array
+            let (index, bit) = self.locate(position);
+
+            self.transport
+                .shift_bit(
+                    ((self.data_buffer[index] ^ self.output_inversion[index]) & (1 << bit)) != 0,
+                )
+                .map_err(Error::Transport)?;
+        }
+
+        self.shift_position = end;
+
+        if end < CHAIN_LENGTH * 8 {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.transport.end().map_err(Error::Transport)?;
+        self.shift_position = 0;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+where
+    Transport: AsyncShiftTransport,
+{
+    /// Updates the chain by shifting the data from the buffer into the chips
+    /// asynchronously.
+    ///
+    /// Returns an error if the transport failed, the outputs may be in an
+    /// undefined state in this case.
+    pub async fn update_async(&mut self) -> Result<(), Error<Transport::Error>> {
+        self.shift_position = 0;
+        self.shifted_buffer = None;
+        let data = self.wire_output();
+        self.transport
+            .transfer(&mut [], &data)
+            .await
+            .map_err(Error::Transport)?;
+        self.shifted_buffer = Some(self.data_buffer);
+
+        Ok(())
+    }
+
+    /// Updates the chain like `update_async()` if it is dirty, otherwise the
+    /// transfer is skipped.
+    ///
+    /// Returns `true` if the buffer was shifted.
+    pub async fn update_async_if_dirty(&mut self) -> Result<bool, Error<Transport::Error>> {
+        if !self.is_dirty() {
+            return Ok(false);
+        }
+
+        self.update_async().await.map(|_| true)
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin> SetOutput
+    for Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+{
+    /// Sets the output state for a pin.
+    ///
+    /// The output state is buffered and not set immediately because the bits
+    /// have to be shifted out by calling `update()` first.
+    fn set_output(&mut self, pin: usize, state: bool) -> Result<(), Error> {
+        if pin >= CHAIN_LENGTH * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        self.set_output_unchecked(pin, state);
+
+        Ok(())
+    }
+
+    /// Sets the output state for a pin without pin boundary checks.
+    ///
+    /// The output state is buffered and not set immediately because the bits
+    /// have to be shifted out by calling `update()` first.
+    fn set_output_unchecked(&mut self, pin: usize, state: bool) {
+        // Calculate index and bit position within buffer array
+        let index = CHAIN_LENGTH - (pin / 8) - 1;
+        let bit = pin % 8;
+
+        if state {
+            self.data_buffer[index] |= 1 << bit;
+        } else {
+            self.data_buffer[index] &= !(1 << bit);
+        }
+    }
+
+    /// Toggles the output state for a pin.
+    ///
+    /// The output state is buffered and not set immediately because the bits
+    /// have to be shifted out by calling `update()` first.
+    fn toggle_output(&mut self, pin: usize) -> Result<(), Error> {
+        if pin >= CHAIN_LENGTH * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        self.toggle_output_unchecked(pin);
+
+        Ok(())
+    }
+
+    /// Toggles the output state for a pin without pin boundary checks.
+    ///
+    /// The bit is flipped in place in the buffer and shifted out by the
+    /// next call to `update()`.
+    fn toggle_output_unchecked(&mut self, pin: usize) {
+        // Calculate index and bit position within buffer array
+        let index = CHAIN_LENGTH - (pin / 8) - 1;
+        let bit = pin % 8;
+
+        self.data_buffer[index] ^= 1 << bit;
+    }
+
+    /// Sets the output states for all pins to high.
+    ///
+    /// The output states are buffered and not set immediately because the
+    /// bits have to be shifted out by calling `update()` first.
+    fn set_all_outputs(&mut self) {
+        self.data_buffer.fill(0xFF);
+    }
+
+    /// Sets the output states for all pins to low.
+    ///
+    /// The output states are buffered and not set immediately because the
+    /// bits have to be shifted out by calling `update()` first.
+    fn clear_all_outputs(&mut self) {
+        self.data_buffer.fill(0);
+    }
+
+    /// Inverts the output states for all pins.
+    ///
+    /// The output states are buffered and not set immediately because the
+    /// bits have to be shifted out by calling `update()` first.
+    fn invert_all_outputs(&mut self) {
+        for data in self.data_buffer.iter_mut() {
+            *data = !*data;
+        }
+    }
+
+    /// Copies `data` into the output buffer, starting with the first chip.
+    ///
+    /// The output states are buffered and not set immediately because the
+    /// bits have to be shifted out by calling `update()` first.
+    fn write_outputs(&mut self, data: &[u8]) -> Result<(), Error> {
+        if data.len() > CHAIN_LENGTH {
+            return Err(Error::InvalidRange);
+        }
+
+        // The buffer is in wire order with the first chip at the end
+        for (target, source) in self.data_buffer.iter_mut().rev().zip(data) {
+            *target = *source;
+        }
+
+        Ok(())
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin> GetOutput
+    for Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+{
+    /// Returns the buffered output state for a pin.
+    ///
+    /// This is the state commanded by the last call to `set_output()` and
+    /// not necessarily the state of the output until `update()` is called.
+    fn get_output(&self, pin: usize) -> Result<bool, Error> {
+        if pin >= CHAIN_LENGTH * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        Ok(self.get_output_unchecked(pin))
+    }
+
+    /// Returns the buffered output state for a pin without pin boundary checks.
+    fn get_output_unchecked(&self, pin: usize) -> bool {
+        // Calculate index and bit position within buffer array
+        let index = CHAIN_LENGTH - (pin / 8) - 1;
+        let bit = pin % 8;
+
+        (self.data_buffer[index] & (1 << bit)) != 0
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin> Update
+    for Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+where
+    Transport: ShiftTransport,
+{
+    type Error = Error<Transport::Error>;
+
+    fn update(&mut self) -> Result<(), Self::Error> {
+        Chain::update(self)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin> AsyncUpdate
+    for Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+where
+    Transport: AsyncShiftTransport,
+{
+    type Error = Error<Transport::Error>;
+
+    async fn update_async(&mut self) -> Result<(), Self::Error> {
+        Chain::update_async(self).await
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin> Length
+    for Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+{
+    /// Returns the chain length.
+    fn len(&self) -> usize {
+        CHAIN_LENGTH
+    }
+}
+
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin> EncodeOutput
+    for Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+{
+    fn encode_output<W: Word>(&self, words: &mut [W]) -> Result<usize, Error> {
+        let data = self.wire_output();
+        encode(&data, words)
+    }
+}
+
+#[cfg(feature = "embedded-dma")]
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>
+{
+    /// Encodes the output buffer into the DMA buffer as shifted out, with
+    /// the inversion, the bit order and the chip order applied.
+    ///
+    /// The returned transfer borrows the chain until `finish()` is called
+    /// after the DMA transfer.
+    pub fn prepare_dma(&mut self) -> DmaTransfer<'_, Transport, CHAIN_LENGTH, OePin, ClearPin> {
+        self.shift_position = 0;
+        self.shifted_buffer = None;
+        self.dma_buffer = self.wire_output();
+        DmaTransfer { chain: self }
+    }
+}
+
+/// DMA transfer from an output chain, returned by `prepare_dma()`.
+#[cfg(feature = "embedded-dma")]
+pub struct DmaTransfer<'a, Transport, const CHAIN_LENGTH: usize, OePin = NoPin, ClearPin = NoPin> {
+    /// Chain the DMA buffer belongs to.
+    chain: &'a mut Chain<Transport, CHAIN_LENGTH, OePin, ClearPin>,
+}
+
+#[cfg(feature = "embedded-dma")]
+impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin>
+    DmaTransfer<'_, Transport, CHAIN_LENGTH, OePin, ClearPin>
+{
+    /// Completes the update after the DMA transfer, so the shifted buffer
+    /// is known to the dirty tracking.
+    pub fn finish(self) {
+        self.chain.shifted_buffer = Some(self.chain.data_buffer);
+    }
+}
+
+#[cfg(feature = "embedded-dma")]
+// SAFETY: The returned pointer and length refer to the DMA buffer of the
+// chain, which is mutably borrowed for the lifetime of the transfer.
+unsafe impl<Transport, const CHAIN_LENGTH: usize, OePin, ClearPin> embedded_dma::ReadTarget
+    for DmaTransfer<'_, Transport, CHAIN_LENGTH, OePin, ClearPin>
+{
+    type Word = u8;
+
+    /// Returns the DMA buffer encoded by `prepare_dma()`.
+    ///
+    /// The bytes are in the order they have to be shifted out, MSB first.
+    /// Toggling the latch signal after the transfer is up to the caller.
+    fn as_read_buffer(&self) -> (*const u8, usize) {
+        (self.chain.dma_buffer.as_ptr(), CHAIN_LENGTH)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Transport shifting the data out by bit-banging pins.
+///
+/// An optional `Delay` inserts a settle time around each signal change,
+/// e.g. for long cables, optocouplers or level shifters.
+pub struct BitBangTransport<ClockPin, LatchPin, DataPin, Delay = NoDelay> {
+    /// Pin for the clock output signal.
+    clock_pin: ClockPin,
+
+    /// Pin for the latch output signal.
+    latch_pin: LatchPin,
+
+    /// Pin for the data output signal.
+    data_pin: DataPin,
+
+    /// Clock configuration.
+    clock: ClockConfig,
+
+    /// Polarity of the latch signal.
+    latch_polarity: LatchPolarity,
+
+    /// Settle time of the signals.
+    settle: Settle<Delay>,
+}
+
+impl<ClockPin, LatchPin, DataPin> BitBangTransport<ClockPin, LatchPin, DataPin>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: OutputPin,
+{
+    /// Creates a new transport by consuming the pins.
+    pub fn new(clock_pin: ClockPin, latch_pin: LatchPin, data_pin: DataPin) -> Self {
+        Self::with_delay(clock_pin, latch_pin, data_pin, NoDelay, 0)
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay> BitBangTransport<ClockPin, LatchPin, DataPin, Delay>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new transport by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        latch_pin: LatchPin,
+        data_pin: DataPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self {
+            clock_pin,
+            latch_pin,
+            data_pin,
+            clock: ClockConfig::default(),
+            latch_polarity: LatchPolarity::Normal,
+            settle: Settle::new(delay, settle_ns),
+        }
+    }
+
+    /// Frees the transport and returns the pins.
+    pub fn free(self) -> (ClockPin, LatchPin, DataPin) {
+        (self.clock_pin, self.latch_pin, self.data_pin)
+    }
+
+    /// Frees the transport and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, LatchPin, DataPin, Delay) {
+        (
+            self.clock_pin,
+            self.latch_pin,
+            self.data_pin,
+            self.settle.into_delay(),
+        )
+    }
+
+    /// Returns the clock configuration.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.clock
+    }
+
+    /// Sets the clock configuration.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.clock = clock;
+    }
+
+    /// Returns the polarity of the latch signal.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.latch_polarity
+    }
+
+    /// Sets the polarity of the latch signal.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.latch_polarity = polarity;
+    }
+
+    /// Returns the settle time in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.settle.ns()
+    }
+
+    /// Sets the settle time in nanoseconds, 0 disables the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.settle.set_ns(settle_ns);
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay> ShiftTransport
+    for BitBangTransport<ClockPin, LatchPin, DataPin, Delay>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin<Error = ClockPin::Error>,
+    DataPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+{
+    type Error = ClockPin::Error;
+
+    fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.begin()?;
+
+        for data in write {
+            for bit in 0..=7 {
+                self.shift_bit((data & (1 << (7 - bit))) != 0)?;
+            }
+        }
+
+        self.end()
+    }
+}
+
+impl<ClockPin, LatchPin, DataPin, Delay> StepTransport
+    for BitBangTransport<ClockPin, LatchPin, DataPin, Delay>
+where
+    ClockPin: OutputPin,
+    LatchPin: OutputPin<Error = ClockPin::Error>,
+    DataPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+{
+    type Error = ClockPin::Error;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.clock.idle(&mut self.clock_pin)?;
+        self.latch_polarity.set(&mut self.latch_pin, false)?;
+        self.settle.wait();
+
+        Ok(())
+    }
+
+    fn shift_bit(&mut self, state: bool) -> Result<bool, Self::Error> {
+        self.clock.before_edge(&mut self.clock_pin)?;
+
+        if state {
+            self.data_pin.set_high()?;
+        } else {
+            self.data_pin.set_low()?;
+        }
+
+        self.settle.wait();
+        self.clock.edge(&mut self.clock_pin)?;
+        self.settle.wait();
+        self.clock.after_edge(&mut self.clock_pin)?;
+
+        Ok(false)
+    }
+
+    fn end(&mut self) -> Result<(), Self::Error> {
+        self.latch_polarity.set(&mut self.latch_pin, true)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Transport shifting the data out by bit-banging pins into chips without a
+/// storage register, e.g. the 74HC164.
+///
+/// The outputs follow the shifted bits, so they ripple during a transfer.
+/// If the outputs are gated externally, e.g. by an AND gate or the enable
+/// input of a driver, a strobe pin attached with `with_strobe_pin()` closes
+/// the gate during the transfer. Its type defaults to the type of the clock
+/// pin.
+pub struct UnlatchedTransport<ClockPin, DataPin, Delay = NoDelay, StrobePin = ClockPin> {
+    /// Pin for the clock output signal.
+    clock_pin: ClockPin,
+
+    /// Pin for the data output signal.
+    data_pin: DataPin,
+
+    /// Pin for the strobe output signal.
+    strobe_pin: Option<StrobePin>,
+
+    /// Clock configuration.
+    clock: ClockConfig,
+
+    /// Polarity of the strobe signal.
+    strobe_polarity: LatchPolarity,
+
+    /// Settle time of the signals.
+    settle: Settle<Delay>,
+}
+
+impl<ClockPin, DataPin> UnlatchedTransport<ClockPin, DataPin>
+where
+    ClockPin: OutputPin,
+    DataPin: OutputPin,
+{
+    /// Creates a new transport by consuming the pins.
+    pub fn new(clock_pin: ClockPin, data_pin: DataPin) -> Self {
+        Self::with_delay(clock_pin, data_pin, NoDelay, 0)
+    }
+}
+
+impl<ClockPin, DataPin, Delay> UnlatchedTransport<ClockPin, DataPin, Delay>
+where
+    ClockPin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new transport by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        data_pin: DataPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
+        Self {
+            clock_pin,
+            data_pin,
+            strobe_pin: None,
+            clock: ClockConfig::default(),
+            strobe_polarity: LatchPolarity::Normal,
+            settle: Settle::new(delay, settle_ns),
+        }
+    }
+}
+
+impl<ClockPin, DataPin, Delay, StrobePin> UnlatchedTransport<ClockPin, DataPin, Delay, StrobePin>
+where
+    ClockPin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+    StrobePin: OutputPin,
+{
+    /// Attaches a strobe pin, replacing any attached before.
+    ///
+    /// The pin is driven low during a transfer and high afterwards, so the
+    /// gated outputs only show the complete data. The pin must share the
+    /// error type of the other pins.
+    pub fn with_strobe_pin<NewStrobePin>(
+        self,
+        strobe_pin: NewStrobePin,
+    ) -> UnlatchedTransport<ClockPin, DataPin, Delay, NewStrobePin>
+    where
+        NewStrobePin: OutputPin,
+    {
+        UnlatchedTransport {
+            clock_pin: self.clock_pin,
+            data_pin: self.data_pin,
+            strobe_pin: Some(strobe_pin),
+            clock: self.clock,
+            strobe_polarity: self.strobe_polarity,
+            settle: self.settle,
+        }
+    }
+
+    /// Detaches the strobe pin and returns it.
+    pub fn take_strobe_pin(&mut self) -> Option<StrobePin> {
+        self.strobe_pin.take()
+    }
+
+    /// Frees the transport and returns the pins.
+    ///
+    /// A strobe pin is dropped, use `take_strobe_pin()` before to get it
+    /// back.
+    pub fn free(self) -> (ClockPin, DataPin) {
+        (self.clock_pin, self.data_pin)
+    }
+
+    /// Frees the transport and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, DataPin, Delay) {
+        (self.clock_pin, self.data_pin, self.settle.into_delay())
+    }
+
+    /// Returns the clock configuration.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.clock
+    }
+
+    /// Sets the clock configuration.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.clock = clock;
+    }
+
+    /// Returns the polarity of the strobe signal.
+    pub fn strobe_polarity(&self) -> LatchPolarity {
+        self.strobe_polarity
+    }
+
+    /// Sets the polarity of the strobe signal.
+    pub fn set_strobe_polarity(&mut self, polarity: LatchPolarity) {
+        self.strobe_polarity = polarity;
+    }
+
+    /// Returns the settle time in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.settle.ns()
+    }
+
+    /// Sets the settle time in nanoseconds, 0 disables the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.settle.set_ns(settle_ns);
+    }
+
+    /// Drives the strobe signal to a level if a strobe pin is attached.
+    fn set_strobe(&mut self, high: bool) -> Result<(), StrobePin::Error> {
+        match &mut self.strobe_pin {
+            Some(strobe_pin) => self.strobe_polarity.set(strobe_pin, high),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<ClockPin, DataPin, Delay, StrobePin> ShiftTransport
+    for UnlatchedTransport<ClockPin, DataPin, Delay, StrobePin>
+where
+    ClockPin: OutputPin,
+    DataPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+    StrobePin: OutputPin<Error = ClockPin::Error>,
+{
+    type Error = ClockPin::Error;
+
+    fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.begin()?;
+
+        for data in write {
+            for bit in 0..=7 {
+                self.shift_bit((data & (1 << (7 - bit))) != 0)?;
+            }
+        }
+
+        self.end()
+    }
+}
+
+impl<ClockPin, DataPin, Delay, StrobePin> StepTransport
+    for UnlatchedTransport<ClockPin, DataPin, Delay, StrobePin>
+where
+    ClockPin: OutputPin,
+    DataPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+    StrobePin: OutputPin<Error = ClockPin::Error>,
+{
+    type Error = ClockPin::Error;
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.clock.idle(&mut self.clock_pin)?;
+        self.set_strobe(false)?;
+        self.settle.wait();
+
+        Ok(())
+    }
+
+    fn shift_bit(&mut self, state: bool) -> Result<bool, Self::Error> {
+        self.clock.before_edge(&mut self.clock_pin)?;
+
+        if state {
+            self.data_pin.set_high()?;
+        } else {
+            self.data_pin.set_low()?;
+        }
+
+        self.settle.wait();
+        self.clock.edge(&mut self.clock_pin)?;
+        self.settle.wait();
+        self.clock.after_edge(&mut self.clock_pin)?;
+
+        Ok(false)
+    }
+
+    fn end(&mut self) -> Result<(), Self::Error> {
+        self.settle.wait();
+        self.set_strobe(true)
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
-/// Chain of SIPO shift registers.
-pub struct Chain<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize> {
+/// Direction of a shift by `shift_outputs()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ShiftDirection {
+    /// From QA towards QD of the 74HC194, to the next higher pin.
+    Right,
+
+    /// From QD towards QA of the 74HC194, to the next lower pin.
+    Left,
+}
+
+/// Transport shifting the data out by bit-banging pins into universal shift
+/// registers with mode pins, e.g. the 74HC194.
+///
+/// The chips are cascaded from QD to DSR for shifting right, and from QA to
+/// DSL of the previous chip for shifting left. Two 4-bit chips make up one
+/// byte of the chain, with QA of the first chip as pin 0. The outputs ripple
+/// during a transfer, as the chips have no storage register.
+///
+/// Transfers shift right, while `step()` shifts in either direction. For
+/// shifting left, the DSL pin of the last chip is attached with
+/// `with_left_data_pin()`, its type defaults to the type of the data pin.
+pub struct UniversalTransport<ClockPin, ModePin, DataPin, Delay = NoDelay, LeftDataPin = DataPin> {
     /// Pin for the clock output signal.
     clock_pin: ClockPin,
 
-    /// Pin for the latch output signal.
-    latch_pin: LatchPin,
+    /// Pin for the S0 mode output signal.
+    s0_pin: ModePin,
 
-    /// Pin for the data output signal.
+    /// Pin for the S1 mode output signal.
+    s1_pin: ModePin,
+
+    /// Pin for the data output signal for shifting right.
     data_pin: DataPin,
 
-    /// Buffer storing the data to output.
-    data_buffer: [u8; CHAIN_LENGTH],
+    /// Pin for the data output signal for shifting left.
+    left_data_pin: Option<LeftDataPin>,
+
+    /// Clock configuration.
+    clock: ClockConfig,
+
+    /// Settle time of the signals.
+    settle: Settle<Delay>,
 }
 
-impl<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize>
-    Chain<ClockPin, LatchPin, DataPin, CHAIN_LENGTH>
+impl<ClockPin, ModePin, DataPin> UniversalTransport<ClockPin, ModePin, DataPin>
 where
     ClockPin: OutputPin,
-    LatchPin: OutputPin,
+    ModePin: OutputPin,
     DataPin: OutputPin,
 {
-    /// Creates a new chain by consuming the pins.
-    pub fn new(clock_pin: ClockPin, latch_pin: LatchPin, data_pin: DataPin) -> Self {
+    /// Creates a new transport by consuming the pins.
+    pub fn new(clock_pin: ClockPin, s0_pin: ModePin, s1_pin: ModePin, data_pin: DataPin) -> Self {
+        Self::with_delay(clock_pin, s0_pin, s1_pin, data_pin, NoDelay, 0)
+    }
+}
+
+impl<ClockPin, ModePin, DataPin, Delay> UniversalTransport<ClockPin, ModePin, DataPin, Delay>
+where
+    ClockPin: OutputPin,
+    ModePin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+{
+    /// Creates a new transport by consuming the pins and a delay waiting
+    /// `settle_ns` nanoseconds around each signal change.
+    pub fn with_delay(
+        clock_pin: ClockPin,
+        s0_pin: ModePin,
+        s1_pin: ModePin,
+        data_pin: DataPin,
+        delay: Delay,
+        settle_ns: u32,
+    ) -> Self {
         Self {
             clock_pin,
-            latch_pin,
+            s0_pin,
+            s1_pin,
             data_pin,
-            data_buffer: [0; CHAIN_LENGTH],
+            left_data_pin: None,
+            clock: ClockConfig::default(),
+            settle: Settle::new(delay, settle_ns),
         }
     }
+}
 
-    /// Frees the chain and returns the pins.
-    pub fn free(self) -> (ClockPin, LatchPin, DataPin) {
-        (self.clock_pin, self.latch_pin, self.data_pin)
+impl<ClockPin, ModePin, DataPin, Delay, LeftDataPin>
+    UniversalTransport<ClockPin, ModePin, DataPin, Delay, LeftDataPin>
+where
+    ClockPin: OutputPin,
+    ModePin: OutputPin,
+    DataPin: OutputPin,
+    Delay: DelayNs,
+    LeftDataPin: OutputPin,
+{
+    /// Attaches a pin driving DSL of the last chip for shifting left,
+    /// replacing any attached before.
+    ///
+    /// The pin must share the error type of the other pins.
+    pub fn with_left_data_pin<NewLeftDataPin>(
+        self,
+        left_data_pin: NewLeftDataPin,
+    ) -> UniversalTransport<ClockPin, ModePin, DataPin, Delay, NewLeftDataPin>
+    where
+        NewLeftDataPin: OutputPin,
+    {
+        UniversalTransport {
+            clock_pin: self.clock_pin,
+            s0_pin: self.s0_pin,
+            s1_pin: self.s1_pin,
+            data_pin: self.data_pin,
+            left_data_pin: Some(left_data_pin),
+            clock: self.clock,
+            settle: self.settle,
+        }
     }
 
-    /// Updates the chain by shifting the data from the buffer into the chips.
-    pub fn update(&mut self) {
-        self.latch_pin.set_low().ok();
+    /// Detaches the left data pin and returns it.
+    pub fn take_left_data_pin(&mut self) -> Option<LeftDataPin> {
+        self.left_data_pin.take()
+    }
 
-        for data in self.data_buffer {
-            for bit in 0..=7 {
-                self.clock_pin.set_low().ok();
+    /// Frees the transport and returns the clock, S0, S1 and data pins.
+    ///
+    /// A left data pin is dropped, use `take_left_data_pin()` before to get
+    /// it back.
+    pub fn free(self) -> (ClockPin, ModePin, ModePin, DataPin) {
+        (self.clock_pin, self.s0_pin, self.s1_pin, self.data_pin)
+    }
+
+    /// Frees the transport and returns the pins and the delay.
+    pub fn free_with_delay(self) -> (ClockPin, ModePin, ModePin, DataPin, Delay) {
+        (
+            self.clock_pin,
+            self.s0_pin,
+            self.s1_pin,
+            self.data_pin,
+            self.settle.into_delay(),
+        )
+    }
+
+    /// Returns the clock configuration.
+    pub fn clock_config(&self) -> ClockConfig {
+        self.clock
+    }
+
+    /// Sets the clock configuration.
+    pub fn set_clock_config(&mut self, clock: ClockConfig) {
+        self.clock = clock;
+    }
+
+    /// Returns the settle time in nanoseconds.
+    pub fn settle_ns(&self) -> u32 {
+        self.settle.ns()
+    }
+
+    /// Sets the settle time in nanoseconds, 0 disables the delay.
+    pub fn set_settle_ns(&mut self, settle_ns: u32) {
+        self.settle.set_ns(settle_ns);
+    }
+}
+
+impl<ClockPin, ModePin, DataPin, Delay, LeftDataPin>
+    UniversalTransport<ClockPin, ModePin, DataPin, Delay, LeftDataPin>
+where
+    ClockPin: OutputPin,
+    ModePin: OutputPin<Error = ClockPin::Error>,
+    DataPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+    LeftDataPin: OutputPin<Error = ClockPin::Error>,
+{
+    /// Shifts the chips by one position in a direction, returning the state
+    /// shifted in.
+    ///
+    /// Shifting left without a left data pin shifts in a low state.
+    pub fn step(
+        &mut self,
+        direction: ShiftDirection,
+        state: bool,
+    ) -> Result<bool, ClockPin::Error> {
+        self.clock.idle(&mut self.clock_pin)?;
+        self.clock.before_edge(&mut self.clock_pin)?;
 
-                let state = (data & (1 << (7 - bit))) != 0;
+        let state = match direction {
+            ShiftDirection::Right => {
+                self.set_mode(true, false)?;
+                set_level(&mut self.data_pin, state)?;
+                state
+            }
+            ShiftDirection::Left => {
+                self.set_mode(false, true)?;
 
-                if state {
-                    self.data_pin.set_high().ok();
-                } else {
-                    self.data_pin.set_low().ok();
+                match &mut self.left_data_pin {
+                    Some(left_data_pin) => {
+                        set_level(left_data_pin, state)?;
+                        state
+                    }
+                    None => false,
                 }
+            }
+        };
+
+        self.settle.wait();
+        self.clock.edge(&mut self.clock_pin)?;
+        self.settle.wait();
+        self.clock.after_edge(&mut self.clock_pin)?;
+        self.set_mode(false, false)?;
+
+        Ok(state)
+    }
+
+    /// Sets the mode pins to the levels of S0 and S1.
+    fn set_mode(&mut self, s0: bool, s1: bool) -> Result<(), ClockPin::Error> {
+        set_level(&mut self.s0_pin, s0)?;
+        set_level(&mut self.s1_pin, s1)
+    }
+}
+
+impl<ClockPin, ModePin, DataPin, Delay, LeftDataPin> ShiftTransport
+    for UniversalTransport<ClockPin, ModePin, DataPin, Delay, LeftDataPin>
+where
+    ClockPin: OutputPin,
+    ModePin: OutputPin<Error = ClockPin::Error>,
+    DataPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+    LeftDataPin: OutputPin<Error = ClockPin::Error>,
+{
+    type Error = ClockPin::Error;
 
-                self.clock_pin.set_high().ok();
+    fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.begin()?;
+
+        for data in write {
+            for bit in 0..=7 {
+                self.shift_bit((data & (1 << (7 - bit))) != 0)?;
             }
         }
 
-        self.latch_pin.set_high().ok();
+        self.end()
     }
 }
 
-impl<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize> SetOutput
-    for Chain<ClockPin, LatchPin, DataPin, CHAIN_LENGTH>
+impl<ClockPin, ModePin, DataPin, Delay, LeftDataPin> StepTransport
+    for UniversalTransport<ClockPin, ModePin, DataPin, Delay, LeftDataPin>
+where
+    ClockPin: OutputPin,
+    ModePin: OutputPin<Error = ClockPin::Error>,
+    DataPin: OutputPin<Error = ClockPin::Error>,
+    Delay: DelayNs,
+    LeftDataPin: OutputPin<Error = ClockPin::Error>,
 {
-    /// Sets the output state for a pin.
-    ///
-    /// The output state is buffered and not set immediately because the bits
-    /// have to be shifted out by calling `update()` first.
-    fn set_output(&mut self, pin: usize, state: bool) -> Result<(), Error> {
-        if pin >= CHAIN_LENGTH * 8 {
-            return Err(Error::PinOutOfRange);
-        }
+    type Error = ClockPin::Error;
 
-        self.set_output_unchecked(pin, state);
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.clock.idle(&mut self.clock_pin)?;
+        self.set_mode(true, false)?;
+        self.settle.wait();
 
         Ok(())
     }
 
-    /// Sets the output state for a pin without pin boundary checks.
-    ///
-    /// The output state is buffered and not set immediately because the bits
-    /// have to be shifted out by calling `update()` first.
-    fn set_output_unchecked(&mut self, pin: usize, state: bool) {
-        // Calculate index and bit position within buffer array
-        let index = CHAIN_LENGTH - (pin / 8) - 1;
-        let bit = pin % 8;
+    fn shift_bit(&mut self, state: bool) -> Result<bool, Self::Error> {
+        self.clock.before_edge(&mut self.clock_pin)?;
+        set_level(&mut self.data_pin, state)?;
+        self.settle.wait();
+        self.clock.edge(&mut self.clock_pin)?;
+        self.settle.wait();
+        self.clock.after_edge(&mut self.clock_pin)?;
 
-        if state {
-            self.data_buffer[index] |= 1 << bit;
-        } else {
-            self.data_buffer[index] &= !(1 << bit);
+        Ok(false)
+    }
+
+    fn end(&mut self) -> Result<(), Self::Error> {
+        self.settle.wait();
+        self.set_mode(false, false)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Transport writing the data by a hardware SPI bus.
+///
+/// The bus must be configured for SPI mode 0 with MSB first. Clock and data
+/// are connected to SCK and MOSI, the latch signal is driven by a separate pin.
+pub struct SpiTransport<Spi, LatchPin> {
+    /// SPI bus for the clock and data output signals.
+    spi: Spi,
+
+    /// Pin for the latch output signal.
+    latch_pin: LatchPin,
+
+    /// Polarity of the latch signal.
+    latch_polarity: LatchPolarity,
+}
+
+impl<Spi, LatchPin> SpiTransport<Spi, LatchPin> {
+    /// Creates a new transport by consuming the SPI bus and the latch pin.
+    pub fn new(spi: Spi, latch_pin: LatchPin) -> Self {
+        Self {
+            spi,
+            latch_pin,
+            latch_polarity: LatchPolarity::Normal,
         }
     }
+
+    /// Frees the transport and returns the SPI bus and the latch pin.
+    pub fn free(self) -> (Spi, LatchPin) {
+        (self.spi, self.latch_pin)
+    }
+
+    /// Returns the polarity of the latch signal.
+    pub fn latch_polarity(&self) -> LatchPolarity {
+        self.latch_polarity
+    }
+
+    /// Sets the polarity of the latch signal.
+    pub fn set_latch_polarity(&mut self, polarity: LatchPolarity) {
+        self.latch_polarity = polarity;
+    }
 }
 
-impl<ClockPin, LatchPin, DataPin, const CHAIN_LENGTH: usize> Length
-    for Chain<ClockPin, LatchPin, DataPin, CHAIN_LENGTH>
+impl<Spi, LatchPin> ShiftTransport for SpiTransport<Spi, LatchPin>
+where
+    Spi: SpiBus,
+    LatchPin: OutputPin,
 {
-    /// Returns the chain length.
-    fn len(&self) -> usize {
-        CHAIN_LENGTH
+    type Error = SpiError<Spi::Error, LatchPin::Error>;
+
+    fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.latch_polarity
+            .set(&mut self.latch_pin, false)
+            .map_err(SpiError::Pin)?;
+
+        self.spi.write(write).map_err(SpiError::Spi)?;
+        self.spi.flush().map_err(SpiError::Spi)?;
+
+        self.latch_polarity
+            .set(&mut self.latch_pin, true)
+            .map_err(SpiError::Pin)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Spi, LatchPin> AsyncShiftTransport for SpiTransport<Spi, LatchPin>
+where
+    Spi: embedded_hal_async::spi::SpiBus,
+    LatchPin: OutputPin,
+{
+    type Error = SpiError<Spi::Error, LatchPin::Error>;
+
+    /// Writes the data in a single asynchronous transfer.
+    ///
+    /// The latch signal is toggled after the transfer has completed.
+    async fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.latch_polarity
+            .set(&mut self.latch_pin, false)
+            .map_err(SpiError::Pin)?;
+
+        self.spi.write(write).await.map_err(SpiError::Spi)?;
+        self.spi.flush().await.map_err(SpiError::Spi)?;
+
+        self.latch_polarity
+            .set(&mut self.latch_pin, true)
+            .map_err(SpiError::Pin)
     }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Output pin of a chip in the chain.
+#[cfg_attr(not(any(feature = "eh1", feature = "eh02")), allow(dead_code))]
 pub struct Pin<'a, Chain> {
     /// Reference to the chain.
     chain: &'a RefCell<Chain>,
@@ -140,7 +2567,7 @@ where
 {
     /// Creates a new output pin.
     pub fn new(chain: &'a RefCell<Chain>, pin: usize) -> Result<Self, Error> {
-        if pin >= chain.borrow().len() * 8 {
+        if pin >= chain.borrow().output_len() * 8 {
             return Err(Error::PinOutOfRange);
         }
 
@@ -148,10 +2575,12 @@ where
     }
 }
 
-impl<Chain> ErrorType for Pin<'_, Chain> {
-    type Error = core::convert::Infallible;
+#[cfg(feature = "eh1")]
+impl<Chain> embedded_hal::digital::ErrorType for Pin<'_, Chain> {
+    type Error = Infallible;
 }
 
+#[cfg(feature = "eh1")]
 impl<Chain> OutputPin for Pin<'_, Chain>
 where
     Chain: SetOutput,
@@ -168,3 +2597,196 @@ where
         Ok(())
     }
 }
+
+#[cfg(feature = "eh1")]
+impl<Chain> embedded_hal::digital::StatefulOutputPin for Pin<'_, Chain>
+where
+    Chain: GetOutput + SetOutput,
+{
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.chain.borrow().get_output_unchecked(self.pin))
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.chain.borrow().get_output_unchecked(self.pin))
+    }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.chain.borrow_mut().toggle_output_unchecked(self.pin);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<Chain> embedded_hal_02::digital::v2::OutputPin for Pin<'_, Chain>
+where
+    Chain: SetOutput,
+{
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.chain
+            .borrow_mut()
+            .set_output_unchecked(self.pin, false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.chain.borrow_mut().set_output_unchecked(self.pin, true);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<Chain> embedded_hal_02::digital::v2::StatefulOutputPin for Pin<'_, Chain>
+where
+    Chain: GetOutput + SetOutput,
+{
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.chain.borrow().get_output_unchecked(self.pin))
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.chain.borrow().get_output_unchecked(self.pin))
+    }
+}
+
+#[cfg(feature = "eh02")]
+impl<Chain> embedded_hal_02::digital::v2::ToggleableOutputPin for Pin<'_, Chain>
+where
+    Chain: GetOutput + SetOutput,
+{
+    type Error = Infallible;
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.chain.borrow_mut().toggle_output_unchecked(self.pin);
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Recorder;
+
+    /// Returns a chain with all wire encoding options changed.
+    fn encoded_chain() -> Chain<Recorder, 4> {
+        let mut chain = Chain::with_transport(Recorder::default());
+        chain.set_bit_order(BitOrder::LsbFirst);
+        chain.set_chip_order(ChipOrder::FarthestFirst);
+        assert_eq!(chain.set_register_bits(16), Ok(()));
+        assert_eq!(chain.set_inverted_output(3, true), Ok(()));
+        chain
+    }
+
+    /// Polls an update in steps of `bits` bits, returning the number of
+    /// calls.
+    fn poll(chain: &mut Chain<Recorder, 4>, bits: usize) -> usize {
+        let mut calls = 1;
+
+        while chain.poll_update(bits) == Err(nb::Error::WouldBlock) {
+            calls += 1;
+        }
+
+        calls
+    }
+
+    #[test]
+    fn update_shifts_farthest_chip_first() {
+        let mut chain = Chain::<_, 2>::with_transport(Recorder::default());
+        assert_eq!(chain.set_output_word(0, 0x1234u16), Ok(()));
+        assert!(chain.is_dirty());
+
+        assert_eq!(chain.update(), Ok(()));
+        assert_eq!(chain.transport.written(), [0x12, 0x34]);
+        assert_eq!(chain.transport.transfers, 1);
+        assert!(!chain.is_dirty());
+    }
+
+    #[test]
+    fn update_is_wire_encoded() {
+        let mut chain = encoded_chain();
+        assert_eq!(chain.set_output_word(0, 0x8421_F00Du32), Ok(()));
+
+        assert_eq!(chain.update(), Ok(()));
+        assert_eq!(chain.transport.written(), [0xA0, 0x0F, 0x84, 0x21]);
+    }
+
+    #[test]
+    fn poll_update_resumes() {
+        let mut chain = encoded_chain();
+        assert_eq!(chain.set_output_word(0, 0x8421_F00Du32), Ok(()));
+
+        assert_eq!(poll(&mut chain, 5), 7);
+        assert_eq!(chain.transport.written(), [0xA0, 0x0F, 0x84, 0x21]);
+        assert_eq!(chain.transport.transfers, 1);
+    }
+
+    #[test]
+    fn failed_poll_update_restarts() {
+        let mut chain = encoded_chain();
+        assert_eq!(chain.set_output_word(0, 0x8421_F00Du32), Ok(()));
+        assert_eq!(chain.poll_update(8), Err(nb::Error::WouldBlock));
+
+        chain.transport.fail = true;
+        assert!(matches!(chain.poll_update(8), Err(nb::Error::Other(_))));
+
+        chain.transport.fail = false;
+        assert_eq!(poll(&mut chain, 8), 4);
+        assert_eq!(chain.transport.written(), [0xA0, 0x0F, 0x84, 0x21]);
+    }
+
+    #[test]
+    fn words_are_wire_encoded() {
+        let mut chain = Chain::<_, 3>::with_transport(Recorder::default());
+        assert_eq!(chain.set_output_word(0, 0x3456u16), Ok(()));
+        assert_eq!(chain.set_output_word(16, 0x12u8), Ok(()));
+
+        let mut words = [0u16; 2];
+        assert_eq!(chain.encode_output(&mut words), Ok(2));
+        assert_eq!(words, [0x0012, 0x3456]);
+
+        let mut words = [0u64; 1];
+        assert_eq!(chain.encode_output(&mut words), Ok(1));
+        assert_eq!(words, [0x12_3456]);
+
+        assert_eq!(
+            chain.encode_output(&mut [0u8; 2]),
+            Err(Error::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn word_from_be_slice() {
+        assert_eq!(u16::from_be_slice(&[0x12, 0x34]), Some(0x1234));
+        assert_eq!(u16::from_be_slice(&[0x12]), None);
+        assert_eq!(u32::from_be_slice(&[0x12, 0x34, 0x56, 0x78, 0x9A]), None);
+    }
+
+    #[cfg(feature = "embedded-dma")]
+    #[test]
+    fn dma_buffer_is_wire_encoded() {
+        use embedded_dma::ReadTarget;
+
+        let mut chain = encoded_chain();
+        assert_eq!(chain.set_output_word(0, 0x8421_F00Du32), Ok(()));
+        assert!(chain.update().is_ok());
+
+        let mut written = [0; 4];
+        written.copy_from_slice(chain.transport.written());
+        let transfer = chain.prepare_dma();
+        let (ptr, len) = transfer.as_read_buffer();
+        // SAFETY: The buffer is borrowed by the transfer, which is alive
+        // while the slice is.
+        let data = unsafe { core::slice::from_raw_parts(ptr, len) };
+        assert_eq!(data, written);
+
+        transfer.finish();
+        assert!(!chain.is_dirty());
+
+        let _ = chain.prepare_dma();
+        assert!(chain.is_dirty());
+    }
+}
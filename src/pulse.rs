@@ -0,0 +1,51 @@
+//! Tracking of timed output pulses
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Remaining ticks of the pulses of the pins of `CHAIN_LENGTH` chips.
+pub(crate) struct Pulses<const CHAIN_LENGTH: usize> {
+    /// Ticks until the pulse of each pin ends, 0 if not pulsing.
+    remaining: [[u16; 8]; CHAIN_LENGTH],
+}
+
+impl<const CHAIN_LENGTH: usize> Pulses<CHAIN_LENGTH> {
+    /// Creates new pulses, none running.
+    pub(crate) const fn new() -> Self {
+        Self {
+            remaining: [[0; 8]; CHAIN_LENGTH],
+        }
+    }
+
+    /// Starts a pulse of a pin in range, lasting at least 1 tick.
+    pub(crate) fn start(&mut self, pin: usize, ticks: u16) {
+        self.remaining[pin / 8][pin % 8] = ticks.max(1);
+    }
+
+    /// Stops the pulse of a pin in range.
+    pub(crate) fn cancel(&mut self, pin: usize) {
+        self.remaining[pin / 8][pin % 8] = 0;
+    }
+
+    /// Returns `true` if a pin in range is pulsing.
+    pub(crate) fn is_running(&self, pin: usize) -> bool {
+        self.remaining[pin / 8][pin % 8] != 0
+    }
+
+    /// Advances the pulses by one tick and calls `end` with each pin whose
+    /// pulse ended.
+    pub(crate) fn tick(&mut self, mut end: impl FnMut(usize)) {
+        for (index, remaining) in self.remaining.iter_mut().enumerate() {
+            for (bit, remaining) in remaining.iter_mut().enumerate() {
+                if *remaining == 0 {
+                    continue;
+                }
+
+                *remaining -= 1;
+
+                if *remaining == 0 {
+                    end(index * 8 + bit);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,784 @@
+//! Software PWM for output pins
+//!
+//! A [`SoftPwm`] engine holds the duty cycles of a fixed set of output pins
+//! and writes the pin states of the current PWM step into an output chain.
+//! Calling [`SoftPwm::step()`] before each `update()` of the chain results
+//! in a PWM frequency of the update rate divided by the maximum duty cycle.
+//! Alternatively, [`SoftPwm::tick()`] does both, e.g. from a periodic timer.
+//!
+//! A [`Bam`] engine uses Bit Angle Modulation instead, shifting only 8
+//! frames per cycle for 8-bit brightness. Frame `n` holds bit `n` of each
+//! brightness level and lasts `2^n` ticks.
+//!
+//! Both engines can apply a [`Gamma`] curve to the commanded values, so the
+//! perceived brightness of LEDs scales linearly with them. A [`Breathing`]
+//! modulator generates the slowly pulsing levels of standby LEDs.
+
+use core::cell::RefCell;
+
+#[cfg(feature = "eh1")]
+use core::convert::Infallible;
+
+use crate::{output::SetOutput, Error, Length, Update};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Gamma 2.2 curve, scaled to 16 bits.
+const GAMMA_2_2: [u16; 256] = [
+    0, 0, 2, 4, 7, 11, 17, 24, 32, 42, 53, 65, 79, 94, 111, 129, 148, 169, 192, 216, 242, 270, 299,
+    330, 362, 396, 432, 469, 508, 549, 591, 635, 681, 729, 779, 830, 883, 938, 995, 1053, 1113,
+    1175, 1239, 1305, 1373, 1443, 1514, 1587, 1663, 1740, 1819, 1900, 1983, 2068, 2155, 2243, 2334,
+    2427, 2521, 2618, 2717, 2817, 2920, 3024, 3131, 3240, 3350, 3463, 3578, 3694, 3813, 3934, 4057,
+    4182, 4309, 4438, 4570, 4703, 4838, 4976, 5115, 5257, 5401, 5547, 5695, 5845, 5998, 6152, 6309,
+    6468, 6629, 6792, 6957, 7124, 7294, 7466, 7640, 7816, 7994, 8175, 8358, 8543, 8730, 8919, 9111,
+    9305, 9501, 9699, 9900, 10102, 10307, 10515, 10724, 10936, 11150, 11366, 11585, 11806, 12029,
+    12254, 12482, 12712, 12944, 13179, 13416, 13655, 13896, 14140, 14386, 14635, 14885, 15138,
+    15394, 15652, 15912, 16174, 16439, 16706, 16975, 17247, 17521, 17798, 18077, 18358, 18642,
+    18928, 19216, 19507, 19800, 20095, 20393, 20694, 20996, 21301, 21609, 21919, 22231, 22546,
+    22863, 23182, 23504, 23829, 24156, 24485, 24817, 25151, 25487, 25826, 26168, 26512, 26858,
+    27207, 27558, 27912, 28268, 28627, 28988, 29351, 29717, 30086, 30457, 30830, 31206, 31585,
+    31966, 32349, 32735, 33124, 33514, 33908, 34304, 34702, 35103, 35507, 35913, 36321, 36732,
+    37146, 37562, 37981, 38402, 38825, 39252, 39680, 40112, 40546, 40982, 41421, 41862, 42306,
+    42753, 43202, 43654, 44108, 44565, 45025, 45487, 45951, 46418, 46888, 47360, 47835, 48313,
+    48793, 49275, 49761, 50249, 50739, 51232, 51728, 52226, 52727, 53230, 53736, 54245, 54756,
+    55270, 55787, 56306, 56828, 57352, 57879, 58409, 58941, 59476, 60014, 60554, 61097, 61642,
+    62190, 62741, 63295, 63851, 64410, 64971, 65535,
+];
+
+/// Gamma 2.8 curve, scaled to 16 bits.
+const GAMMA_2_8: [u16; 256] = [
+    0, 0, 0, 0, 1, 1, 2, 3, 4, 6, 8, 10, 13, 16, 19, 24, 28, 33, 39, 46, 53, 60, 69, 78, 88, 98,
+    110, 122, 135, 149, 164, 179, 196, 214, 232, 252, 273, 295, 317, 341, 366, 393, 420, 449, 478,
+    510, 542, 575, 610, 647, 684, 723, 764, 806, 849, 894, 940, 988, 1037, 1088, 1140, 1194, 1250,
+    1307, 1366, 1427, 1489, 1553, 1619, 1686, 1756, 1827, 1900, 1975, 2051, 2130, 2210, 2293, 2377,
+    2463, 2552, 2642, 2734, 2829, 2925, 3024, 3124, 3227, 3332, 3439, 3548, 3660, 3774, 3890, 4008,
+    4128, 4251, 4376, 4504, 4634, 4766, 4901, 5038, 5177, 5319, 5464, 5611, 5760, 5912, 6067, 6224,
+    6384, 6546, 6711, 6879, 7049, 7222, 7397, 7576, 7757, 7941, 8128, 8317, 8509, 8704, 8902, 9103,
+    9307, 9514, 9723, 9936, 10151, 10370, 10591, 10816, 11043, 11274, 11507, 11744, 11984, 12227,
+    12473, 12722, 12975, 13230, 13489, 13751, 14017, 14285, 14557, 14833, 15111, 15393, 15678,
+    15967, 16259, 16554, 16853, 17155, 17461, 17770, 18083, 18399, 18719, 19042, 19369, 19700,
+    20034, 20372, 20713, 21058, 21407, 21759, 22115, 22475, 22838, 23206, 23577, 23952, 24330,
+    24713, 25099, 25489, 25884, 26282, 26683, 27089, 27499, 27913, 28330, 28752, 29178, 29608,
+    30041, 30479, 30921, 31367, 31818, 32272, 32730, 33193, 33660, 34131, 34606, 35085, 35569,
+    36057, 36549, 37046, 37547, 38052, 38561, 39075, 39593, 40116, 40643, 41175, 41711, 42251,
+    42796, 43346, 43899, 44458, 45021, 45588, 46161, 46737, 47319, 47905, 48495, 49091, 49691,
+    50295, 50905, 51519, 52138, 52761, 53390, 54023, 54661, 55303, 55951, 56604, 57261, 57923,
+    58590, 59262, 59939, 60621, 61308, 62000, 62697, 63399, 64106, 64818, 65535,
+];
+
+/// Gamma correction applied to duty cycles and brightness levels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Gamma {
+    /// No correction.
+    #[default]
+    Linear,
+
+    /// Gamma 2.2, the sRGB approximation.
+    Gamma22,
+
+    /// Gamma 2.8, commonly used for LEDs.
+    Gamma28,
+
+    /// Custom curve mapping 256 evenly spaced inputs to outputs scaled to
+    /// 16 bits.
+    Table(&'static [u16; 256]),
+}
+
+impl Gamma {
+    /// Returns the corrected value of `value` in the range `0..=max`.
+    ///
+    /// Values between the entries of the table are interpolated.
+    pub fn apply(self, value: u16, max: u16) -> u16 {
+        let table = match self {
+            Self::Linear => return value.min(max),
+            Self::Gamma22 => &GAMMA_2_2,
+            Self::Gamma28 => &GAMMA_2_8,
+            Self::Table(table) => table,
+        };
+
+        if max == 0 {
+            return 0;
+        }
+
+        let position = u32::from(value.min(max)) * 255 * 256 / u32::from(max);
+        let index = (position / 256) as usize;
+        let fraction = position % 256;
+
+        let low = u32::from(table[index]);
+        let high = u32::from(table[(index + 1).min(255)]);
+        let corrected = (low * (256 - fraction) + high * fraction) / 256;
+
+        u16::try_from((corrected * u32::from(max) + 32767) / 65535).unwrap_or(max)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Software PWM engine for `CHANNELS` output pins.
+pub struct SoftPwm<const CHANNELS: usize> {
+    /// Output pin numbers of the channels.
+    pins: [usize; CHANNELS],
+
+    /// Duty cycles of the channels.
+    duty_cycles: [u16; CHANNELS],
+
+    /// Duty cycles of the channels after gamma correction.
+    outputs: [u16; CHANNELS],
+
+    /// Gamma correction of the duty cycles.
+    gamma: Gamma,
+
+    /// Maximum duty cycle, equal to the number of steps per period.
+    max_duty_cycle: u16,
+
+    /// Current step within the period.
+    counter: u16,
+}
+
+impl<const CHANNELS: usize> SoftPwm<CHANNELS> {
+    /// Creates a new engine driving `pins` of `chain` with all duty cycles
+    /// set to 0.
+    ///
+    /// Returns an error if a pin is out of range for the chain.
+    /// `max_duty_cycle` is clamped to a minimum of 1.
+    pub fn new(
+        chain: &impl Length,
+        pins: [usize; CHANNELS],
+        max_duty_cycle: u16,
+    ) -> Result<Self, Error> {
+        if pins.iter().any(|&pin| pin >= chain.output_len() * 8) {
+            return Err(Error::PinOutOfRange);
+        }
+
+        Ok(Self {
+            pins,
+            duty_cycles: [0; CHANNELS],
+            outputs: [0; CHANNELS],
+            gamma: Gamma::Linear,
+            max_duty_cycle: max_duty_cycle.max(1),
+            counter: 0,
+        })
+    }
+
+    /// Returns the maximum duty cycle.
+    pub fn max_duty_cycle(&self) -> u16 {
+        self.max_duty_cycle
+    }
+
+    /// Returns the duty cycle of a channel.
+    pub fn duty_cycle(&self, channel: usize) -> Result<u16, Error> {
+        self.duty_cycles
+            .get(channel)
+            .copied()
+            .ok_or(Error::PinOutOfRange)
+    }
+
+    /// Sets the duty cycle of a channel, clamped to the maximum duty cycle.
+    pub fn set_duty_cycle(&mut self, channel: usize, duty: u16) -> Result<(), Error> {
+        let duty = duty.min(self.max_duty_cycle);
+        let duty_cycle = self
+            .duty_cycles
+            .get_mut(channel)
+            .ok_or(Error::PinOutOfRange)?;
+        *duty_cycle = duty;
+        self.outputs[channel] = self.gamma.apply(duty, self.max_duty_cycle);
+
+        Ok(())
+    }
+
+    /// Returns the gamma correction of the duty cycles.
+    pub fn gamma(&self) -> Gamma {
+        self.gamma
+    }
+
+    /// Sets the gamma correction of the duty cycles, `Gamma::Linear` by
+    /// default.
+    ///
+    /// The duty cycles returned by `duty_cycle()` stay uncorrected.
+    pub fn set_gamma(&mut self, gamma: Gamma) {
+        self.gamma = gamma;
+
+        for (output, &duty_cycle) in self.outputs.iter_mut().zip(self.duty_cycles.iter()) {
+            *output = gamma.apply(duty_cycle, self.max_duty_cycle);
+        }
+    }
+
+    /// Returns the duty cycle of the channel driving an output pin.
+    pub fn pin_duty_cycle(&self, pin: usize) -> Result<u16, Error> {
+        self.duty_cycle(self.channel_of(pin)?)
+    }
+
+    /// Sets the duty cycle of the channel driving an output pin, clamped to
+    /// the maximum duty cycle.
+    pub fn set_pin_duty_cycle(&mut self, pin: usize, duty: u16) -> Result<(), Error> {
+        self.set_duty_cycle(self.channel_of(pin)?, duty)
+    }
+
+    /// Sets the duty cycles of all channels, clamped to the maximum duty
+    /// cycle.
+    pub fn set_all_duty_cycles(&mut self, duty: u16) {
+        let duty = duty.min(self.max_duty_cycle);
+        self.duty_cycles = [duty; CHANNELS];
+        self.outputs = [self.gamma.apply(duty, self.max_duty_cycle); CHANNELS];
+    }
+
+    /// Returns the channel driving an output pin.
+    fn channel_of(&self, pin: usize) -> Result<usize, Error> {
+        self.pins
+            .iter()
+            .position(|&channel_pin| channel_pin == pin)
+            .ok_or(Error::PinOutOfRange)
+    }
+
+    /// Writes the states of the current step into the chain buffer and
+    /// advances to the next step.
+    pub fn step(&mut self, chain: &mut impl SetOutput) {
+        for (&pin, &duty_cycle) in self.pins.iter().zip(self.outputs.iter()) {
+            chain.set_output_unchecked(pin, duty_cycle > self.counter);
+        }
+
+        self.counter += 1;
+
+        if self.counter >= self.max_duty_cycle {
+            self.counter = 0;
+        }
+    }
+
+    /// Writes the states of the current step into the chain buffer, updates
+    /// the chain and advances to the next step.
+    ///
+    /// Intended to be called periodically, e.g. from a timer interrupt, with
+    /// the PWM frequency being the tick rate divided by the maximum duty
+    /// cycle. The step is also advanced if the update failed.
+    pub fn tick<Chain>(&mut self, chain: &mut Chain) -> Result<(), Chain::Error>
+    where
+        Chain: SetOutput + Update,
+    {
+        self.step(chain);
+        chain.update()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Bit Angle Modulation engine for `CHANNELS` output pins.
+pub struct Bam<const CHANNELS: usize> {
+    /// Output pin numbers of the channels.
+    pins: [usize; CHANNELS],
+
+    /// Brightness levels of the channels.
+    levels: [u8; CHANNELS],
+
+    /// Brightness levels of the channels after gamma correction.
+    outputs: [u8; CHANNELS],
+
+    /// Gamma correction of the brightness levels.
+    gamma: Gamma,
+
+    /// Bit of the levels shown by the next frame.
+    bit: u8,
+
+    /// Ticks until the next frame is due, 0 if due immediately.
+    remaining: u8,
+}
+
+impl<const CHANNELS: usize> Bam<CHANNELS> {
+    /// Creates a new engine driving `pins` of `chain` with all levels set
+    /// to 0.
+    ///
+    /// Returns an error if a pin is out of range for the chain.
+    pub fn new(chain: &impl Length, pins: [usize; CHANNELS]) -> Result<Self, Error> {
+        if pins.iter().any(|&pin| pin >= chain.output_len() * 8) {
+            return Err(Error::PinOutOfRange);
+        }
+
+        Ok(Self {
+            pins,
+            levels: [0; CHANNELS],
+            outputs: [0; CHANNELS],
+            gamma: Gamma::Linear,
+            bit: 0,
+            remaining: 0,
+        })
+    }
+
+    /// Returns the brightness level of a channel.
+    pub fn level(&self, channel: usize) -> Result<u8, Error> {
+        self.levels
+            .get(channel)
+            .copied()
+            .ok_or(Error::PinOutOfRange)
+    }
+
+    /// Sets the brightness level of a channel, taking effect with the
+    /// frame of each bit.
+    pub fn set_level(&mut self, channel: usize, level: u8) -> Result<(), Error> {
+        *self.levels.get_mut(channel).ok_or(Error::PinOutOfRange)? = level;
+        self.outputs[channel] = self.correct(level);
+
+        Ok(())
+    }
+
+    /// Returns the gamma correction of the brightness levels.
+    pub fn gamma(&self) -> Gamma {
+        self.gamma
+    }
+
+    /// Sets the gamma correction of the brightness levels, `Gamma::Linear`
+    /// by default.
+    ///
+    /// The levels returned by `level()` stay uncorrected. Low levels may be
+    /// corrected to 0 due to the 8-bit resolution.
+    pub fn set_gamma(&mut self, gamma: Gamma) {
+        self.gamma = gamma;
+
+        for channel in 0..CHANNELS {
+            self.outputs[channel] = self.correct(self.levels[channel]);
+        }
+    }
+
+    /// Returns the gamma corrected value of a brightness level.
+    fn correct(&self, level: u8) -> u8 {
+        u8::try_from(self.gamma.apply(u16::from(level), 255)).unwrap_or(u8::MAX)
+    }
+
+    /// Returns the brightness level of the channel driving an output pin.
+    pub fn pin_level(&self, pin: usize) -> Result<u8, Error> {
+        self.level(self.channel_of(pin)?)
+    }
+
+    /// Sets the brightness level of the channel driving an output pin.
+    pub fn set_pin_level(&mut self, pin: usize, level: u8) -> Result<(), Error> {
+        self.set_level(self.channel_of(pin)?, level)
+    }
+
+    /// Sets the brightness levels of all channels.
+    pub fn set_all_levels(&mut self, level: u8) {
+        self.levels = [level; CHANNELS];
+        self.outputs = [self.correct(level); CHANNELS];
+    }
+
+    /// Returns the channel driving an output pin.
+    fn channel_of(&self, pin: usize) -> Result<usize, Error> {
+        self.pins
+            .iter()
+            .position(|&channel_pin| channel_pin == pin)
+            .ok_or(Error::PinOutOfRange)
+    }
+
+    /// Writes the states of the next frame into the chain buffer and
+    /// returns its duration in ticks, i.e. the weight of its bit.
+    ///
+    /// Intended for timers whose period can be changed, which are set to
+    /// the returned duration before the chain is updated. A cycle takes
+    /// 255 ticks.
+    pub fn step(&mut self, chain: &mut impl SetOutput) -> u8 {
+        let mask = 1 << self.bit;
+
+        for (&pin, &level) in self.pins.iter().zip(self.outputs.iter()) {
+            chain.set_output_unchecked(pin, level & mask != 0);
+        }
+
+        self.bit = (self.bit + 1) % 8;
+
+        mask
+    }
+
+    /// Shifts the next frame out if the current one has ended and returns
+    /// `true` if the chain was updated.
+    ///
+    /// Intended to be called from a timer with a fixed period of one tick.
+    /// Only 8 of the 255 ticks of a cycle update the chain.
+    pub fn tick<Chain>(&mut self, chain: &mut Chain) -> Result<bool, Chain::Error>
+    where
+        Chain: SetOutput + Update,
+    {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+
+            if self.remaining > 0 {
+                return Ok(false);
+            }
+        }
+
+        self.remaining = self.step(chain);
+        chain.update()?;
+
+        Ok(true)
+    }
+
+    /// Restarts the cycle with the frame of the lowest bit.
+    pub fn reset(&mut self) {
+        self.bit = 0;
+        self.remaining = 0;
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Waveform of a breathing modulator.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Waveform {
+    /// Linear ramps up and down.
+    Triangle,
+
+    /// Raised cosine, slow at the minimum and the maximum.
+    #[default]
+    Sine,
+}
+
+/// Breathing brightness modulator.
+///
+/// Each `tick()` advances the phase, and `level()` returns the brightness at
+/// the phase, starting at 0 and peaking at half the period.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Breathing {
+    /// Ticks per period.
+    period: u32,
+
+    /// Current tick within the period.
+    phase: u32,
+
+    /// Waveform of the modulation.
+    waveform: Waveform,
+}
+
+impl Breathing {
+    /// Creates a new modulator with a sine waveform and a period of
+    /// `period` ticks, clamped to a minimum of 1.
+    pub const fn new(period: u32) -> Self {
+        Self {
+            period: if period > 0 { period } else { 1 },
+            phase: 0,
+            waveform: Waveform::Sine,
+        }
+    }
+
+    /// Returns the ticks per period.
+    pub fn period(&self) -> u32 {
+        self.period
+    }
+
+    /// Sets the ticks per period, clamped to a minimum of 1.
+    ///
+    /// The phase is kept if it is within the new period.
+    pub fn set_period(&mut self, period: u32) {
+        self.period = period.max(1);
+        self.phase %= self.period;
+    }
+
+    /// Returns the waveform.
+    pub fn waveform(&self) -> Waveform {
+        self.waveform
+    }
+
+    /// Sets the waveform, `Waveform::Sine` by default.
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        self.waveform = waveform;
+    }
+
+    /// Returns the current tick within the period.
+    pub fn phase(&self) -> u32 {
+        self.phase
+    }
+
+    /// Sets the current tick within the period, wrapped around it.
+    pub fn set_phase(&mut self, phase: u32) {
+        self.phase = phase % self.period;
+    }
+
+    /// Advances the phase by one tick.
+    pub fn tick(&mut self) {
+        self.phase = (self.phase + 1) % self.period;
+    }
+
+    /// Returns the brightness at the current phase in the range `0..=max`,
+    /// e.g. the maximum duty cycle of a `SoftPwm` or 255 for a `Bam`.
+    pub fn level(&self, max: u16) -> u16 {
+        // Position within the period in 1/65536.
+        let position = (u64::from(self.phase) << 16) / u64::from(self.period);
+
+        let level = match self.waveform {
+            Waveform::Triangle => {
+                if position < 1 << 15 {
+                    position << 1
+                } else {
+                    (1 << 17) - (position << 1)
+                }
+            }
+            Waveform::Sine => {
+                // sin(pi * position) by the approximation of Bhaskara I,
+                // squared to get the raised cosine.
+                let product = (position * ((1 << 16) - position)) >> 16;
+                let sine = ((16 * product) << 16) / ((5 << 16) - 4 * product);
+
+                (sine * sine) >> 16
+            }
+        };
+
+        u16::try_from((level * u64::from(max) + (1 << 15)) >> 16).unwrap_or(max)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Channel of a software PWM engine.
+#[cfg_attr(not(feature = "eh1"), allow(dead_code))]
+pub struct Channel<'a, const CHANNELS: usize> {
+    /// Reference to the engine.
+    pwm: &'a RefCell<SoftPwm<CHANNELS>>,
+
+    /// Channel number within the engine.
+    channel: usize,
+}
+
+impl<'a, const CHANNELS: usize> Channel<'a, CHANNELS> {
+    /// Creates a new channel.
+    pub fn new(pwm: &'a RefCell<SoftPwm<CHANNELS>>, channel: usize) -> Result<Self, Error> {
+        if channel >= CHANNELS {
+            return Err(Error::PinOutOfRange);
+        }
+
+        Ok(Self { pwm, channel })
+    }
+}
+
+#[cfg(feature = "eh1")]
+impl<const CHANNELS: usize> embedded_hal::pwm::ErrorType for Channel<'_, CHANNELS> {
+    type Error = Infallible;
+}
+
+#[cfg(feature = "eh1")]
+impl<const CHANNELS: usize> embedded_hal::pwm::SetDutyCycle for Channel<'_, CHANNELS> {
+    fn max_duty_cycle(&self) -> u16 {
+        self.pwm.borrow().max_duty_cycle()
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        // The channel number is checked by `new()`, so this can't fail.
+        let _ = self.pwm.borrow_mut().set_duty_cycle(self.channel, duty);
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock::Recorder, output, output::GetOutput};
+
+    type Chain = output::Chain<Recorder, 1>;
+
+    /// Returns the buffered states of output pins 0 to 7.
+    fn outputs(chain: &Chain) -> u8 {
+        (0..8).fold(0, |outputs, pin| {
+            outputs | u8::from(chain.get_output_unchecked(pin)) << pin
+        })
+    }
+
+    #[test]
+    fn soft_pwm_steps() -> Result<(), Error> {
+        let mut chain = Chain::with_transport(Recorder::default());
+        assert!(SoftPwm::new(&chain, [8], 4).is_err());
+
+        let mut pwm = SoftPwm::new(&chain, [0, 1, 2], 4)?;
+        pwm.set_duty_cycle(1, 2)?;
+        pwm.set_duty_cycle(2, 5)?;
+        assert_eq!(pwm.duty_cycle(2), Ok(4));
+        assert_eq!(pwm.set_duty_cycle(3, 1), Err(Error::PinOutOfRange));
+
+        let period = [(); 8].map(|_| {
+            pwm.step(&mut chain);
+            outputs(&chain)
+        });
+
+        assert_eq!(
+            period,
+            [0b110, 0b110, 0b100, 0b100, 0b110, 0b110, 0b100, 0b100]
+        );
+
+        assert!(chain.is_dirty());
+        assert!(pwm.tick(&mut chain).is_ok());
+        assert!(!chain.is_dirty());
+        assert_eq!(outputs(&chain), 0b110);
+
+        Ok(())
+    }
+
+    #[test]
+    fn soft_pwm_gamma() -> Result<(), Error> {
+        let chain = Chain::with_transport(Recorder::default());
+        let mut pwm = SoftPwm::new(&chain, [0], 0)?;
+        assert_eq!(pwm.max_duty_cycle(), 1);
+
+        pwm = SoftPwm::new(&chain, [0], 255)?;
+        pwm.set_duty_cycle(0, 128)?;
+        pwm.set_gamma(Gamma::Gamma22);
+
+        assert_eq!(pwm.duty_cycle(0), Ok(128));
+        assert_eq!(pwm.outputs[0], Gamma::Gamma22.apply(128, 255));
+        assert!(pwm.outputs[0] < 64);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "eh1")]
+    #[test]
+    fn channel_sets_duty_cycle() -> Result<(), Error> {
+        use embedded_hal::pwm::SetDutyCycle;
+
+        let chain = Chain::with_transport(Recorder::default());
+        let pwm = RefCell::new(SoftPwm::new(&chain, [0, 1], 255)?);
+        pwm.borrow_mut().set_gamma(Gamma::Gamma22);
+        assert!(Channel::new(&pwm, 2).is_err());
+
+        let mut channel = Channel::new(&pwm, 1)?;
+        assert_eq!(channel.max_duty_cycle(), 255);
+        assert_eq!(channel.set_duty_cycle(300), Ok(()));
+        assert_eq!(pwm.borrow().duty_cycle(1), Ok(255));
+        assert_eq!(channel.set_duty_cycle_fraction(1, 2), Ok(()));
+
+        let pwm = pwm.borrow();
+        assert_eq!(pwm.duty_cycle(0), Ok(0));
+        assert_eq!(pwm.duty_cycle(1), Ok(127));
+        assert_eq!(pwm.outputs[1], Gamma::Gamma22.apply(127, 255));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bam_frame_timing() -> Result<(), Error> {
+        let mut chain = Chain::with_transport(Recorder::default());
+        let mut bam = Bam::new(&chain, [0, 1])?;
+        bam.set_level(0, 0b1010_0101)?;
+        bam.set_level(1, 0xFF)?;
+
+        let mut updates = [0; 9];
+        let mut frames = [0; 9];
+        let mut count = 0;
+
+        for tick in 0..256 {
+            if bam.tick(&mut chain) == Ok(true) {
+                updates[count] = tick;
+                frames[count] = outputs(&chain);
+                count += 1;
+            }
+        }
+
+        assert_eq!(count, 9);
+        assert_eq!(updates, [0, 1, 3, 7, 15, 31, 63, 127, 255]);
+        assert_eq!(frames, [3, 2, 3, 2, 2, 3, 2, 3, 3]);
+
+        bam.reset();
+        assert_eq!(bam.step(&mut chain), 1);
+        assert_eq!(bam.step(&mut chain), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn gamma_end_points() {
+        let curves = [Gamma::Linear, Gamma::Gamma22, Gamma::Gamma28];
+
+        for gamma in curves {
+            for max in [1, 100, 255, 1000, u16::MAX] {
+                assert_eq!(gamma.apply(max, max), max);
+                assert_eq!(gamma.apply(u16::MAX, max), max);
+                assert_eq!(gamma.apply(0, max), 0);
+            }
+
+            assert_eq!(gamma.apply(0, 0), 0);
+        }
+    }
+
+    #[test]
+    fn gamma_is_monotonic() {
+        for gamma in [Gamma::Gamma22, Gamma::Gamma28] {
+            for max in [255, 1000, u16::MAX] {
+                let mut previous = 0;
+
+                for value in 0..=max {
+                    let corrected = gamma.apply(value, max);
+                    assert!(corrected >= previous);
+                    previous = corrected;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gamma_interpolates() {
+        // Table points are hit exactly with 257 values per entry.
+        for index in [0, 1, 100, 254, 255] {
+            assert_eq!(
+                Gamma::Gamma22.apply(index * 257, u16::MAX),
+                GAMMA_2_2[usize::from(index)]
+            );
+            assert_eq!(
+                Gamma::Gamma28.apply(index * 257, u16::MAX),
+                GAMMA_2_8[usize::from(index)]
+            );
+        }
+
+        static RAMP: [u16; 256] = {
+            let mut table = [0; 256];
+            let mut index = 0;
+
+            while index < 256 {
+                table[index] = index as u16 * 257;
+                index += 1;
+            }
+
+            table
+        };
+
+        for value in (0..=u16::MAX).step_by(97) {
+            assert!(Gamma::Table(&RAMP).apply(value, u16::MAX).abs_diff(value) <= 1);
+        }
+
+        assert_eq!(Gamma::Table(&RAMP).apply(50, 100), 50);
+        assert_eq!(Gamma::Linear.apply(200, 100), 100);
+    }
+
+    #[test]
+    fn triangle_breathing() {
+        let mut breathing = Breathing::new(100);
+        breathing.set_waveform(Waveform::Triangle);
+
+        let levels = [0, 25, 50, 75, 99].map(|phase| {
+            breathing.set_phase(phase);
+            breathing.level(1000)
+        });
+
+        assert_eq!(levels, [0, 500, 1000, 500, 20]);
+    }
+
+    #[test]
+    fn sine_breathing() {
+        let mut breathing = Breathing::new(0);
+        assert_eq!(breathing.period(), 1);
+        assert_eq!(breathing.level(255), 0);
+
+        breathing.set_period(200);
+        let mut levels = [0; 200];
+
+        for level in levels.iter_mut() {
+            *level = breathing.level(255);
+            breathing.tick();
+        }
+
+        assert_eq!(breathing.phase(), 0);
+        assert_eq!((levels[0], levels[50], levels[100]), (0, 127, 255));
+        assert!(levels[..=100].windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!((1..100).all(|phase| levels[phase].abs_diff(levels[200 - phase]) <= 1));
+
+        breathing.set_phase(250);
+        assert_eq!(breathing.phase(), 50);
+        breathing.set_period(30);
+        assert_eq!(breathing.phase(), 20);
+    }
+}
@@ -0,0 +1,248 @@
+//! Remapping of logical pin numbers to physical chain positions
+//!
+//! A [`Remapped`] chain wraps another chain and translates the pin numbers
+//! using a [`PinMap`], so application code can address e.g. "button 0..15"
+//! independent of the PCB routing. It implements the same traits as the
+//! wrapped chain, so the `Pin` types and helpers can be used with logical
+//! pin numbers.
+
+#[cfg(feature = "async")]
+use crate::AsyncUpdate;
+use crate::{
+    input::GetInput,
+    output::{GetOutput, SetOutput},
+    Error, Length, Update,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Trait to be implemented by maps translating logical to physical pins.
+pub trait PinMap {
+    /// Returns the number of logical pins.
+    fn pin_count(&self) -> usize;
+
+    /// Returns the physical pin of a logical pin or `None` if not mapped.
+    fn physical_pin(&self, pin: usize) -> Option<usize>;
+}
+
+/// Table with the physical pin for each logical pin.
+impl<const PINS: usize> PinMap for [usize; PINS] {
+    fn pin_count(&self) -> usize {
+        PINS
+    }
+
+    fn physical_pin(&self, pin: usize) -> Option<usize> {
+        self.get(pin).copied()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Map translating the pins by a function.
+pub struct FnMap<F> {
+    /// Number of logical pins.
+    pin_count: usize,
+
+    /// Function returning the physical pin of a logical pin.
+    f: F,
+}
+
+impl<F> FnMap<F>
+where
+    F: Fn(usize) -> usize,
+{
+    /// Creates a new map for `pin_count` logical pins translated by `f`.
+    pub fn new(pin_count: usize, f: F) -> Self {
+        Self { pin_count, f }
+    }
+}
+
+impl<F> PinMap for FnMap<F>
+where
+    F: Fn(usize) -> usize,
+{
+    fn pin_count(&self) -> usize {
+        self.pin_count
+    }
+
+    fn physical_pin(&self, pin: usize) -> Option<usize> {
+        (pin < self.pin_count).then(|| (self.f)(pin))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Chain addressed by logical pin numbers.
+///
+/// The length is the number of logical pins rounded up to whole chips.
+/// Logical pins beyond the map are not connected, reading low and ignoring
+/// writes. Operations on all pins like `set_all_outputs()` apply to the
+/// logical pins only. Physical pins beyond the inputs or outputs of the
+/// wrapped chain, e.g. a dual chain of different lengths, are not connected
+/// in that direction either.
+pub struct Remapped<Chain, Map> {
+    /// Wrapped chain.
+    chain: Chain,
+
+    /// Map translating the pins.
+    map: Map,
+}
+
+impl<Chain, Map> Remapped<Chain, Map>
+where
+    Chain: Length,
+    Map: PinMap,
+{
+    /// Creates a new remapped chain.
+    ///
+    /// Returns an error if a physical pin of the map is out of range for
+    /// the chain.
+    pub fn new(chain: Chain, map: Map) -> Result<Self, Error> {
+        let pins = chain.len() * 8;
+
+        let valid =
+            (0..map.pin_count()).all(|pin| map.physical_pin(pin).is_some_and(|pin| pin < pins));
+
+        if !valid {
+            return Err(Error::PinOutOfRange);
+        }
+
+        Ok(Self { chain, map })
+    }
+}
+
+impl<Chain, Map> Remapped<Chain, Map> {
+    /// Returns the wrapped chain.
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+
+    /// Returns the wrapped chain mutably, e.g. to update it.
+    pub fn chain_mut(&mut self) -> &mut Chain {
+        &mut self.chain
+    }
+
+    /// Returns the map.
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    /// Frees the wrapped chain and the map.
+    pub fn into_parts(self) -> (Chain, Map) {
+        (self.chain, self.map)
+    }
+}
+
+impl<Chain, Map> GetInput for Remapped<Chain, Map>
+where
+    Chain: GetInput + Length,
+    Map: PinMap,
+{
+    fn get_input(&self, pin: usize) -> Result<bool, Error> {
+        let pin = self.map.physical_pin(pin).ok_or(Error::PinOutOfRange)?;
+        self.chain.get_input(pin)
+    }
+
+    fn get_input_unchecked(&self, pin: usize) -> bool {
+        self.map
+            .physical_pin(pin)
+            .filter(|&pin| pin < self.chain.input_len() * 8)
+            .is_some_and(|pin| self.chain.get_input_unchecked(pin))
+    }
+
+    fn read_inputs(&self, data: &mut [u8]) -> Result<(), Error> {
+        if data.len() > self.map.pin_count().div_ceil(8) {
+            return Err(Error::InvalidRange);
+        }
+
+        self.check_inputs()?;
+
+        for (index, byte) in data.iter_mut().enumerate() {
+            *byte = (0..8).fold(0, |value, bit| {
+                if self.get_input_unchecked(index * 8 + bit) {
+                    value | (1 << bit)
+                } else {
+                    value
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    fn check_inputs(&self) -> Result<(), Error> {
+        self.chain.check_inputs()
+    }
+}
+
+impl<Chain, Map> SetOutput for Remapped<Chain, Map>
+where
+    Chain: SetOutput + Length,
+    Map: PinMap,
+{
+    fn set_output(&mut self, pin: usize, state: bool) -> Result<(), Error> {
+        let pin = self.map.physical_pin(pin).ok_or(Error::PinOutOfRange)?;
+        self.chain.set_output(pin, state)
+    }
+
+    fn set_output_unchecked(&mut self, pin: usize, state: bool) {
+        if let Some(pin) = self
+            .map
+            .physical_pin(pin)
+            .filter(|&pin| pin < self.chain.output_len() * 8)
+        {
+            self.chain.set_output_unchecked(pin, state);
+        }
+    }
+}
+
+impl<Chain, Map> GetOutput for Remapped<Chain, Map>
+where
+    Chain: GetOutput + Length,
+    Map: PinMap,
+{
+    fn get_output(&self, pin: usize) -> Result<bool, Error> {
+        let pin = self.map.physical_pin(pin).ok_or(Error::PinOutOfRange)?;
+        self.chain.get_output(pin)
+    }
+
+    fn get_output_unchecked(&self, pin: usize) -> bool {
+        self.map
+            .physical_pin(pin)
+            .filter(|&pin| pin < self.chain.output_len() * 8)
+            .is_some_and(|pin| self.chain.get_output_unchecked(pin))
+    }
+}
+
+impl<Chain, Map> Update for Remapped<Chain, Map>
+where
+    Chain: Update,
+{
+    type Error = Chain::Error;
+
+    fn update(&mut self) -> Result<(), Self::Error> {
+        self.chain.update()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Chain, Map> AsyncUpdate for Remapped<Chain, Map>
+where
+    Chain: AsyncUpdate,
+{
+    type Error = Chain::Error;
+
+    async fn update_async(&mut self) -> Result<(), Self::Error> {
+        self.chain.update_async().await
+    }
+}
+
+impl<Chain, Map> Length for Remapped<Chain, Map>
+where
+    Map: PinMap,
+{
+    /// Returns the number of logical pins rounded up to whole chips.
+    fn len(&self) -> usize {
+        self.map.pin_count().div_ceil(8)
+    }
+}
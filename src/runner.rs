@@ -0,0 +1,125 @@
+//! Runner updating a chain periodically
+
+use embedded_hal::delay::DelayNs;
+
+#[cfg(feature = "async")]
+use crate::AsyncUpdate;
+use crate::Update;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Runner owning a chain and updating it repeatedly with a fixed delay in
+/// between.
+///
+/// The delay is waited for after each update and the closure of `run()`, so
+/// the update period is the interval plus their duration. Updates at a fixed
+/// rate require a timer, e.g. calling `update()` from its interrupt.
+///
+/// The latest input states and the output commands are accessed via the chain
+/// returned by `chain()` and `chain_mut()`.
+pub struct Runner<Chain, Delay> {
+    /// Chain to be updated.
+    chain: Chain,
+
+    /// Delay used to wait between the updates.
+    delay: Delay,
+
+    /// Delay between the updates in nanoseconds.
+    interval_ns: u32,
+}
+
+impl<Chain, Delay> Runner<Chain, Delay> {
+    /// Creates a new runner by consuming a chain and a delay.
+    pub fn new(chain: Chain, delay: Delay, interval_ns: u32) -> Self {
+        Self {
+            chain,
+            delay,
+            interval_ns,
+        }
+    }
+
+    /// Frees the runner and returns the chain and the delay.
+    pub fn free(self) -> (Chain, Delay) {
+        (self.chain, self.delay)
+    }
+
+    /// Returns a reference to the chain.
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+
+    /// Returns a mutable reference to the chain.
+    pub fn chain_mut(&mut self) -> &mut Chain {
+        &mut self.chain
+    }
+
+    /// Sets the delay between the updates in nanoseconds.
+    pub fn set_interval_ns(&mut self, interval_ns: u32) {
+        self.interval_ns = interval_ns;
+    }
+}
+
+impl<Chain, Delay> Runner<Chain, Delay>
+where
+    Chain: Update,
+    Delay: DelayNs,
+{
+    /// Updates the chain once and waits for the interval afterwards.
+    ///
+    /// The interval is also waited for if the update failed.
+    pub fn tick(&mut self) -> Result<(), Chain::Error> {
+        let result = self.chain.update();
+        self.delay.delay_ns(self.interval_ns);
+        result
+    }
+
+    /// Updates the chain forever, calling `f` after each update and waiting
+    /// for the interval afterwards.
+    ///
+    /// The closure can read the input states and set the outputs that will
+    /// be shifted out by the next update. It also receives the result of the
+    /// update to handle transport errors.
+    pub fn run<F>(mut self, mut f: F) -> !
+    where
+        F: FnMut(&mut Chain, Result<(), Chain::Error>),
+    {
+        loop {
+            let result = self.chain.update();
+            f(&mut self.chain, result);
+            self.delay.delay_ns(self.interval_ns);
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Chain, Delay> Runner<Chain, Delay>
+where
+    Chain: AsyncUpdate,
+    Delay: embedded_hal_async::delay::DelayNs,
+{
+    /// Updates the chain once and waits for the interval afterwards, both
+    /// asynchronously.
+    ///
+    /// The interval is also waited for if the update failed.
+    pub async fn tick_async(&mut self) -> Result<(), Chain::Error> {
+        let result = self.chain.update_async().await;
+        self.delay.delay_ns(self.interval_ns).await;
+        result
+    }
+
+    /// Updates the chain forever, calling `f` after each update and waiting
+    /// for the interval afterwards, both asynchronously.
+    ///
+    /// The closure also receives the result of the update to handle
+    /// transport errors.
+    pub async fn run_async<F>(mut self, mut f: F) -> !
+    where
+        F: FnMut(&mut Chain, Result<(), Chain::Error>),
+    {
+        loop {
+            let result = self.chain.update_async().await;
+            f(&mut self.chain, result);
+            self.delay.delay_ns(self.interval_ns).await;
+        }
+    }
+}
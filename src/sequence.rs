@@ -0,0 +1,205 @@
+//! Playback of output frame sequences
+//!
+//! A [`Sequencer`] plays a list of [`Frame`]s on an output chain, e.g. light
+//! chasers, boot animations or signalling patterns. Each call of
+//! [`Sequencer::tick()`] advances the playback by one tick and writes the
+//! pins of a frame into the chain buffer when it becomes due. Combined with
+//! `update_if_dirty()`, the chain is only shifted when the frame changes.
+
+use crate::{output::SetOutput, Error};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Frame of a sequence, setting some or all pins for a number of ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Frame<'a> {
+    /// Pin states, with bit `n` of byte `i` being the state of pin
+    /// `i * 8 + n`.
+    pub states: &'a [u8],
+
+    /// Pins set by the frame in the same layout, all pins of `states` if
+    /// empty. Other pins keep their states.
+    pub mask: &'a [u8],
+
+    /// Ticks the frame is shown, at least 1.
+    pub duration: u32,
+}
+
+impl<'a> Frame<'a> {
+    /// Creates a frame setting all pins of `states`.
+    pub const fn new(states: &'a [u8], duration: u32) -> Self {
+        Self {
+            states,
+            mask: &[],
+            duration,
+        }
+    }
+
+    /// Creates a frame setting only the pins of `states` selected by
+    /// `mask`.
+    pub const fn partial(states: &'a [u8], mask: &'a [u8], duration: u32) -> Self {
+        Self {
+            states,
+            mask,
+            duration,
+        }
+    }
+
+    /// Writes the pins of the frame into the chain buffer.
+    ///
+    /// Returns an error if a pin is out of range for the chain.
+    pub fn apply(&self, chain: &mut (impl SetOutput + ?Sized)) -> Result<(), Error> {
+        for (index, &states) in self.states.iter().enumerate() {
+            let mask = match self.mask {
+                [] => 0xFF,
+                mask => mask.get(index).copied().unwrap_or(0),
+            };
+
+            for bit in 0..8 {
+                if mask & (1 << bit) != 0 {
+                    chain.set_output(index * 8 + bit, states & (1 << bit) != 0)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Sequencer playing a list of frames.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sequencer<'a> {
+    /// Frames of the sequence.
+    frames: &'a [Frame<'a>],
+
+    /// Frame being shown.
+    index: usize,
+
+    /// Ticks until the next frame is due, 0 if the current frame is not
+    /// shown yet.
+    remaining: u32,
+
+    /// Whether the sequence restarts after the last frame.
+    looping: bool,
+
+    /// Whether the sequence is playing.
+    running: bool,
+}
+
+impl<'a> Sequencer<'a> {
+    /// Creates a new sequencer playing `frames` in a loop, starting with
+    /// the first tick.
+    pub const fn new(frames: &'a [Frame<'a>]) -> Self {
+        Self {
+            frames,
+            index: 0,
+            remaining: 0,
+            looping: true,
+            running: true,
+        }
+    }
+
+    /// Returns the frames of the sequence.
+    pub fn frames(&self) -> &'a [Frame<'a>] {
+        self.frames
+    }
+
+    /// Replaces the frames of the sequence and restarts it.
+    pub fn set_frames(&mut self, frames: &'a [Frame<'a>]) {
+        self.frames = frames;
+        self.restart();
+    }
+
+    /// Returns `true` if the sequence restarts after the last frame.
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Sets whether the sequence restarts after the last frame, enabled by
+    /// default.
+    ///
+    /// Without looping, the playback stops after the last frame was shown
+    /// for its duration, keeping its pin states.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Returns `true` if the sequence is playing.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Returns the index of the frame being shown, `None` if stopped or not
+    /// shown yet.
+    pub fn frame_index(&self) -> Option<usize> {
+        (self.running && self.remaining > 0).then_some(self.index)
+    }
+
+    /// Restarts the sequence with the first frame on the next tick.
+    pub fn restart(&mut self) {
+        self.index = 0;
+        self.remaining = 0;
+        self.running = true;
+    }
+
+    /// Stops the sequence, keeping the pin states of the current frame.
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    /// Resumes a stopped sequence at the current frame.
+    pub fn resume(&mut self) {
+        self.running = true;
+    }
+
+    /// Advances the playback by one tick and writes the pins of the next
+    /// frame into the chain buffer if it became due.
+    ///
+    /// Intended to be called periodically, e.g. before each update of the
+    /// chain. Returns `true` if a frame was written. Returns an error if a
+    /// pin of the frame is out of range, the playback is stopped in this
+    /// case.
+    pub fn tick(&mut self, chain: &mut (impl SetOutput + ?Sized)) -> Result<bool, Error> {
+        if !self.running {
+            return Ok(false);
+        }
+
+        if self.remaining > 0 {
+            self.remaining -= 1;
+
+            if self.remaining > 0 {
+                return Ok(false);
+            }
+
+            self.index += 1;
+
+            if self.index >= self.frames.len() {
+                if !self.looping {
+                    self.index = self.frames.len().saturating_sub(1);
+                    self.running = false;
+                    return Ok(false);
+                }
+
+                self.index = 0;
+            }
+        }
+
+        let Some(frame) = self.frames.get(self.index) else {
+            self.running = false;
+            return Ok(false);
+        };
+
+        if let Err(error) = frame.apply(chain) {
+            self.running = false;
+            return Err(error);
+        }
+
+        self.remaining = frame.duration.max(1);
+
+        Ok(true)
+    }
+}
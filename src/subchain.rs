@@ -0,0 +1,184 @@
+//! Windowed views of a contiguous pin range of a chain
+//!
+//! A [`SubChain`] exposes a range of pins of a shared chain with its own
+//! numbering starting at 0, so e.g. a driver for one daughterboard can be
+//! handed pins 16..32 without knowing about the rest of the chain. Several
+//! views can share the same chain, which is updated by its owner.
+
+use core::{cell::RefCell, ops::Range};
+
+use crate::{
+    input::GetInput,
+    output::{GetOutput, SetOutput},
+    Error, Length,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// View of a contiguous pin range of a chain.
+///
+/// The length is the number of pins in the range rounded up to whole chips.
+/// Pins beyond the range are not connected, reading low and ignoring writes.
+/// Operations on all pins like `set_all_outputs()` only apply to the range.
+pub struct SubChain<'a, Chain> {
+    /// Reference to the chain.
+    chain: &'a RefCell<Chain>,
+
+    /// Pins of the chain exposed by the view.
+    range: Range<usize>,
+}
+
+impl<'a, Chain> SubChain<'a, Chain>
+where
+    Chain: Length,
+{
+    /// Creates a new view of the pins in `range`.
+    ///
+    /// Returns an error if the range is reversed or exceeds the chain.
+    pub fn new(chain: &'a RefCell<Chain>, range: Range<usize>) -> Result<Self, Error> {
+        if range.start > range.end || range.end > chain.borrow().len() * 8 {
+            return Err(Error::PinOutOfRange);
+        }
+
+        Ok(Self { chain, range })
+    }
+}
+
+impl<Chain> SubChain<'_, Chain> {
+    /// Returns the pins of the chain exposed by the view.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the number of pins in the range.
+    pub fn pin_count(&self) -> usize {
+        self.range.len()
+    }
+
+    /// Returns the pin of the chain for a pin of the view or `None` if it is
+    /// beyond the range.
+    fn chain_pin(&self, pin: usize) -> Option<usize> {
+        (pin < self.range.len()).then(|| self.range.start + pin)
+    }
+}
+
+impl<Chain> SubChain<'_, Chain>
+where
+    Chain: Length,
+{
+    /// Returns the number of pins of the view below `chips` chips of the
+    /// chain.
+    fn pins_below(&self, chips: usize) -> usize {
+        self.range
+            .end
+            .min(chips * 8)
+            .saturating_sub(self.range.start)
+    }
+
+    /// Returns the pin of the chain for an input pin of the view or `None`
+    /// if it is beyond the range or the inputs of the chain.
+    fn chain_input_pin(&self, pin: usize) -> Option<usize> {
+        let pins = self.pins_below(self.chain.borrow().input_len());
+        (pin < pins).then(|| self.range.start + pin)
+    }
+
+    /// Returns the pin of the chain for an output pin of the view or `None`
+    /// if it is beyond the range or the outputs of the chain.
+    fn chain_output_pin(&self, pin: usize) -> Option<usize> {
+        let pins = self.pins_below(self.chain.borrow().output_len());
+        (pin < pins).then(|| self.range.start + pin)
+    }
+}
+
+impl<Chain> GetInput for SubChain<'_, Chain>
+where
+    Chain: GetInput + Length,
+{
+    fn get_input(&self, pin: usize) -> Result<bool, Error> {
+        let pin = self.chain_pin(pin).ok_or(Error::PinOutOfRange)?;
+        self.chain.borrow().get_input(pin)
+    }
+
+    fn get_input_unchecked(&self, pin: usize) -> bool {
+        self.chain_input_pin(pin)
+            .is_some_and(|pin| self.chain.borrow().get_input_unchecked(pin))
+    }
+
+    fn read_inputs(&self, data: &mut [u8]) -> Result<(), Error> {
+        if data.len() > self.input_len() {
+            return Err(Error::InvalidRange);
+        }
+
+        self.check_inputs()?;
+
+        for (index, byte) in data.iter_mut().enumerate() {
+            *byte = (0..8).fold(0, |value, bit| {
+                if self.get_input_unchecked(index * 8 + bit) {
+                    value | (1 << bit)
+                } else {
+                    value
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    fn check_inputs(&self) -> Result<(), Error> {
+        self.chain.borrow().check_inputs()
+    }
+}
+
+impl<Chain> SetOutput for SubChain<'_, Chain>
+where
+    Chain: SetOutput + Length,
+{
+    fn set_output(&mut self, pin: usize, state: bool) -> Result<(), Error> {
+        let pin = self.chain_pin(pin).ok_or(Error::PinOutOfRange)?;
+        self.chain.borrow_mut().set_output(pin, state)
+    }
+
+    fn set_output_unchecked(&mut self, pin: usize, state: bool) {
+        if let Some(pin) = self.chain_output_pin(pin) {
+            self.chain.borrow_mut().set_output_unchecked(pin, state);
+        }
+    }
+}
+
+impl<Chain> GetOutput for SubChain<'_, Chain>
+where
+    Chain: GetOutput + Length,
+{
+    fn get_output(&self, pin: usize) -> Result<bool, Error> {
+        let pin = self.chain_pin(pin).ok_or(Error::PinOutOfRange)?;
+        self.chain.borrow().get_output(pin)
+    }
+
+    fn get_output_unchecked(&self, pin: usize) -> bool {
+        self.chain_output_pin(pin)
+            .is_some_and(|pin| self.chain.borrow().get_output_unchecked(pin))
+    }
+}
+
+impl<Chain> Length for SubChain<'_, Chain>
+where
+    Chain: Length,
+{
+    /// Returns the number of pins in the range rounded up to whole chips.
+    fn len(&self) -> usize {
+        self.range.len().div_ceil(8)
+    }
+
+    /// Returns the number of input pins in the range rounded up to whole
+    /// chips.
+    fn input_len(&self) -> usize {
+        self.pins_below(self.chain.borrow().input_len()).div_ceil(8)
+    }
+
+    /// Returns the number of output pins in the range rounded up to whole
+    /// chips.
+    fn output_len(&self) -> usize {
+        self.pins_below(self.chain.borrow().output_len())
+            .div_ceil(8)
+    }
+}
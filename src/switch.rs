@@ -0,0 +1,179 @@
+//! Integration with the `switch-hal` crate
+//!
+//! The `Pin` types implement the `embedded-hal` 0.2 traits required by
+//! `switch_hal::Switch`, so they can be turned into switches directly using
+//! `switch_hal::IntoSwitch`. The types in this module add debouncing for
+//! buttons connected to input chains.
+
+use core::cell::Cell;
+
+use embedded_hal_02::digital::v2::InputPin;
+use switch_hal::{ActiveHigh, ActiveLow, Switch};
+
+use crate::input::{self, GetInput};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Button connected to an input chain, pressed when the input is high.
+pub type ActiveHighButton<'a, Chain> = Switch<Debounced<input::Pin<'a, Chain>>, ActiveHigh>;
+
+/// Button connected to an input chain, pressed when the input is low.
+pub type ActiveLowButton<'a, Chain> = Switch<Debounced<input::Pin<'a, Chain>>, ActiveLow>;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Input pin debounced by integrating its samples.
+///
+/// Each read takes a sample of the wrapped pin, counting the integrator up
+/// on a high and down on a low sample, between 0 and `samples`. The reported
+/// state becomes high when the count reaches `samples` and low when it
+/// reaches 0, so a bounce delays a state change instead of restarting it.
+/// The pin should be read exactly once after each chain update.
+pub struct Debounced<Pin> {
+    /// Wrapped pin.
+    pin: Pin,
+
+    /// Number of samples required for a state change.
+    samples: u8,
+
+    /// Integrator counting from 0 (low) to `samples` (high).
+    count: Cell<u8>,
+
+    /// Debounced state.
+    state: Cell<bool>,
+}
+
+impl<Pin> Debounced<Pin> {
+    /// Creates a new debounced pin with an initial low state.
+    ///
+    /// `samples` is clamped to a minimum of 1.
+    pub fn new(pin: Pin, samples: u8) -> Self {
+        Self::with_state(pin, samples, false)
+    }
+
+    /// Creates a new debounced pin with an initial state.
+    ///
+    /// `samples` is clamped to a minimum of 1.
+    pub fn with_state(pin: Pin, samples: u8, state: bool) -> Self {
+        let samples = samples.max(1);
+
+        Self {
+            pin,
+            samples,
+            count: Cell::new(if state { samples } else { 0 }),
+            state: Cell::new(state),
+        }
+    }
+
+    /// Returns the wrapped pin.
+    pub fn into_pin(self) -> Pin {
+        self.pin
+    }
+}
+
+impl<Pin> Debounced<Pin>
+where
+    Pin: InputPin,
+{
+    /// Samples the wrapped pin and returns the debounced state.
+    fn sample(&self) -> Result<bool, Pin::Error> {
+        let count = if self.pin.is_high()? {
+            self.count.get().saturating_add(1).min(self.samples)
+        } else {
+            self.count.get().saturating_sub(1)
+        };
+        self.count.set(count);
+
+        if count == self.samples {
+            self.state.set(true);
+        } else if count == 0 {
+            self.state.set(false);
+        }
+
+        Ok(self.state.get())
+    }
+}
+
+impl<Pin> InputPin for Debounced<Pin>
+where
+    Pin: InputPin,
+{
+    type Error = Pin::Error;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.sample()
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.sample().map(|state| !state)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl<'a, Chain> input::Pin<'a, Chain>
+where
+    Chain: GetInput,
+{
+    /// Converts the pin into a debounced button pressed when the input is high.
+    ///
+    /// The button is initially released.
+    pub fn into_active_high_button(self, samples: u8) -> ActiveHighButton<'a, Chain> {
+        Switch::new(Debounced::with_state(self, samples, false))
+    }
+
+    /// Converts the pin into a debounced button pressed when the input is low.
+    ///
+    /// The button is initially released.
+    pub fn into_active_low_button(self, samples: u8) -> ActiveLowButton<'a, Chain> {
+        Switch::new(Debounced::with_state(self, samples, true))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use super::*;
+
+    /// Pin returning a sequence of samples, high after the last one.
+    struct Samples<const N: usize> {
+        samples: [bool; N],
+        index: Cell<usize>,
+    }
+
+    impl<const N: usize> InputPin for Samples<N> {
+        type Error = Infallible;
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            let index = self.index.get();
+            self.index.set(index + 1);
+            Ok(self.samples.get(index).copied().unwrap_or(true))
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            self.is_high().map(|state| !state)
+        }
+    }
+
+    #[test]
+    fn integrator_tolerates_bounces() {
+        let samples = [true, true, false, true, true, false, false, false];
+        let pin = Debounced::new(
+            Samples {
+                samples,
+                index: Cell::new(0),
+            },
+            3,
+        );
+
+        // Counts 1, 2, 1, 2, 3, 2, 1, 0
+        let states = samples.map(|_| pin.is_high().unwrap_or_default());
+        assert_eq!(
+            states,
+            [false, false, false, false, true, true, true, false]
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Transports shifting the data from and into the chips
+
+use core::{convert::Infallible, fmt};
+
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{ErrorType, OutputPin},
+};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Trait to be implemented by transports that physically shift the data.
+///
+/// A transport is responsible for the complete transfer sequence including
+/// the latch or load signals of the chips. Bytes are shifted MSB first with
+/// the first byte of `write` ending up in the chip farthest away and the
+/// first byte of `read` coming from the chip nearest to the controller.
+///
+/// Transports that only shift in one direction ignore the other buffer.
+pub trait ShiftTransport {
+    /// Error type of the underlying pins or bus.
+    type Error;
+
+    /// Shifts the data from the chips into `read` and from `write` into the
+    /// chips simultaneously.
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Trait to be implemented by transports that can shift the data bit by bit.
+///
+/// This allows splitting a transfer into several steps, e.g. to meet the time
+/// constraints of a main loop.
+pub trait StepTransport {
+    /// Error type of the underlying pins.
+    type Error;
+
+    /// Begins a transfer, e.g. by loading the parallel inputs.
+    fn begin(&mut self) -> Result<(), Self::Error>;
+
+    /// Shifts a single bit out and returns the bit shifted in simultaneously.
+    ///
+    /// Transports that only shift in one direction ignore `state` or
+    /// return `false`.
+    fn shift_bit(&mut self, state: bool) -> Result<bool, Self::Error>;
+
+    /// Ends a transfer, e.g. by latching the outputs.
+    fn end(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Asynchronous variant of [`ShiftTransport`].
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncShiftTransport {
+    /// Error type of the underlying pins or bus.
+    type Error;
+
+    /// Shifts the data from the chips into `read` and from `write` into the
+    /// chips simultaneously.
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error>;
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Clock edge on which the chips shift.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClockEdge {
+    /// Low to high transition, as used by the 74HC165 and 74HC595.
+    #[default]
+    Rising,
+
+    /// High to low transition, e.g. for clock lines inverted by an
+    /// optocoupler or a transistor.
+    Falling,
+}
+
+/// Clock configuration of the bit-banged transports.
+///
+/// The data is presented and sampled before the active edge. The default
+/// matches the 74HC165 and 74HC595 with the clock idling high between bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClockConfig {
+    /// Edge on which the chips shift.
+    pub edge: ClockEdge,
+
+    /// Level of the clock between the bits and transfers.
+    pub idle_high: bool,
+}
+
+impl ClockConfig {
+    /// Returns whether the clock is high after the active edge.
+    fn active_high(self) -> bool {
+        self.edge == ClockEdge::Rising
+    }
+
+    /// Sets the clock to its idle level.
+    pub(crate) fn idle<Pin: OutputPin>(self, clock_pin: &mut Pin) -> Result<(), Pin::Error> {
+        set_level(clock_pin, self.idle_high)
+    }
+
+    /// Sets the clock to the level before the active edge, if not already
+    /// idling at it.
+    pub(crate) fn before_edge<Pin: OutputPin>(self, clock_pin: &mut Pin) -> Result<(), Pin::Error> {
+        if self.idle_high == self.active_high() {
+            set_level(clock_pin, !self.active_high())?;
+        }
+
+        Ok(())
+    }
+
+    /// Generates the active edge.
+    pub(crate) fn edge<Pin: OutputPin>(self, clock_pin: &mut Pin) -> Result<(), Pin::Error> {
+        set_level(clock_pin, self.active_high())
+    }
+
+    /// Returns the clock to the idle level after the active edge, if not
+    /// idling at the level after it.
+    pub(crate) fn after_edge<Pin: OutputPin>(self, clock_pin: &mut Pin) -> Result<(), Pin::Error> {
+        if self.idle_high != self.active_high() {
+            set_level(clock_pin, self.idle_high)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            edge: ClockEdge::Rising,
+            idle_high: true,
+        }
+    }
+}
+
+/// Delay doing nothing, used by the bit-banged transports by default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NoDelay;
+
+impl DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// Placeholder for an optional control pin that is not connected.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Settle time of the signals of the bit-banged transports.
+pub(crate) struct Settle<Delay> {
+    /// Delay used to wait.
+    delay: Delay,
+
+    /// Time to wait in nanoseconds, 0 to skip the delay.
+    ns: u32,
+}
+
+impl<Delay> Settle<Delay>
+where
+    Delay: DelayNs,
+{
+    /// Creates a new settle time.
+    pub(crate) fn new(delay: Delay, ns: u32) -> Self {
+        Self { delay, ns }
+    }
+
+    /// Waits for the signals to settle.
+    pub(crate) fn wait(&mut self) {
+        if self.ns > 0 {
+            self.delay.delay_ns(self.ns);
+        }
+    }
+}
+
+impl<Delay> Settle<Delay> {
+    /// Returns the settle time in nanoseconds.
+    pub(crate) fn ns(&self) -> u32 {
+        self.ns
+    }
+
+    /// Sets the settle time in nanoseconds.
+    pub(crate) fn set_ns(&mut self, ns: u32) {
+        self.ns = ns;
+    }
+
+    /// Frees the delay.
+    pub(crate) fn into_delay(self) -> Delay {
+        self.delay
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Polarity of the latch or load signal.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LatchPolarity {
+    /// The pin drives the signal of the chips directly.
+    #[default]
+    Normal,
+
+    /// The signal is inverted between the pin and the chips, e.g. by a
+    /// transistor.
+    Inverted,
+}
+
+impl LatchPolarity {
+    /// Sets the pin to drive the signal of the chips to a level.
+    pub(crate) fn set<Pin: OutputPin>(self, pin: &mut Pin, high: bool) -> Result<(), Pin::Error> {
+        set_level(pin, high != (self == Self::Inverted))
+    }
+}
+
+/// Sets an output pin to a level.
+pub(crate) fn set_level<Pin: OutputPin>(pin: &mut Pin, high: bool) -> Result<(), Pin::Error> {
+    if high {
+        pin.set_high()
+    } else {
+        pin.set_low()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Error of the SPI transports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpiError<SpiE, PinE> {
+    /// Error of the SPI bus or device.
+    Spi(SpiE),
+
+    /// Error of the latch or load pin.
+    Pin(PinE),
+}
+
+impl<SpiE, PinE> fmt::Display for SpiError<SpiE, PinE>
+where
+    SpiE: fmt::Display,
+    PinE: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spi(error) => write!(f, "SPI error: {error}"),
+            Self::Pin(error) => write!(f, "pin error: {error}"),
+        }
+    }
+}
+
+impl<SpiE, PinE> core::error::Error for SpiError<SpiE, PinE>
+where
+    SpiE: core::error::Error + 'static,
+    PinE: core::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Spi(error) => Some(error),
+            Self::Pin(error) => Some(error),
+        }
+    }
+}
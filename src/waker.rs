@@ -0,0 +1,124 @@
+//! Registration of tasks waiting for chain updates
+
+use core::task::Waker;
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Maximum number of tasks waiting at the same time.
+const CAPACITY: usize = 4;
+
+/// Storage for the wakers of several tasks, all woken by `wake()`.
+///
+/// Registered wakers are never displaced. If all slots are taken, the waker
+/// to register is woken right away instead, so its task polls again until a
+/// slot is free.
+#[derive(Debug, Clone)]
+pub(crate) struct WakerRegistration {
+    /// Registered wakers.
+    wakers: [Option<Waker>; CAPACITY],
+}
+
+impl WakerRegistration {
+    /// Creates a new empty registration.
+    pub(crate) const fn new() -> Self {
+        Self {
+            wakers: [const { None }; CAPACITY],
+        }
+    }
+
+    /// Registers a waker unless an equivalent one is already registered.
+    pub(crate) fn register(&mut self, waker: &Waker) {
+        if self
+            .wakers
+            .iter()
+            .flatten()
+            .any(|registered| registered.will_wake(waker))
+        {
+            return;
+        }
+
+        match self.wakers.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => *slot = Some(waker.clone()),
+            None => waker.wake_by_ref(),
+        }
+    }
+
+    /// Wakes all registered wakers.
+    pub(crate) fn wake(&mut self) {
+        for waker in self.wakers.iter_mut().filter_map(Option::take) {
+            waker.wake();
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::Wake,
+    };
+
+    use super::*;
+
+    /// Waker counting its wake-ups.
+    #[derive(Default)]
+    struct Counter(AtomicUsize);
+
+    impl Wake for Counter {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    impl Counter {
+        fn count(&self) -> usize {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn several_tasks_do_not_wake_each_other() {
+        let counters: [Arc<Counter>; 2] = Default::default();
+        let wakers = counters.clone().map(Waker::from);
+        let mut registration = WakerRegistration::new();
+
+        for _ in 0..3 {
+            registration.register(&wakers[0]);
+            registration.register(&wakers[1]);
+        }
+
+        assert_eq!(counters.each_ref().map(|counter| counter.count()), [0, 0]);
+
+        registration.wake();
+        registration.wake();
+
+        assert_eq!(counters.each_ref().map(|counter| counter.count()), [1, 1]);
+    }
+
+    #[test]
+    fn full_registration_wakes_new_waker() {
+        let counters: [Arc<Counter>; CAPACITY + 1] = Default::default();
+        let wakers = counters.clone().map(Waker::from);
+        let mut registration = WakerRegistration::new();
+
+        for waker in wakers.iter() {
+            registration.register(waker);
+        }
+
+        assert_eq!(counters[CAPACITY].count(), 1);
+        assert!(counters[..CAPACITY]
+            .iter()
+            .all(|counter| counter.count() == 0));
+
+        registration.wake();
+
+        assert!(counters.iter().all(|counter| counter.count() == 1));
+    }
+}